@@ -4,78 +4,535 @@
 //!
 //! ```rust,ignore
 //! #[webtau::command]
-//! fn name(state: &T | &mut T [, arg: Type]*) [-> ReturnType] { body }
+//! fn name([state: &T | &mut T,]* [arg: Type],*) [-> ReturnType] { body }
 //! ```
 //!
 //! **Supported grammar:**
-//! - First parameter **must** be a reference: `name: &T` (read-only) or `name: &mut T` (mutable).
-//!   The identifier can be any name (e.g., `state`, `world`, `game`).
-//! - Additional parameters are named, typed values forwarded as the command's args.
+//! - Leading reference parameters receive managed state: `name: &T` (read-only)
+//!   or `name: &mut T` (mutable). The identifier can be any name (e.g., `state`,
+//!   `world`, `game`). A command may borrow several distinct state types
+//!   (`world: &mut World, profile: &Profile`). Commands without any are
+//!   stateless and never touch `State<Mutex<T>>` or `wasm_state!`.
+//! - Remaining parameters are named, typed values forwarded as the command's args.
+//! - Parameters typed `webtau::Emitter` (matched by the last path segment,
+//!   `Emitter`) are supplied by the wrappers and are not part of the args
+//!   object: natively an emitter over the command's `tauri::AppHandle`, on
+//!   WASM one dispatching `CustomEvent`s on `window`, and under
+//!   `webtau::Router` one whose events a `Harness` collects.
+//! - A `webtau::TaskContext` parameter is supplied the same way, and only
+//!   to `#[command(task)]` commands.
+//! - A `webtau::Channel<T>` parameter (matched by the last path segment,
+//!   `Channel<..>`) stays in the args object, where `invoke()` puts the
+//!   frontend's `Channel`. The native wrapper takes it as a
+//!   `tauri::ipc::Channel`, the WASM wrapper as the callback function, and
+//!   `webtau::Router` sends its items to the `Harness`.
+//! - A `webtau::Cancel` parameter (matched by the last path segment,
+//!   `Cancel`) also stays in the args object, as the frontend's optional
+//!   `AbortSignal`. The native wrapper takes the number `invoke()` sends in
+//!   its place and a generated async `cancel_<name>({ id })` command
+//!   cancels it; the WASM wrapper reads the signal itself. A cancelled or
+//!   timed-out command fails with a `CANCELLED` or `TIMEOUT` diagnostic.
 //! - Return type may be:
 //!   - `T` where `T: Serialize` — value returned directly.
-//!   - `Result<T, E>` where `T: Serialize, E: Display + Serialize` — errors surface to JS.
+//!   - `Result<T, E>` where `T: Serialize` and `E: Serialize` and/or
+//!     `E: Display` — errors surface to JS as a `COMMAND_ERROR`
+//!     `webtau::Diagnostic` whose `details` hold the serialized `E`.
+//!     Single-parameter aliases named `Result` (`anyhow::Result<T>`,
+//!     `std::io::Result<T>`, a crate-local `Result<T>`) are recognised too;
+//!     other aliases of `Result` need `#[command(fallible)]`.
 //!   - Omitted (unit `()`) — command returns nothing.
-//! - The function name becomes the command name for `invoke()`.
+//! - The function name becomes the command name for `invoke()`, unless
+//!   overridden with `name` (see below).
+//! - The function may be `async`; the wrappers then await it on both targets.
+//!
+//! **Attribute arguments** (all optional):
+//! - `name = "tick_world"` — command name on both targets: a Rust identifier,
+//!   or dot-separated identifiers such as `"world.tick"`. The generated
+//!   wrappers are named with the dots replaced by underscores (`world_tick`);
+//!   the Tauri command is renamed back with `#[tauri::command(rename)]`, and
+//!   `invoke()` maps the dots when it looks up the WASM export.
+//! - `js_name = "tickWorld"` — WASM export name only
+//!   (`#[wasm_bindgen(js_name = ...)]`). The Tauri command keeps `name`.
+//! - `fallible` — treat the return type as a `Result` alias the macro cannot
+//!   recognise by name (`AppResult<T>`, `type Saved = Result<Save, E>`).
+//! - `err = Type` — implies `fallible` and pins the error type, for aliases
+//!   whose error type should be checked at the command.
+//! - `rename_all = "snake_case" | "camelCase"` — casing of argument keys in
+//!   the `invoke()` payload, applied to `#[tauri::command(rename_all)]` and
+//!   to the WASM args struct's `#[serde(rename_all)]`. Defaults to
+//!   `"snake_case"`.
+//! - `task` — run the body in the background: instead of one command, generate
+//!   `start_<name>` (the args, returning a task id), `poll_<name>({ id })`
+//!   (a `webtau::task::TaskStatus`) and `cancel_<name>({ id })`. Natively
+//!   `start_` spawns the body on a `spawn_blocking` thread that locks the
+//!   state mutexes; on WASM it spawns it on the event loop from the next
+//!   turn, borrowing the state cells until it returns. Under
+//!   `webtau::Router`, `start_` runs the body to completion. Not combinable
+//!   with `js_name`.
+//! - `timeout_ms = 2000` — deadline after which the command's
+//!   `webtau::Cancel` reports it cancelled; requires that parameter. The
+//!   timeout is cooperative: nothing interrupts the body, so one that never
+//!   checks the `Cancel` runs to completion, and only its result is dropped
+//!   for a `TIMEOUT` diagnostic.
+//! - `blocking` — make the Tauri wrapper `async` and run a synchronous body,
+//!   state locking included, on a `spawn_blocking` thread instead of the
+//!   main thread, like an async command's. A panicking body fails the
+//!   command with a `COMMAND_ERROR` diagnostic carrying the panic message.
+//!   The WASM wrapper is unchanged. Not combinable with `task` or
+//!   `async fn`.
+//! - `state = "rival"` — on WASM, borrow the `wasm_state!(rival: T)`
+//!   instance rather than the only one of its type; for a command with
+//!   several state parameters, `state(profile = "rival", ...)` names them by
+//!   parameter. Needed when a type is declared under several names, which
+//!   otherwise fails with `STATE_AMBIGUOUS`. `wasm_state!(T)` is named
+//!   `state`. Native wrappers ignore it: Tauri manages one state per type.
 //!
 //! **Generated code:**
 //! - Inner function `__webtau_<name>` containing the original body.
-//! - `#[cfg(not(wasm32))]` — `#[tauri::command]` wrapper with `State<Mutex<T>>`.
-//! - `#[cfg(wasm32)]` — `#[wasm_bindgen]` wrapper with args-object deserialize.
+//! - `#[cfg(not(wasm32))]` — `#[tauri::command]` wrapper with one `State<Mutex<T>>`
+//!   per state parameter (none for stateless commands), plus an `AppHandle`
+//!   when the command takes an `Emitter`.
+//! - `#[cfg(wasm32)]` — `#[wasm_bindgen]` wrapper with args-object deserialize,
+//!   exported from a hidden module (`wasm_bindgen` needs a `pub` fn) and
+//!   re-exported at the command's visibility.
+//! - `#[cfg(not(wasm32))]` — a registry entry, collected with `inventory`,
+//!   that lets `webtau::generate_handler!` dispatch to the Tauri wrapper by
+//!   command name, plus one per state type that lets
+//!   `webtau::events::listen` handlers lock the managed state.
+//! - `#[cfg(not(wasm32))]` — a JSON entry point for `webtau::Router`, which
+//!   deserializes the args object like the WASM wrapper and calls the inner
+//!   function. Commands borrowing several state types take them as one
+//!   tuple, in parameter order.
+//! - Commands borrowing any state mutably append their name, args and
+//!   outcome to a running `webtau::replay` recorder while their state is
+//!   held, on both targets. A single flag check when no recorder runs.
+//! - `#[cfg(not(wasm32))]` — the command's TypeScript signature (args object
+//!   keyed by `rename_all` casing, success type), collected with `inventory`
+//!   for `webtau::ts::export_commands()`. Types without a
+//!   `webtau::ts::TypeScript` impl are declared `unknown`.
+//!
+//! **Attributes and visibility:**
+//! - The wrappers keep the function's declared visibility (`pub`,
+//!   `pub(crate)`, private).
+//! - `#[cfg]`, `#[cfg_attr]` and lint attributes (`#[allow]`, `#[deny]`, ...)
+//!   apply to the inner function and both wrappers, so
+//!   `#[cfg(feature = "dev")]` gates the whole command.
+//! - Doc comments and `#[deprecated]` go on the wrappers, where callers and
+//!   `wasm-bindgen`'s generated typings see them.
+//! - Any other attribute stays on the inner function with the body.
+//!
+//! **Errors:**
+//! - WASM wrappers throw a `webtau::Diagnostic` object for args, state,
+//!   serialization and command failures.
+//! - Native wrappers of `Result` commands return
+//!   `Result<T, webtau::Diagnostic>`, which Tauri serializes into the rejection.
+//!
+//! **Multiple states:**
+//! - Native: mutexes are locked in `TypeId` order, so two commands sharing
+//!   states always lock them in the same order and cannot deadlock.
+//! - WASM: each state's `wasm_state!` cell is borrowed in turn.
+//! - Borrowing the same state type twice is a compile-time error; an alias the
+//!   macro cannot see through (`type W = World`) panics natively and returns
+//!   an error on WASM instead of deadlocking or aliasing.
+//!
+//! **Async commands:**
+//! - Native: an `async` `#[tauri::command]` taking `AppHandle` that runs the
+//!   inner future on `tauri::async_runtime::spawn`, so it must be `Send`.
+//!   Each `Mutex` is locked by a `webtau::__private::lease` on a
+//!   `spawn_blocking` thread that keeps the `!Send` guard until the future
+//!   completes; waiting for the lock never blocks the runtime. A panicking body fails the
+//!   command with a `COMMAND_ERROR` diagnostic.
+//! - WASM: an `async` `#[wasm_bindgen]` export (a `Promise` in JS, requires
+//!   `wasm-bindgen-futures`). The state `RefCell` stays borrowed until the
+//!   inner future completes; commands that run in the meantime get a
+//!   "state is busy" error instead of a borrow panic.
 //!
 //! **Unsupported forms** (compile-time error):
 //! - Methods with `self`.
-//! - Missing or non-reference state parameter.
+//! - Reference parameters after a command arg (args must be owned values).
+//! - The same state type borrowed twice.
 //! - Tuple or struct patterns in parameters.
-//! - Async functions.
+//! - Unknown or repeated attribute arguments.
 
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
 use quote::{format_ident, quote};
 use syn::{
-    parse_macro_input, spanned::Spanned, FnArg, GenericArgument, ItemFn, Pat, PatIdent,
-    PathArguments, ReturnType, Type, TypeReference,
+    meta::ParseNestedMeta,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    DeriveInput, FnArg, GenericArgument, ItemFn, LitInt, LitStr, Pat, PatIdent, PathArguments,
+    ReturnType, Token, Type, TypeReference,
 };
 
+mod ts;
+
 // ── Public entry point ────────────────────────────────────────────────
 
 #[proc_macro_attribute]
-pub fn command(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = CommandArgs::default();
+    let parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attr with parser);
     let input = parse_macro_input!(item as ItemFn);
-    match expand_command(input) {
+    match expand_command(args, input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Implements `webtau::ts::TypeScript` for a struct or enum, following its
+/// `#[serde(...)]` attributes. `#[ts(type = "...")]` overrides a field's type.
+#[proc_macro_derive(TypeScript, attributes(ts))]
+pub fn derive_typescript(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match ts::expand_derive(input) {
         Ok(tokens) => tokens.into(),
         Err(err) => err.to_compile_error().into(),
     }
 }
 
+/// Generates the accessor functions behind `webtau::wasm_state!`.
+///
+/// Input: `$crate, name, Type`. Not a public API — use `wasm_state!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __wasm_state_accessors(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as WasmStateInput);
+    expand_wasm_state(input).into()
+}
+
+/// Generates the command pair behind `webtau::snapshot_commands!`.
+///
+/// Input: `$crate, name, Type, version`. Not a public API — use
+/// `snapshot_commands!`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __snapshot_commands(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as SnapshotInput);
+    expand_snapshot_commands(input).into()
+}
+
 // ── Parsed representation ─────────────────────────────────────────────
 
 struct CommandDef {
     name: syn::Ident,
-    state_ident: syn::Ident,
-    state_ty: Box<Type>,
-    state_mut: bool,
+    /// Rust name of the generated wrappers on both targets, and the stem of
+    /// a task's `start_`/`poll_`/`cancel_` fns. Always a valid identifier.
+    export: syn::Ident,
+    /// The string `invoke()` calls and the router and registries key on.
+    /// Usually `export` spelled out; a dotted `name` such as `world.tick`
+    /// keeps its dots here while `export` is `world_tick`.
+    command: String,
+    js_name: Option<LitStr>,
+    rename_all: LitStr,
+    vis: syn::Visibility,
+    attrs: ForwardedAttrs,
+    is_async: bool,
+    states: Vec<StateParam>,
     extra_params: Vec<(Option<syn::token::Mut>, syn::Ident, Box<Type>)>,
+    /// Parameters the wrappers supply instead of the args object.
+    injected: Vec<InjectedParam>,
+    /// `#[command(task)]`: generate `start_`, `poll_` and `cancel_` commands.
+    task: bool,
+    /// The `webtau::Cancel` arg, which also gets the command a `cancel_`.
+    cancel: Option<syn::Ident>,
+    /// `#[command(timeout_ms = N)]`, the `Cancel` arg's deadline.
+    timeout_ms: Option<u64>,
+    /// `#[command(blocking)]`: run the native body on a `spawn_blocking` thread.
+    blocking: bool,
     ret: ReturnShape,
     body: syn::Block,
 }
 
+/// User attributes on the command fn, sorted by which generated items get them.
+struct ForwardedAttrs {
+    /// `#[cfg]`, `#[cfg_attr]` and lint levels: inner fn and both wrappers.
+    shared: Vec<syn::Attribute>,
+    /// Doc comments and `#[deprecated]`: the wrappers callers see.
+    wrapper: Vec<syn::Attribute>,
+    /// Everything else: the inner fn that holds the body.
+    inner: Vec<syn::Attribute>,
+}
+
+impl ForwardedAttrs {
+    fn sort(attrs: &[syn::Attribute]) -> Self {
+        let mut sorted = Self {
+            shared: Vec::new(),
+            wrapper: Vec::new(),
+            inner: Vec::new(),
+        };
+        for attr in attrs {
+            let path = attr.path();
+            let bucket = if [
+                "cfg", "cfg_attr", "allow", "expect", "warn", "deny", "forbid",
+            ]
+            .iter()
+            .any(|name| path.is_ident(name))
+            {
+                &mut sorted.shared
+            } else if path.is_ident("doc") || path.is_ident("deprecated") {
+                &mut sorted.wrapper
+            } else {
+                &mut sorted.inner
+            };
+            bucket.push(attr.clone());
+        }
+        sorted
+    }
+}
+
+struct StateParam {
+    ident: syn::Ident,
+    ty: Box<Type>,
+    mutable: bool,
+    /// `#[command(state = "...")]`: the `wasm_state!` name to borrow on
+    /// WASM instead of the only instance of `ty`.
+    instance: Option<LitStr>,
+}
+
+/// A parameter whose value comes from the runtime rather than `invoke()`,
+/// recognised by the last segment of its type path.
+struct InjectedParam {
+    mutability: Option<syn::token::Mut>,
+    ident: syn::Ident,
+    ty: Box<Type>,
+    kind: Injected,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Injected {
+    /// `webtau::Emitter`.
+    Emitter,
+    /// `webtau::TaskContext`, for `#[command(task)]` only.
+    TaskContext,
+}
+
+impl Injected {
+    fn of(ty: &Type) -> Option<Self> {
+        let Type::Path(tp) = ty else {
+            return None;
+        };
+        let seg = tp.path.segments.last()?;
+        match seg.ident.to_string().as_str() {
+            "Emitter" if seg.arguments.is_empty() => Some(Self::Emitter),
+            "TaskContext" if seg.arguments.is_empty() => Some(Self::TaskContext),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `ty` is a `webtau::Channel<T>`, recognised like [`Injected`]. A
+/// channel is still a command arg, but each wrapper builds it differently.
+fn is_channel(ty: &Type) -> bool {
+    let Type::Path(tp) = ty else {
+        return false;
+    };
+    tp.path.segments.last().is_some_and(|seg| {
+        seg.ident == "Channel" && matches!(seg.arguments, syn::PathArguments::AngleBracketed(_))
+    })
+}
+
+/// Whether `ty` is a `webtau::Cancel`, recognised like [`Injected`]. The
+/// `AbortSignal` for it is a command arg.
+fn is_cancel(ty: &Type) -> bool {
+    let Type::Path(tp) = ty else {
+        return false;
+    };
+    tp.path
+        .segments
+        .last()
+        .is_some_and(|seg| seg.ident == "Cancel" && seg.arguments.is_empty())
+}
+
 enum ReturnShape {
     Unit,
     Plain(Box<Type>),
-    Result { ok: Box<Type>, err: Box<Type> },
+    /// A `Result` or an alias of one. `ty` is the return type as written;
+    /// `ok` is its success type when it can be read off the path, and `err`
+    /// the error type pinned by `#[command(err = ...)]`.
+    Result {
+        ty: Box<Type>,
+        ok: Option<Box<Type>>,
+        err: Option<Box<Type>>,
+    },
 }
 
-// ── Parsing + diagnostics (Step 1) ────────────────────────────────────
+// ── Attribute arguments ───────────────────────────────────────────────
+
+/// `#[command(name = "...", js_name = "...", rename_all = "...", fallible, err = Type, task,
+/// timeout_ms = N, blocking)]`
+#[derive(Default)]
+struct CommandArgs {
+    name: Option<(syn::Ident, String)>,
+    js_name: Option<LitStr>,
+    rename_all: Option<LitStr>,
+    fallible: Option<syn::Ident>,
+    err: Option<Type>,
+    task: Option<syn::Ident>,
+    timeout_ms: Option<LitInt>,
+    blocking: Option<syn::Ident>,
+    state: Option<StateInstances>,
+}
+
+/// `#[command(state = "name")]` or `#[command(state(param = "name", ...))]`.
+enum StateInstances {
+    Only(LitStr),
+    ByParam(Vec<(syn::Ident, LitStr)>),
+}
+
+impl CommandArgs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            ensure_unset(&self.name, &meta, "name")?;
+            let lit: LitStr = meta.value()?.parse()?;
+            self.name = Some(parse_command_name(&lit)?);
+        } else if meta.path.is_ident("js_name") {
+            ensure_unset(&self.js_name, &meta, "js_name")?;
+            let lit: LitStr = meta.value()?.parse()?;
+            if !is_js_ident(&lit.value()) {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!(
+                        "#[command] `js_name` must be a valid JavaScript identifier, got `{}`",
+                        lit.value()
+                    ),
+                ));
+            }
+            self.js_name = Some(lit);
+        } else if meta.path.is_ident("rename_all") {
+            ensure_unset(&self.rename_all, &meta, "rename_all")?;
+            let lit: LitStr = meta.value()?.parse()?;
+            if !matches!(lit.value().as_str(), "snake_case" | "camelCase") {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!(
+                        "#[command] `rename_all` must be \"snake_case\" or \"camelCase\", got \"{}\"",
+                        lit.value()
+                    ),
+                ));
+            }
+            self.rename_all = Some(lit);
+        } else if meta.path.is_ident("fallible") {
+            ensure_unset(&self.fallible, &meta, "fallible")?;
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("#[command] `fallible` takes no value"));
+            }
+            self.fallible = meta.path.get_ident().cloned();
+        } else if meta.path.is_ident("err") {
+            ensure_unset(&self.err, &meta, "err")?;
+            self.err = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("task") {
+            ensure_unset(&self.task, &meta, "task")?;
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("#[command] `task` takes no value"));
+            }
+            self.task = meta.path.get_ident().cloned();
+        } else if meta.path.is_ident("timeout_ms") {
+            ensure_unset(&self.timeout_ms, &meta, "timeout_ms")?;
+            let lit: LitInt = meta.value()?.parse()?;
+            lit.base10_parse::<u64>()?;
+            self.timeout_ms = Some(lit);
+        } else if meta.path.is_ident("blocking") {
+            ensure_unset(&self.blocking, &meta, "blocking")?;
+            if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+                return Err(meta.error("#[command] `blocking` takes no value"));
+            }
+            self.blocking = meta.path.get_ident().cloned();
+        } else if meta.path.is_ident("state") {
+            ensure_unset(&self.state, &meta, "state")?;
+            if meta.input.peek(Token![=]) {
+                let lit: LitStr = meta.value()?.parse()?;
+                self.state = Some(StateInstances::Only(parse_state_name(lit)?));
+            } else {
+                let mut names: Vec<(syn::Ident, LitStr)> = Vec::new();
+                meta.parse_nested_meta(|param| {
+                    let ident = param.path.require_ident()?.clone();
+                    if names.iter().any(|(seen, _)| *seen == ident) {
+                        return Err(param.error(format!(
+                            "duplicate #[command] `state` entry for `{}`",
+                            ident
+                        )));
+                    }
+                    let lit: LitStr = param.value()?.parse()?;
+                    names.push((ident, parse_state_name(lit)?));
+                    Ok(())
+                })?;
+                self.state = Some(StateInstances::ByParam(names));
+            }
+        } else {
+            let path = &meta.path;
+            return Err(meta.error(format!(
+                "unsupported #[command] argument `{}`; expected `name`, `js_name`, \
+                 `rename_all`, `fallible`, `err`, `task`, `timeout_ms`, `blocking`, or `state`",
+                quote!(#path).to_string().replace(' ', "")
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn ensure_unset<T>(slot: &Option<T>, meta: &ParseNestedMeta, key: &str) -> syn::Result<()> {
+    match slot {
+        Some(_) => Err(meta.error(format!("duplicate #[command] argument `{}`", key))),
+        None => Ok(()),
+    }
+}
+
+/// A `wasm_state!` name, which is an identifier since it also names the
+/// generated `set_<name>` accessors.
+fn parse_state_name(lit: LitStr) -> syn::Result<LitStr> {
+    match syn::parse_str::<syn::Ident>(&lit.value()) {
+        Ok(_) => Ok(lit),
+        Err(_) => Err(syn::Error::new(
+            lit.span(),
+            format!(
+                "#[command] `state` must name a wasm_state! instance, an identifier, got `{}`",
+                lit.value()
+            ),
+        )),
+    }
+}
 
-fn expand_command(func: ItemFn) -> syn::Result<TokenStream2> {
-    // Reject async
-    if let Some(tok) = &func.sig.asyncness {
+/// The command name becomes the wrapper fn name, which is what
+/// `tauri::generate_handler!` and `wasm_bindgen` register. Dotted names such
+/// as `world.tick` name their wrappers `world_tick`: the Tauri command is
+/// renamed back to `world.tick`, and `invoke()` maps the dots to underscores
+/// when it looks up the WASM export.
+fn parse_command_name(lit: &LitStr) -> syn::Result<(syn::Ident, String)> {
+    let value = lit.value();
+    if let Ok(mut ident) = syn::parse_str::<syn::Ident>(&value) {
+        ident.set_span(lit.span());
+        return Ok((ident, value));
+    }
+    let segments = value.split('.');
+    if !segments
+        .clone()
+        .all(|segment| !segment.starts_with("r#") && syn::parse_str::<syn::Ident>(segment).is_ok())
+    {
         return Err(syn::Error::new(
-            tok.span(),
-            "#[command] does not support async functions",
+            lit.span(),
+            format!(
+                "#[command] `name` must be a Rust identifier or dot-separated identifiers \
+                 such as `world.tick`, got `{}`",
+                value
+            ),
         ));
     }
+    let ident = syn::Ident::new(&segments.collect::<Vec<_>>().join("_"), lit.span());
+    Ok((ident, value))
+}
+
+fn is_js_ident(value: &str) -> bool {
+    let mut chars = value.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+// ── Parsing + diagnostics (Step 1) ────────────────────────────────────
 
+fn expand_command(args: CommandArgs, func: ItemFn) -> syn::Result<TokenStream2> {
     // Reject methods with self
     for arg in &func.sig.inputs {
         if let FnArg::Receiver(recv) = arg {
@@ -87,55 +544,73 @@ fn expand_command(func: ItemFn) -> syn::Result<TokenStream2> {
         }
     }
 
-    // Must have at least one parameter (the state)
-    if func.sig.inputs.is_empty() {
-        return Err(syn::Error::new(
-            func.sig.ident.span(),
-            "#[command] requires at least one parameter: `state: &T` or `state: &mut T`",
-        ));
-    }
+    // ── Parse state parameters (leading references) ──
+    let mut inputs = func.sig.inputs.iter().peekable();
+    let mut states: Vec<StateParam> = Vec::new();
+    while let Some(FnArg::Typed(typed)) = inputs.peek() {
+        let Type::Reference(TypeReference {
+            elem, mutability, ..
+        }) = &*typed.ty
+        else {
+            break;
+        };
 
-    // ── Parse state parameter (first) ──
-    let first = match func.sig.inputs.first().unwrap() {
-        FnArg::Typed(pt) => pt,
-        _ => unreachable!("already rejected Receiver"),
-    };
+        // Must be a simple ident pattern
+        let ident = match &*typed.pat {
+            Pat::Ident(PatIdent { ident, .. }) => ident.clone(),
+            other => {
+                return Err(syn::Error::new(
+                    other.span(),
+                    "#[command] state parameter must be a simple identifier \
+                     (e.g., `state: &T`)",
+                ));
+            }
+        };
 
-    // Must be a simple ident pattern
-    let state_ident = match &*first.pat {
-        Pat::Ident(PatIdent { ident, .. }) => ident.clone(),
-        other => {
-            return Err(syn::Error::new(
-                other.span(),
-                "#[command] state parameter must be a simple identifier \
-                 (e.g., `state: &T`)",
+        let ty_key = quote!(#elem).to_string();
+        if let Some(prev) = states.iter().find(|s| {
+            let prev_ty = &s.ty;
+            quote!(#prev_ty).to_string() == ty_key
+        }) {
+            return Err(syn::Error::new_spanned(
+                elem,
+                format!(
+                    "#[command] state type `{}` is already borrowed by `{}`; \
+                     each state type can only be borrowed once per command",
+                    ty_key, prev.ident
+                ),
             ));
         }
-    };
 
-    // Must be &T or &mut T
-    let (state_ty, state_mut) = match &*first.ty {
-        Type::Reference(TypeReference {
-            elem, mutability, ..
-        }) => (elem.clone(), mutability.is_some()),
-        other => {
-            return Err(syn::Error::new(
-                other.span(),
-                "#[command] first parameter must be a reference: \
-                 `&T` or `&mut T`",
-            ));
-        }
-    };
+        states.push(StateParam {
+            ident,
+            ty: elem.clone(),
+            mutable: mutability.is_some(),
+            instance: None,
+        });
+        inputs.next();
+    }
 
     // ── Parse extra parameters ──
     let mut extra_params = Vec::new();
-    for arg in func.sig.inputs.iter().skip(1) {
+    let mut injected: Vec<InjectedParam> = Vec::new();
+    let mut cancel: Option<syn::Ident> = None;
+    for arg in inputs {
         let typed = match arg {
             FnArg::Typed(pt) => pt,
-            _ => unreachable!(),
+            _ => unreachable!("already rejected Receiver"),
         };
+        if let Type::Reference(_) = &*typed.ty {
+            return Err(syn::Error::new_spanned(
+                &typed.ty,
+                "#[command] state references must come before command args; \
+                 command args must be owned values",
+            ));
+        }
         let (mutability, ident) = match &*typed.pat {
-            Pat::Ident(PatIdent { mutability, ident, .. }) => (*mutability, ident.clone()),
+            Pat::Ident(PatIdent {
+                mutability, ident, ..
+            }) => (*mutability, ident.clone()),
             other => {
                 return Err(syn::Error::new(
                     other.span(),
@@ -151,21 +626,164 @@ fn expand_command(func: ItemFn) -> syn::Result<TokenStream2> {
                  for generated code",
             ));
         }
+        if let Some(kind) = Injected::of(&typed.ty) {
+            if kind == Injected::TaskContext && args.task.is_none() {
+                return Err(syn::Error::new_spanned(
+                    &typed.ty,
+                    "#[command] `TaskContext` parameters need `#[command(task)]`",
+                ));
+            }
+            if injected.iter().any(|p| p.kind == kind) {
+                return Err(syn::Error::new_spanned(
+                    &typed.ty,
+                    "#[command] takes at most one parameter of this type",
+                ));
+            }
+            injected.push(InjectedParam {
+                mutability,
+                ident,
+                ty: typed.ty.clone(),
+                kind,
+            });
+            continue;
+        }
+        if is_cancel(&typed.ty) {
+            if args.task.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &typed.ty,
+                    "#[command(task)] commands are cancelled through their `TaskContext`; \
+                     remove the `Cancel` parameter",
+                ));
+            }
+            if cancel.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &typed.ty,
+                    "#[command] takes at most one parameter of this type",
+                ));
+            }
+            cancel = Some(ident.clone());
+        }
         extra_params.push((mutability, ident, typed.ty.clone()));
     }
 
+    match args.state {
+        Some(StateInstances::Only(lit)) => match states.as_mut_slice() {
+            [state] => state.instance = Some(lit),
+            _ => {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "#[command(state = \"...\")] names the instance of a command's only \
+                     state parameter; name each one with `state(param = \"...\", ...)`",
+                ))
+            }
+        },
+        Some(StateInstances::ByParam(names)) => {
+            for (ident, lit) in names {
+                match states.iter_mut().find(|state| state.ident == ident) {
+                    Some(state) => state.instance = Some(lit),
+                    None => {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            format!(
+                                "#[command] `state` names `{}`, which is not a state parameter",
+                                ident
+                            ),
+                        ))
+                    }
+                }
+            }
+        }
+        None => {}
+    }
+
+    if let (Some(timeout_ms), None) = (&args.timeout_ms, &cancel) {
+        return Err(syn::Error::new(
+            timeout_ms.span(),
+            "#[command(timeout_ms)] needs a `webtau::Cancel` parameter, which the body \
+             checks to stop at the deadline",
+        ));
+    }
+
+    if let Some(blocking) = &args.blocking {
+        if args.task.is_some() {
+            return Err(syn::Error::new(
+                blocking.span(),
+                "#[command(task)] bodies already run on a blocking thread; remove `blocking`",
+            ));
+        }
+        if let Some(asyncness) = &func.sig.asyncness {
+            return Err(syn::Error::new(
+                asyncness.span(),
+                "#[command(blocking)] is for synchronous commands; `async` commands \
+                 already run off the main thread",
+            ));
+        }
+    }
+
+    if let (Some(_), Some(js_name)) = (&args.task, &args.js_name) {
+        return Err(syn::Error::new(
+            js_name.span(),
+            "#[command(task)] does not support `js_name`; its commands are named \
+             `start_<name>`, `poll_<name>` and `cancel_<name>`",
+        ));
+    }
+
     // ── Parse return type ──
+    let forced = args.fallible.is_some() || args.err.is_some();
     let ret = match &func.sig.output {
+        ReturnType::Default if forced => {
+            let span = match (&args.fallible, &args.err) {
+                (Some(flag), _) => flag.span(),
+                (None, Some(err)) => err.span(),
+                (None, None) => unreachable!(),
+            };
+            return Err(syn::Error::new(
+                span,
+                "#[command(fallible)] and #[command(err = ...)] need a `Result` return type",
+            ));
+        }
         ReturnType::Default => ReturnShape::Unit,
-        ReturnType::Type(_, ty) => parse_return_type(ty),
+        ReturnType::Type(_, ty) => match parse_return_type(ty) {
+            ReturnShape::Result { ty, ok, .. } => ReturnShape::Result {
+                ty,
+                ok,
+                err: args.err.map(Box::new),
+            },
+            ReturnShape::Plain(ty) if forced => ReturnShape::Result {
+                ty,
+                ok: None,
+                err: args.err.map(Box::new),
+            },
+            other => other,
+        },
     };
 
+    let (export, command) = args.name.unwrap_or_else(|| {
+        let ident = func.sig.ident.clone();
+        let command = ident.to_string();
+        (ident, command)
+    });
     let def = CommandDef {
         name: func.sig.ident.clone(),
-        state_ident,
-        state_ty,
-        state_mut,
+        export,
+        command,
+        js_name: args.js_name,
+        rename_all: args
+            .rename_all
+            .unwrap_or_else(|| LitStr::new("snake_case", Span::call_site())),
+        vis: func.vis.clone(),
+        attrs: ForwardedAttrs::sort(&func.attrs),
+        is_async: func.sig.asyncness.is_some(),
+        states,
         extra_params,
+        injected,
+        task: args.task.is_some(),
+        cancel,
+        timeout_ms: match &args.timeout_ms {
+            Some(lit) => Some(lit.base10_parse()?),
+            None => None,
+        },
+        blocking: args.blocking.is_some(),
         ret,
         body: (*func.block).clone(),
     };
@@ -173,20 +791,28 @@ fn expand_command(func: ItemFn) -> syn::Result<TokenStream2> {
     Ok(generate_all(&def))
 }
 
+/// Recognise `Result<T, E>` and single-parameter aliases such as
+/// `anyhow::Result<T>`, `std::io::Result<T>` or a crate-local
+/// `type Result<T> = ...`. Other aliases need `#[command(fallible)]`.
 fn parse_return_type(ty: &Type) -> ReturnShape {
     if let Type::Path(tp) = ty {
         if let Some(seg) = tp.path.segments.last() {
             if seg.ident == "Result" {
                 if let PathArguments::AngleBracketed(ab) = &seg.arguments {
-                    let mut types = ab.args.iter().filter_map(|a| {
-                        if let GenericArgument::Type(t) = a {
-                            Some(Box::new(t.clone()))
-                        } else {
-                            None
-                        }
-                    });
-                    if let (Some(ok), Some(err)) = (types.next(), types.next()) {
-                        return ReturnShape::Result { ok, err };
+                    let types: Vec<_> = ab
+                        .args
+                        .iter()
+                        .filter_map(|a| match a {
+                            GenericArgument::Type(t) => Some(Box::new(t.clone())),
+                            _ => None,
+                        })
+                        .collect();
+                    if matches!(types.len(), 1 | 2) {
+                        return ReturnShape::Result {
+                            ty: Box::new(ty.clone()),
+                            ok: types.into_iter().next(),
+                            err: None,
+                        };
                     }
                 }
             }
@@ -199,13 +825,34 @@ fn parse_return_type(ty: &Type) -> ReturnShape {
 
 fn generate_all(def: &CommandDef) -> TokenStream2 {
     let inner = generate_inner(def);
-    let native = generate_native(def);
-    let wasm = generate_wasm(def);
+    let registration = generate_registration(def);
+    let (native, wasm, route, signature) = if def.task {
+        (
+            generate_task_native(def),
+            generate_task_wasm(def),
+            generate_task_route(def),
+            ts::task_signatures(def),
+        )
+    } else {
+        let native = generate_native(def);
+        let cancel = generate_native_cancel(def);
+        let signature = ts::command_signature(def, &command_name(def));
+        let cancel_signature = def.cancel.is_some().then(|| ts::cancel_signature(def));
+        (
+            quote! { #native #cancel },
+            generate_wasm(def),
+            generate_route(def),
+            quote! { #signature #cancel_signature },
+        )
+    };
 
     quote! {
         #inner
         #native
+        #registration
+        #route
         #wasm
+        #signature
     }
 }
 
@@ -213,190 +860,1743 @@ fn generate_all(def: &CommandDef) -> TokenStream2 {
 fn generate_inner(def: &CommandDef) -> TokenStream2 {
     let inner_name = format_ident!("__webtau_{}", def.name);
     let body = &def.body;
-    let state_ty = &def.state_ty;
-    let state_ident = &def.state_ident;
 
-    let state_param = if def.state_mut {
-        quote! { #state_ident: &mut #state_ty }
-    } else {
-        quote! { #state_ident: &#state_ty }
-    };
+    let state_params = def.states.iter().map(|s| {
+        let (ident, ty) = (&s.ident, &s.ty);
+        if s.mutable {
+            quote! { #ident: &mut #ty }
+        } else {
+            quote! { #ident: &#ty }
+        }
+    });
 
-    let extra: Vec<_> = def
+    let extra = def
         .extra_params
         .iter()
-        .map(|(mutability, id, ty)| quote! { #mutability #id: #ty })
-        .collect();
+        .map(|(mutability, id, ty)| quote! { #mutability #id: #ty });
+    // Injected params go last; only the generated wrappers call this fn.
+    let injected = def.injected.iter().map(|p| {
+        let (mutability, id, ty) = (&p.mutability, &p.ident, &p.ty);
+        quote! { #mutability #id: #ty }
+    });
+    let params: Vec<_> = state_params.chain(extra).chain(injected).collect();
 
     let ret = ret_tokens(&def.ret);
+    let shared = &def.attrs.shared;
+    let inner_attrs = &def.attrs.inner;
 
-    quote! {
-        #[doc(hidden)]
-        #[inline(always)]
-        fn #inner_name(#state_param, #(#extra),*) #ret #body
+    if def.is_async {
+        quote! {
+            #(#shared)*
+            #(#inner_attrs)*
+            #[doc(hidden)]
+            async fn #inner_name(#(#params),*) #ret #body
+        }
+    } else {
+        // Respect a user-supplied `#[inline]` hint instead of stacking ours.
+        let inline = (!inner_attrs.iter().any(|a| a.path().is_ident("inline")))
+            .then(|| quote! { #[inline(always)] });
+        quote! {
+            #(#shared)*
+            #(#inner_attrs)*
+            #[doc(hidden)]
+            #inline
+            fn #inner_name(#(#params),*) #ret #body
+        }
     }
 }
 
 /// Emit the `#[tauri::command]` wrapper (Step 2 — native codegen).
 fn generate_native(def: &CommandDef) -> TokenStream2 {
-    let pub_name = &def.name;
+    let pub_name = &def.export;
     let inner_name = format_ident!("__webtau_{}", def.name);
-    let state_ty = &def.state_ty;
+    let command = &def.command;
+    let tauri_command = tauri_command_attr(def, pub_name, command);
+    let vis = &def.vis;
+    let shared = &def.attrs.shared;
+    let wrapper_attrs = &def.attrs.wrapper;
 
-    let extra_defs: Vec<_> = def
-        .extra_params
-        .iter()
-        .map(|(_, id, ty)| quote! { #id: #ty })
-        .collect();
+    let (extra_defs, channels) = native_extra_params(def);
     let extra_names: Vec<_> = def
         .extra_params
         .iter()
         .map(|(_, id, _)| quote! { #id })
         .collect();
 
-    // Use `__webtau_` prefix to avoid collisions with user arg names.
-    // unwrap_or_else recovers from a poisoned mutex rather than panicking.
-    let (lock, state_ref) = if def.state_mut {
-        (
-            quote! { let mut __webtau_guard = __webtau_tauri_state.lock().unwrap_or_else(|p| p.into_inner()); },
-            quote! { &mut __webtau_guard },
-        )
+    let ret = native_wrapper_ret(def);
+
+    // Injected params are built from the `AppHandle` Tauri passes in.
+    let injected = injected_values(def, Target::Native);
+    let injected = quote! { #channels #injected };
+    let injected_names: Vec<_> = def.injected.iter().map(|p| &p.ident).collect();
+    let app_param = (!def.injected.is_empty()).then(|| quote! { __webtau_app: ::tauri::AppHandle });
+
+    // Stateless commands forward their args straight to the inner function.
+    if def.states.is_empty() && !def.blocking {
+        let params = extra_defs.iter().cloned().chain(app_param);
+        let call = quote! { #inner_name(#(#extra_names,)* #(#injected_names),*) };
+        let (asyncness, call) = if def.is_async {
+            (quote! { async }, quote! { #call.await })
+        } else {
+            (quote! {}, call)
+        };
+        let call = native_finish(def, call);
+        return quote! {
+            #(#shared)*
+            #(#wrapper_attrs)*
+            #[cfg(not(target_arch = "wasm32"))]
+            #tauri_command
+            #vis #asyncness fn #pub_name(#(#params),*) #ret {
+                #injected
+                #call
+            }
+        };
+    }
+
+    let handles: Vec<_> = (0..def.states.len())
+        .map(|i| format_ident!("__webtau_tauri_state_{}", i))
+        .collect();
+    let state_tys: Vec<_> = def.states.iter().map(|s| &s.ty).collect();
+    let (lock, state_refs) = if def.states.is_empty() {
+        (quote! {}, Vec::new())
     } else {
-        (
-            quote! { let __webtau_guard = __webtau_tauri_state.lock().unwrap_or_else(|p| p.into_inner()); },
-            quote! { &__webtau_guard },
-        )
+        native_lock(def, &handles)
     };
+    let record = record_call(def, &extra_names);
+    let record_outcome = record_outcome(def, quote! { &__webtau_out });
 
-    let ret = ret_tokens(&def.ret);
+    if def.is_async || def.blocking {
+        let app_param = if def.states.is_empty() {
+            app_param
+        } else {
+            Some(quote! { __webtau_app: ::tauri::AppHandle })
+        };
+        // A body that panics fails the command with a diagnostic, so the
+        // wrapper returns a `Result` even for infallible commands.
+        let (ret, joined) = if native_returns_result(def) {
+            (ret, quote! { *__webtau_out })
+        } else {
+            (native_join_ret(def), quote! { Ok(*__webtau_out) })
+        };
+        // `AppHandle` is owned, so the task can be `'static`. The std
+        // `MutexGuard` is `!Send`, so an async body, which runs on the async
+        // runtime, borrows its states through `webtau::__private::Lease`s
+        // instead; a `#[command(blocking)]` body locks them on its blocking
+        // thread.
+        let spawn = if def.is_async {
+            let (lease, lease_refs, release) = native_lease(def);
+            let call = native_finish(
+                def,
+                quote! { #inner_name(#(#lease_refs,)* #(#extra_names,)* #(#injected_names),*).await },
+            );
+            quote! {
+                ::tauri::async_runtime::spawn(async move {
+                    #lease
+                    #record
+                    let __webtau_out = #call;
+                    #record_outcome
+                    #release
+                    // Boxed: a `Diagnostic` error is large to move back
+                    // through the join handle.
+                    ::std::boxed::Box::new(__webtau_out)
+                })
+            }
+        } else {
+            let call = native_finish(
+                def,
+                quote! { #inner_name(#(#state_refs,)* #(#extra_names,)* #(#injected_names),*) },
+            );
+            quote! {
+                ::tauri::async_runtime::spawn_blocking(move || {
+                    #(
+                        let #handles =
+                            ::tauri::Manager::state::<::std::sync::Mutex<#state_tys>>(&__webtau_app);
+                    )*
+                    #lock
+                    #record
+                    let __webtau_out = #call;
+                    #record_outcome
+                    ::std::boxed::Box::new(__webtau_out)
+                })
+            }
+        };
+        return quote! {
+            #(#shared)*
+            #(#wrapper_attrs)*
+            #[cfg(not(target_arch = "wasm32"))]
+            #tauri_command
+            #vis async fn #pub_name(
+                #(#extra_defs,)*
+                #app_param
+            ) #ret {
+                #injected
+                let __webtau_join = #spawn;
+                match __webtau_join.await {
+                    Ok(__webtau_out) => #joined,
+                    Err(__webtau_err) => Err(::webtau::__private::join_error(#command, __webtau_err)),
+                }
+            }
+        };
+    }
 
+    let call = native_finish(
+        def,
+        quote! { #inner_name(#(#state_refs,)* #(#extra_names,)* #(#injected_names),*) },
+    );
     quote! {
+        #(#shared)*
+        #(#wrapper_attrs)*
         #[cfg(not(target_arch = "wasm32"))]
-        #[::tauri::command(rename_all = "snake_case")]
-        pub fn #pub_name(
+        #tauri_command
+        #vis fn #pub_name(
             #(#extra_defs,)*
-            __webtau_tauri_state: ::tauri::State<'_, ::std::sync::Mutex<#state_ty>>
+            #(#handles: ::tauri::State<'_, ::std::sync::Mutex<#state_tys>>,)*
+            #app_param
         ) #ret {
+            #injected
             #lock
-            #inner_name(#state_ref, #(#extra_names),*)
+            #record
+            let __webtau_out = #call;
+            #record_outcome
+            __webtau_out
         }
     }
 }
 
-/// Emit the `#[wasm_bindgen]` wrapper (WASM codegen).
-fn generate_wasm(def: &CommandDef) -> TokenStream2 {
-    let pub_name = &def.name;
-    let inner_name = format_ident!("__webtau_{}", def.name);
-    let has_extra = !def.extra_params.is_empty();
+/// Emit `cancel_<name>` for a command with a `webtau::Cancel` arg. It is
+/// async, so Tauri runs it off the main thread that a sync body occupies.
+fn generate_native_cancel(def: &CommandDef) -> TokenStream2 {
+    if def.cancel.is_none() {
+        return quote! {};
+    }
+    let name = &def.command;
+    let cancel = format_ident!("cancel_{}", def.export);
+    let tauri_command = tauri_command_attr(def, &cancel, &format!("cancel_{}", name));
+    let vis = &def.vis;
+    let shared = &def.attrs.shared;
+    let doc = format!(
+        "Cancels the running `{}` that `invoke()` passed the `Cancel` id `id`.",
+        name
+    );
+    quote! {
+        #(#shared)*
+        #[doc = #doc]
+        #[cfg(not(target_arch = "wasm32"))]
+        #tauri_command
+        #vis async fn #cancel(id: u32) {
+            ::webtau::__private::cancel_request(#name, id)
+        }
+    }
+}
 
-    let try_state_accessor = if def.state_mut {
-        quote! { try_with_state_mut }
+/// Register the native wrapper for `webtau::generate_handler!`. Tauri's own
+/// `generate_handler!` builds the invoke fn, so argument handling stays
+/// exactly Tauri's; the registry only maps the command name to it.
+fn generate_registration(def: &CommandDef) -> TokenStream2 {
+    let commands: Vec<(syn::Ident, String)> = if def.task {
+        task_names(def)
+            .into_iter()
+            .zip(task_commands(def))
+            .collect()
     } else {
-        quote! { try_with_state }
+        let cancel = def.cancel.as_ref().map(|_| {
+            (
+                format_ident!("cancel_{}", def.export),
+                format!("cancel_{}", def.command),
+            )
+        });
+        std::iter::once((def.export.clone(), def.command.clone()))
+            .chain(cancel)
+            .collect()
     };
+    let entries = commands.iter().map(|(pub_name, name)| {
+        let invoke = format_ident!("__webtau_invoke_{}", pub_name);
+        quote! {
+            #[allow(deprecated)]
+            fn #invoke(__webtau_invoke: ::tauri::ipc::Invoke) -> bool {
+                (::tauri::generate_handler![#pub_name])(__webtau_invoke)
+            }
+            ::webtau::__private::inventory::submit! {
+                ::webtau::__private::NativeCommand {
+                    name: #name,
+                    handler: &(#invoke as fn(::tauri::ipc::Invoke) -> bool),
+                }
+            }
+        }
+    });
+    let cfgs = def.attrs.shared.iter().filter(|a| a.path().is_ident("cfg"));
+    // Lets `webtau::events::listen` handlers lock the same managed state.
+    let accessors = def.states.iter().enumerate().map(|(i, state)| {
+        let ty = &state.ty;
+        let access = format_ident!("__webtau_state_{}", i);
+        quote! {
+            fn #access(
+                __webtau_app: &dyn ::std::any::Any,
+                __webtau_run: &mut ::webtau::__private::StateRun<'_>,
+            ) -> bool {
+                let Some(__webtau_app) = __webtau_app.downcast_ref::<::tauri::AppHandle>() else {
+                    return false;
+                };
+                let Some(__webtau_state) =
+                    ::tauri::Manager::try_state::<::std::sync::Mutex<#ty>>(__webtau_app)
+                else {
+                    return false;
+                };
+                let mut __webtau_guard = __webtau_state.lock().unwrap_or_else(|p| p.into_inner());
+                __webtau_run(&mut *__webtau_guard);
+                true
+            }
+            ::webtau::__private::inventory::submit! {
+                ::webtau::__private::StateAccessor {
+                    state: ::std::any::TypeId::of::<#ty>,
+                    access: #access,
+                }
+            }
+        }
+    });
+    quote! {
+        #(#cfgs)*
+        #[cfg(not(target_arch = "wasm32"))]
+        const _: () = {
+            #(#entries)*
+            #(#accessors)*
+        };
+    }
+}
 
-    // ── Args handling ──
-    let (wasm_param, args_preamble, call_args) = if has_extra {
-        let struct_name = format_ident!("__Webtau{}Args", to_pascal_case(&def.name.to_string()));
+/// Register a JSON entry point for `webtau::Router`.
+fn generate_route(def: &CommandDef) -> TokenStream2 {
+    let inner_name = format_ident!("__webtau_{}", def.name);
+    let name = def.command.clone();
+    let cfgs = def.attrs.shared.iter().filter(|a| a.path().is_ident("cfg"));
 
-        let field_defs: Vec<_> = def
-            .extra_params
-            .iter()
-            .map(|(_, id, ty)| quote! { #id: #ty })
-            .collect();
-        let field_refs: Vec<_> = def
-            .extra_params
-            .iter()
-            .map(|(_, id, _)| quote! { __args.#id })
-            .collect();
+    let (args_preamble, call_args) = route_args(def, &name);
 
-        (
-            quote! { args: ::wasm_bindgen::JsValue },
-            quote! {
-                #[derive(::serde::Deserialize)]
-                struct #struct_name { #(#field_defs,)* }
-                let __args: #struct_name =
-                    ::serde_wasm_bindgen::from_value(args)
-                        .map_err(|e| ::wasm_bindgen::JsError::new(&e.to_string()))?;
-            },
-            field_refs,
-        )
+    let (state_param, state_arg, route_ty) = route_state(def);
+
+    let injected = injected_values(def, Target::Route);
+    let injected_names = def.injected.iter().map(|p| &p.ident);
+    let call = quote! { #inner_name(#state_arg #(#call_args,)* #(#injected_names),*) };
+    let call = if def.is_async {
+        quote! { ::webtau::__private::block_on(#call) }
     } else {
-        (quote! {}, quote! {}, vec![])
+        call
     };
-
-    // ── Return handling ──
-    // All WASM wrappers now return Result<_, JsError> so that args deserialization
-    // failures, serialization failures, and uninitialized-state errors surface as
-    // recoverable JsErrors instead of panics.
-    let (wasm_ret, body_expr) = match &def.ret {
-        ReturnShape::Unit => (
-            quote! { -> ::std::result::Result<(), ::wasm_bindgen::JsError> },
-            quote! {
-                match #try_state_accessor(|state| {
-                    #inner_name(state, #(#call_args),*);
-                }) {
-                    None => Err(::wasm_bindgen::JsError::new(
-                        "webtau: state not initialized — call set_state() first",
-                    )),
-                    Some(_) => Ok(()),
+    let to_json = quote! {{
+        #[allow(unused_imports)]
+        use ::webtau::__private::{RouteOpaque as _, RouteSerialize as _};
+        (&&::webtau::__private::RouteValue(&__val)).to_json(#name)
+    }};
+    let cancel_route = def.cancel.as_ref().map(|_| {
+        let cancel = format!("cancel_{}", name);
+        quote! {
+            fn __webtau_route_cancel(
+                __webtau_args: ::webtau::__private::serde_json::Value,
+            ) -> ::std::result::Result<
+                ::webtau::__private::serde_json::Value,
+                ::std::boxed::Box<::webtau::Diagnostic>,
+            > {
+                ::webtau::__private::route_cancel_request(#name, __webtau_args)
+            }
+            ::webtau::__private::inventory::submit! {
+                ::webtau::__private::RouteCommand {
+                    name: #cancel,
+                    route: &(__webtau_route_cancel as ::webtau::__private::StatelessRoute),
                 }
-            },
-        ),
-        ReturnShape::Plain(_) => (
-            quote! { -> ::std::result::Result<::wasm_bindgen::JsValue, ::wasm_bindgen::JsError> },
+            }
+        }
+    });
+    let finish = match &def.ret {
+        _ if def.cancel.is_some() => {
+            let finish = cancel_finish(def, call, &name);
             quote! {
-                match #try_state_accessor(|state| {
-                    let __result = #inner_name(state, #(#call_args),*);
-                    ::serde_wasm_bindgen::to_value(&__result)
-                        .map_err(|e| ::wasm_bindgen::JsError::new(&e.to_string()))
-                }) {
-                    None => Err(::wasm_bindgen::JsError::new(
-                        "webtau: state not initialized — call set_state() first",
-                    )),
-                    Some(inner) => inner,
-                }
-            },
-        ),
-        ReturnShape::Result { .. } => (
-            quote! { -> ::std::result::Result<::wasm_bindgen::JsValue, ::wasm_bindgen::JsError> },
+                let __val = #finish?;
+                #to_json
+            }
+        }
+        ReturnShape::Unit => quote! {
+            #call;
+            Ok(::webtau::__private::serde_json::Value::Null)
+        },
+        ReturnShape::Plain(_) => quote! {
+            let __val = #call;
+            #to_json
+        },
+        ReturnShape::Result { .. } => {
+            let command_error = command_error(def, &name);
             quote! {
-                match #try_state_accessor(|state| {
-                    match #inner_name(state, #(#call_args),*) {
-                        Ok(__val) => ::serde_wasm_bindgen::to_value(&__val)
-                            .map_err(|e| ::wasm_bindgen::JsError::new(&e.to_string())),
-                        Err(__err) => Err(::wasm_bindgen::JsError::new(&__err.to_string())),
-                    }
-                }) {
-                    None => Err(::wasm_bindgen::JsError::new(
-                        "webtau: state not initialized — call set_state() first",
-                    )),
-                    Some(inner) => inner,
+                match #call {
+                    Ok(__val) => #to_json,
+                    Err(__webtau_err) => Err(::std::boxed::Box::new(#command_error)),
                 }
-            },
-        ),
+            }
+        }
     };
 
     quote! {
-        #[cfg(target_arch = "wasm32")]
-        #[::wasm_bindgen::prelude::wasm_bindgen]
-        pub fn #pub_name(#wasm_param) #wasm_ret {
-            #args_preamble
-            #body_expr
-        }
+        #(#cfgs)*
+        #[cfg(not(target_arch = "wasm32"))]
+        const _: () = {
+            #[allow(deprecated)]
+            fn __webtau_route(
+                #state_param
+                __webtau_args: ::webtau::__private::serde_json::Value,
+            ) -> ::std::result::Result<
+                ::webtau::__private::serde_json::Value,
+                ::std::boxed::Box<::webtau::Diagnostic>,
+            > {
+                #args_preamble
+                #injected
+                #finish
+            }
+            ::webtau::__private::inventory::submit! {
+                ::webtau::__private::RouteCommand {
+                    name: #name,
+                    route: &(__webtau_route as #route_ty),
+                }
+            }
+            #cancel_route
+        };
     }
 }
 
-// ── Helpers ───────────────────────────────────────────────────────────
-
-fn ret_tokens(shape: &ReturnShape) -> TokenStream2 {
-    match shape {
-        ReturnShape::Unit => quote! {},
-        ReturnShape::Plain(ty) => quote! { -> #ty },
-        ReturnShape::Result { ok, err } => quote! { -> ::std::result::Result<#ok, #err> },
+/// The router state param, the state args for the inner call, and the
+/// route's fn type. A command borrowing several states is routed by
+/// `Router<(A, B, ..)>`, the state types in parameter order, and splits the
+/// tuple into its fields.
+fn route_state(def: &CommandDef) -> (TokenStream2, TokenStream2, TokenStream2) {
+    let fields: Vec<_> = (0..def.states.len())
+        .map(|i| format_ident!("__webtau_state_{}", i))
+        .collect();
+    let args = def.states.iter().zip(&fields).map(|(state, field)| {
+        if state.mutable {
+            quote! { #field, }
+        } else {
+            quote! { &*#field, }
+        }
+    });
+    let state_arg = quote! { #(#args)* };
+    match &def.states[..] {
+        [] => (
+            quote! {},
+            quote! {},
+            quote! { ::webtau::__private::StatelessRoute },
+        ),
+        [state] => {
+            let ty = &state.ty;
+            (
+                quote! { __webtau_state_0: &mut #ty, },
+                state_arg,
+                quote! { ::webtau::__private::StatefulRoute<#ty> },
+            )
+        }
+        states => {
+            let tys = states.iter().map(|s| &s.ty);
+            let tuple = quote! { (#(#tys,)*) };
+            (
+                quote! { (#(#fields,)*): &mut #tuple, },
+                state_arg,
+                quote! { ::webtau::__private::StatefulRoute<#tuple> },
+            )
+        }
     }
 }
 
+/// Deserialize a routed command's args for `command`, returning the
+/// statements and the call arguments. Channel and `Cancel` params are not
+/// read from the args object: channels send to the `Harness` under their
+/// key, and the `Cancel` only enforces the timeout.
+fn route_args(def: &CommandDef, command: &str) -> (TokenStream2, Vec<TokenStream2>) {
+    let rename_all = &def.rename_all;
+    let fields: Vec<_> = def
+        .extra_params
+        .iter()
+        .filter(|(_, _, ty)| !is_channel(ty) && !is_cancel(ty))
+        .map(|(_, id, ty)| quote! { #id: #ty })
+        .collect();
+    let call_args = def
+        .extra_params
+        .iter()
+        .map(|(_, id, ty)| {
+            if is_channel(ty) {
+                let key = ts::arg_key(def, id);
+                quote! { ::webtau::__private::route_channel(#key) }
+            } else if is_cancel(ty) {
+                quote! { __webtau_cancel.token() }
+            } else {
+                quote! { __webtau_args.#id }
+            }
+        })
+        .collect();
+    let cancel = def.cancel.as_ref().map(|_| {
+        let timeout_ms = timeout_expr(def);
+        quote! {
+            let __webtau_cancel = ::webtau::__private::cancel_scope(#command, None, #timeout_ms);
+        }
+    });
+    if fields.is_empty() {
+        return (quote! { let _ = __webtau_args; #cancel }, call_args);
+    }
+    let preamble = quote! {
+        #[derive(::webtau::__private::serde::Deserialize)]
+        #[serde(crate = "::webtau::__private::serde", rename_all = #rename_all)]
+        struct __WebtauArgs { #(#fields,)* }
+        let __webtau_args: __WebtauArgs =
+            ::webtau::__private::route_args(#command, __webtau_args)?;
+        #cancel
+    };
+    (preamble, call_args)
+}
+
+/// The native wrapper's extra params, with every `webtau::Channel` taken as
+/// the `tauri::ipc::Channel` that `invoke()` passes and the `webtau::Cancel`
+/// as its id, plus the statements turning those back into the body's args.
+fn native_extra_params(def: &CommandDef) -> (Vec<TokenStream2>, TokenStream2) {
+    let defs = def
+        .extra_params
+        .iter()
+        .map(|(_, id, ty)| {
+            if is_channel(ty) {
+                quote! { #id: ::tauri::ipc::Channel<::webtau::__private::serde_json::Value> }
+            } else if is_cancel(ty) {
+                quote! { #id: ::std::option::Option<u32> }
+            } else {
+                quote! { #id: #ty }
+            }
+        })
+        .collect();
+    let cancel = def.cancel.as_ref().map(|id| {
+        let name = def.command.clone();
+        let timeout_ms = timeout_expr(def);
+        quote! {
+            let __webtau_cancel = ::webtau::__private::cancel_scope(#name, #id, #timeout_ms);
+            let #id = __webtau_cancel.token();
+        }
+    });
+    let channels = def
+        .extra_params
+        .iter()
+        .filter(|(_, _, ty)| is_channel(ty))
+        .map(|(_, id, _)| {
+            quote! {
+                let #id = {
+                    let __webtau_channel = #id;
+                    ::webtau::Channel::from_fn(move |__webtau_item| {
+                        __webtau_channel
+                            .send(__webtau_item)
+                            .map_err(|__webtau_err| __webtau_err.to_string())
+                    })
+                };
+            }
+        });
+    (defs, quote! { #cancel #(#channels)* })
+}
+
+/// The body's outcome through `__webtau_cancel`, as a
+/// `Result<_, Box<Diagnostic>>` that fails once the command was cancelled or
+/// timed out.
+fn cancel_finish(def: &CommandDef, call: TokenStream2, command: &str) -> TokenStream2 {
+    let outcome = match def.ret {
+        ReturnShape::Unit => quote! {{
+            #call;
+            ::std::result::Result::Ok(())
+        }},
+        ReturnShape::Plain(_) => quote! { ::std::result::Result::Ok(#call) },
+        ReturnShape::Result { .. } => {
+            let command_error = command_error(def, command);
+            quote! { (#call).map_err(|__webtau_err| ::std::boxed::Box::new(#command_error)) }
+        }
+    };
+    quote! {{
+        let __webtau_outcome = #outcome;
+        __webtau_cancel.finish(__webtau_outcome)
+    }}
+}
+
+/// `#[command(timeout_ms = N)]` as an `Option<u64>` expression.
+fn timeout_expr(def: &CommandDef) -> TokenStream2 {
+    match def.timeout_ms {
+        Some(ms) => {
+            let ms = proc_macro2::Literal::u64_unsuffixed(ms);
+            quote! { ::std::option::Option::Some(#ms) }
+        }
+        None => quote! { ::std::option::Option::None },
+    }
+}
+
+/// Convert `__webtau_err` into a `COMMAND_ERROR` diagnostic. `E` is
+/// serialized into `details` when it implements `Serialize`, and `Display`
+/// provides the message when available; see `webtau::__private::CommandError`.
+fn command_error(def: &CommandDef, command: &str) -> TokenStream2 {
+    let pin_err = match &def.ret {
+        ReturnShape::Result { err: Some(err), .. } => Some(quote! {
+            let __webtau_err: #err = __webtau_err;
+        }),
+        _ => None,
+    };
+    quote! {{
+        #pin_err
+        #[allow(unused_imports)]
+        use ::webtau::__private::{
+            DisplayError as _, SerializeDisplayError as _, SerializeError as _,
+        };
+        (&&&::webtau::__private::CommandError(&__webtau_err)).to_diagnostic(#command)
+    }}
+}
+
+/// Native wrappers return command errors as a `COMMAND_ERROR` diagnostic,
+/// which Tauri serializes into the rejected `invoke()` promise.
+fn native_finish(def: &CommandDef, result: TokenStream2) -> TokenStream2 {
+    if def.cancel.is_some() {
+        let finish = cancel_finish(def, result, &def.command);
+        return quote! { (#finish).map_err(|__webtau_err| *__webtau_err) };
+    }
+    match def.ret {
+        ReturnShape::Result { .. } => {
+            let command_error = command_error(def, &def.command);
+            quote! {
+                (#result).map_err(|__webtau_err| #command_error)
+            }
+        }
+        _ => result,
+    }
+}
+
+/// Lock every state mutex named by `handles`, returning the locking
+/// statements and the `&guard` / `&mut guard` expressions to pass inward.
+fn native_lock(def: &CommandDef, handles: &[syn::Ident]) -> (TokenStream2, Vec<TokenStream2>) {
+    let guards: Vec<_> = (0..handles.len())
+        .map(|i| format_ident!("__webtau_guard_{}", i))
+        .collect();
+    let state_refs = def
+        .states
+        .iter()
+        .zip(&guards)
+        .map(|(s, g)| {
+            if s.mutable {
+                quote! { &mut #g }
+            } else {
+                quote! { &#g }
+            }
+        })
+        .collect();
+    let muts: Vec<_> = def
+        .states
+        .iter()
+        .map(|s| {
+            if s.mutable {
+                quote! { mut }
+            } else {
+                quote! {}
+            }
+        })
+        .collect();
+
+    // Use `__webtau_` prefix to avoid collisions with user arg names.
+    // unwrap_or_else recovers from a poisoned mutex rather than panicking.
+    if handles.len() == 1 {
+        let (handle, guard, m) = (&handles[0], &guards[0], &muts[0]);
+        let lock = quote! {
+            let #m #guard = #handle.lock().unwrap_or_else(|p| p.into_inner());
+        };
+        return (lock, state_refs);
+    }
+
+    // Several states: lock in the global `TypeId` order so commands sharing
+    // states can never deadlock on each other.
+    let name = &def.export;
+    let state_tys: Vec<_> = def.states.iter().map(|s| &s.ty).collect();
+    let indices: Vec<_> = (0..handles.len()).collect();
+    let lock = quote! {
+        let __webtau_order = ::webtau::__private::lock_order([
+            #((
+                ::std::any::TypeId::of::<#state_tys>(),
+                ::std::any::type_name::<#state_tys>(),
+            )),*
+        ])
+        .unwrap_or_else(|__webtau_ty| panic!(
+            "webtau: command `{}` borrows state `{}` more than once",
+            stringify!(#name),
+            __webtau_ty
+        ));
+        #(let mut #guards = None;)*
+        for __webtau_i in __webtau_order {
+            match __webtau_i {
+                #(#indices => #guards = Some(#handles.lock().unwrap_or_else(|p| p.into_inner())),)*
+                _ => unreachable!(),
+            }
+        }
+        #(let #muts #guards = #guards.unwrap();)*
+    };
+    (lock, state_refs)
+}
+
+/// Lease every state an async body borrows, in the same `TypeId` order
+/// [`native_lock`] locks them, returning the statements, the `&*lease` /
+/// `&mut *lease` expressions to pass inward, and the statements that
+/// release the leases once the body is done with them.
+fn native_lease(def: &CommandDef) -> (TokenStream2, Vec<TokenStream2>, TokenStream2) {
+    let leases: Vec<_> = (0..def.states.len())
+        .map(|i| format_ident!("__webtau_lease_{}", i))
+        .collect();
+    let release = quote! { #(#leases.release().await;)* };
+    let state_refs = def
+        .states
+        .iter()
+        .zip(&leases)
+        .map(|(s, l)| {
+            if s.mutable {
+                quote! { &mut *#l }
+            } else {
+                quote! { &*#l }
+            }
+        })
+        .collect();
+    let muts: Vec<_> = def
+        .states
+        .iter()
+        .map(|s| {
+            if s.mutable {
+                quote! { mut }
+            } else {
+                quote! {}
+            }
+        })
+        .collect();
+    let acquire: Vec<_> = def
+        .states
+        .iter()
+        .map(|s| {
+            let ty = &s.ty;
+            quote! {
+                ::webtau::__private::lease(
+                    __webtau_app.clone(),
+                    |__webtau_app| {
+                        ::tauri::Manager::state::<::std::sync::Mutex<#ty>>(__webtau_app).inner()
+                    },
+                    |__webtau_hold| {
+                        ::tauri::async_runtime::spawn_blocking(__webtau_hold);
+                    },
+                )
+                .await
+            }
+        })
+        .collect();
+
+    if leases.len() == 1 {
+        let (lease, acquire, m) = (&leases[0], &acquire[0], &muts[0]);
+        return (quote! { let #m #lease = #acquire; }, state_refs, release);
+    }
+
+    let name = &def.export;
+    let state_tys: Vec<_> = def.states.iter().map(|s| &s.ty).collect();
+    let indices: Vec<_> = (0..leases.len()).collect();
+    let lease = quote! {
+        let __webtau_order = ::webtau::__private::lock_order([
+            #((
+                ::std::any::TypeId::of::<#state_tys>(),
+                ::std::any::type_name::<#state_tys>(),
+            )),*
+        ])
+        .unwrap_or_else(|__webtau_ty| panic!(
+            "webtau: command `{}` borrows state `{}` more than once",
+            stringify!(#name),
+            __webtau_ty
+        ));
+        #(let mut #leases = None;)*
+        for __webtau_i in __webtau_order {
+            match __webtau_i {
+                #(#indices => #leases = Some(#acquire),)*
+                _ => unreachable!(),
+            }
+        }
+        #(let #muts #leases = #leases.unwrap();)*
+    };
+    (lease, state_refs, release)
+}
+
+/// Which wrapper injected params are built for.
+#[derive(Clone, Copy)]
+enum Target {
+    /// The `#[tauri::command]` wrapper, holding `__webtau_app: AppHandle`.
+    Native,
+    /// The `webtau::Router` entry point.
+    Route,
+    /// The `#[wasm_bindgen]` wrapper.
+    Wasm,
+}
+
+/// `let` statements binding every injected param for `target`.
+fn injected_values(def: &CommandDef, target: Target) -> TokenStream2 {
+    let bindings = def.injected.iter().map(|p| {
+        let ident = &p.ident;
+        let value = match (p.kind, target) {
+            (Injected::Emitter, Target::Native) => quote! {{
+                let __webtau_app = ::std::clone::Clone::clone(&__webtau_app);
+                ::webtau::Emitter::from_fn(move |__webtau_event, __webtau_payload| {
+                    ::tauri::Emitter::emit(&__webtau_app, __webtau_event, __webtau_payload)
+                        .map_err(|__webtau_err| __webtau_err.to_string())
+                })
+            }},
+            (Injected::Emitter, Target::Route) => quote! { ::webtau::__private::route_emitter() },
+            (Injected::Emitter, Target::Wasm) => quote! { ::webtau::Emitter::new() },
+            // Only task wrappers, which hold `__webtau_task`, take a context.
+            (Injected::TaskContext, _) => quote! { __webtau_task.context() },
+        };
+        quote! { let #ident = #value; }
+    });
+    quote! { #(#bindings)* }
+}
+
+/// Whether the command's calls go to a running `webtau::replay` recorder:
+/// only commands that mutate state are recorded.
+fn is_recorded(def: &CommandDef) -> bool {
+    def.states.iter().any(|s| s.mutable)
+}
+
+/// Start a `webtau::replay` entry for the call as `__webtau_record`, once
+/// the state is held. [`record_outcome`] appends it.
+fn record_call(def: &CommandDef, args: &[TokenStream2]) -> TokenStream2 {
+    if !is_recorded(def) {
+        return quote! {};
+    }
+    let name = def.command.clone();
+    let keys = def
+        .extra_params
+        .iter()
+        .map(|(_, id, _)| ts::arg_key(def, id));
+    quote! {
+        let __webtau_record = if ::webtau::__private::is_recording() {
+            #[allow(unused_imports)]
+            use ::webtau::__private::{RecordOpaque as _, RecordSerialize as _};
+            Some(::webtau::__private::record(#name, [
+                #((#keys, (&&::webtau::__private::RecordArg(&#args)).to_record()),)*
+            ]))
+        } else {
+            None
+        };
+    }
+}
+
+/// Append the `__webtau_record` entry with the wrapper's outcome `out`,
+/// still holding the state, so the log lists mutations in the order they
+/// were applied and replay can check each command ends the same way.
+fn record_outcome(def: &CommandDef, out: TokenStream2) -> TokenStream2 {
+    if !is_recorded(def) {
+        return quote! {};
+    }
+    quote! {
+        if let Some(__webtau_record) = __webtau_record {
+            #[allow(unused_imports)]
+            use ::webtau::__private::{RecordFailure as _, RecordSuccess as _};
+            __webtau_record.finish((&&::webtau::__private::RecordOutcome(#out)).failure());
+        }
+    }
+}
+
+/// Emit the `#[wasm_bindgen]` wrapper (WASM codegen).
+fn generate_wasm(def: &CommandDef) -> TokenStream2 {
+    let pub_name = &def.export;
+    let inner_name = format_ident!("__webtau_{}", def.name);
+    let has_extra = !def.extra_params.is_empty();
+
+    // Every failure is thrown as a `webtau::Diagnostic` object naming the
+    // command as JS invokes it.
+    let command = command_name(def);
+    let throw =
+        |code: &str, message: TokenStream2| js_error(diagnostic_expr(code, &command, message));
+
+    let (wasm_param, args_preamble, call_args) = wasm_args(def, &command);
+    let (state_preamble, state_args) = wasm_state_borrows(def, &command, js_error);
+    let record = record_call(def, &call_args);
+    let record_out = |out: TokenStream2| record_outcome(def, out);
+    let record_outcome = record_out(quote! { &__webtau_out });
+    let injected = injected_values(def, Target::Wasm);
+    let injected_names = def.injected.iter().map(|p| {
+        let ident = &p.ident;
+        quote! { #ident }
+    });
+    let inner_args: Vec<_> = state_args
+        .into_iter()
+        .chain(call_args)
+        .chain(injected_names)
+        .collect();
+
+    let call = if def.is_async {
+        quote! { #inner_name(#(#inner_args),*).await }
+    } else {
+        quote! { #inner_name(#(#inner_args),*) }
+    };
+
+    // ── Return handling ──
+    // All WASM wrappers return Result<_, JsValue> so that args deserialization
+    // failures, serialization failures, and state errors reject with a
+    // diagnostic object instead of panicking.
+    let serialize_failed = throw("SerializeFailed", quote! { e });
+    let (wasm_ret, body_expr) = match &def.ret {
+        _ if def.cancel.is_some() => {
+            let finish = cancel_finish(def, call, &command);
+            (
+                quote! { -> ::std::result::Result<::wasm_bindgen::JsValue, ::wasm_bindgen::JsValue> },
+                quote! {
+                    let __webtau_out = (#finish).map_err(|__webtau_err| *__webtau_err);
+                    #record_outcome
+                    match __webtau_out {
+                        Ok(__val) => ::serde_wasm_bindgen::to_value(&__val).map_err(|e| #serialize_failed),
+                        Err(__webtau_err) => Err(::wasm_bindgen::JsValue::from(__webtau_err)),
+                    }
+                },
+            )
+        }
+        ReturnShape::Unit => (
+            quote! { -> ::std::result::Result<(), ::wasm_bindgen::JsValue> },
+            {
+                let record_outcome = record_out(quote! { &() });
+                quote! {
+                    #call;
+                    #record_outcome
+                    Ok(())
+                }
+            },
+        ),
+        ReturnShape::Plain(_) => (
+            quote! { -> ::std::result::Result<::wasm_bindgen::JsValue, ::wasm_bindgen::JsValue> },
+            {
+                let record_outcome = record_out(quote! { &__result });
+                quote! {
+                    let __result = #call;
+                    #record_outcome
+                    ::serde_wasm_bindgen::to_value(&__result).map_err(|e| #serialize_failed)
+                }
+            },
+        ),
+        ReturnShape::Result { .. } => {
+            let command_error = command_error(def, &command);
+            (
+                quote! { -> ::std::result::Result<::wasm_bindgen::JsValue, ::wasm_bindgen::JsValue> },
+                quote! {
+                    let __webtau_out = (#call).map_err(|__webtau_err| #command_error);
+                    #record_outcome
+                    match __webtau_out {
+                        Ok(__val) => ::serde_wasm_bindgen::to_value(&__val).map_err(|e| #serialize_failed),
+                        Err(__webtau_err) => Err(::wasm_bindgen::JsValue::from(__webtau_err)),
+                    }
+                },
+            )
+        }
+    };
+
+    let asyncness = if def.is_async {
+        quote! { async }
+    } else {
+        quote! {}
+    };
+    let bindgen_args = def
+        .js_name
+        .as_ref()
+        .map(|js_name| quote! { (js_name = #js_name) });
+    let shared = &def.attrs.shared;
+    let wrapper_attrs = &def.attrs.wrapper;
+    let body = quote! {
+        #args_preamble
+        #state_preamble
+        #record
+        #injected
+        #body_expr
+    };
+
+    // `#[wasm_bindgen]` only accepts `pub` fns. Keep the body in a private
+    // shim, export a `pub` forwarder from a hidden module, and re-export it at
+    // the declared visibility. The forwarder only names `wasm_bindgen` types,
+    // so user type paths never have to resolve from inside the module, and the
+    // module scopes `allow(deprecated)` over `wasm_bindgen`'s generated glue.
+    let vis = &def.vis;
+    let shim = format_ident!("__webtau_wasm_{}", pub_name);
+    let module = format_ident!("__webtau_export_{}", pub_name);
+    let forward_args = if has_extra {
+        quote! { args }
+    } else {
+        quote! {}
+    };
+    let forward_await = def.is_async.then(|| quote! { .await });
+    // Async commands hold their state borrows across `.await` by design.
+    let hold_state = def
+        .is_async
+        .then(|| quote! { #[allow(clippy::await_holding_refcell_ref)] });
+    // Lint attributes on a `use` are rejected by `clippy::useless_attribute`.
+    let cfgs = shared.iter().filter(|a| a.path().is_ident("cfg"));
+    quote! {
+        #(#shared)*
+        #[cfg(target_arch = "wasm32")]
+        #[doc(hidden)]
+        #hold_state
+        #asyncness fn #shim(#wasm_param) #wasm_ret {
+            #body
+        }
+
+        #(#shared)*
+        #[cfg(target_arch = "wasm32")]
+        #[doc(hidden)]
+        #[allow(deprecated)]
+        mod #module {
+            #(#wrapper_attrs)*
+            #[::wasm_bindgen::prelude::wasm_bindgen #bindgen_args]
+            pub #asyncness fn #pub_name(#wasm_param) #wasm_ret {
+                super::#shim(#forward_args) #forward_await
+            }
+        }
+
+        #(#cfgs)*
+        #[cfg(target_arch = "wasm32")]
+        #[allow(deprecated)]
+        #vis use self::#module::#pub_name;
+    }
+}
+
+// ── #[command(task)] ──────────────────────────────────────────────────
+
+/// `start_<name>`, `poll_<name>` and `cancel_<name>`.
+fn task_names(def: &CommandDef) -> [syn::Ident; 3] {
+    ["start", "poll", "cancel"].map(|verb| format_ident!("{}_{}", verb, def.export))
+}
+
+/// The names `invoke()` uses for [`task_names`].
+fn task_commands(def: &CommandDef) -> [String; 3] {
+    ["start", "poll", "cancel"].map(|verb| format!("{}_{}", verb, def.command))
+}
+
+/// `#[tauri::command]` for the wrapper `ident`, renamed to `command` when
+/// that is a dotted name.
+fn tauri_command_attr(def: &CommandDef, ident: &syn::Ident, command: &str) -> TokenStream2 {
+    let rename_all = &def.rename_all;
+    if *ident == command {
+        quote! { #[::tauri::command(rename_all = #rename_all)] }
+    } else {
+        quote! { #[::tauri::command(rename_all = #rename_all, rename = #command)] }
+    }
+}
+
+/// The task body's outcome as `Result<serde_json::Value, Box<Diagnostic>>`,
+/// failing under the `start_` command's name.
+fn task_finish(def: &CommandDef, call: TokenStream2) -> TokenStream2 {
+    let command = format!("start_{}", def.command);
+    match &def.ret {
+        ReturnShape::Unit => quote! {
+            #call;
+            Ok(::webtau::__private::serde_json::Value::Null)
+        },
+        ReturnShape::Plain(_) => quote! {
+            ::webtau::__private::task_value(#command, &#call)
+        },
+        ReturnShape::Result { .. } => {
+            let command_error = command_error(def, &command);
+            quote! {
+                match #call {
+                    Ok(__val) => ::webtau::__private::task_value(#command, &__val),
+                    Err(__webtau_err) => Err(::std::boxed::Box::new(#command_error)),
+                }
+            }
+        }
+    }
+}
+
+/// Doc comments for the generated `poll_` and `cancel_` commands.
+fn task_docs(def: &CommandDef) -> (String, String) {
+    (
+        format!(
+            " Reports the progress or outcome of a `start_{}` task.",
+            def.command
+        ),
+        format!(" Asks a running `start_{}` task to stop.", def.command),
+    )
+}
+
+/// Emit the `#[tauri::command]` wrappers of a task. `start_` spawns the
+/// body on a blocking worker thread, which locks the state mutexes.
+fn generate_task_native(def: &CommandDef) -> TokenStream2 {
+    let [start, poll, cancel] = task_names(def);
+    let [start_command, poll_command, cancel_command] = task_commands(def);
+    let start_attr = tauri_command_attr(def, &start, &start_command);
+    let poll_attr = tauri_command_attr(def, &poll, &poll_command);
+    let cancel_attr = tauri_command_attr(def, &cancel, &cancel_command);
+    let name = def.command.clone();
+    let inner_name = format_ident!("__webtau_{}", def.name);
+    let vis = &def.vis;
+    let shared = &def.attrs.shared;
+    let wrapper_attrs = &def.attrs.wrapper;
+    let (poll_doc, cancel_doc) = task_docs(def);
+
+    let (extra_defs, channels) = native_extra_params(def);
+    let extra_names: Vec<_> = def
+        .extra_params
+        .iter()
+        .map(|(_, id, _)| quote! { #id })
+        .collect();
+    let injected = injected_values(def, Target::Native);
+    let injected = quote! { #channels #injected };
+    let injected_names = def.injected.iter().map(|p| &p.ident);
+    let needs_app =
+        !def.states.is_empty() || def.injected.iter().any(|p| p.kind == Injected::Emitter);
+    let app_param = needs_app.then(|| quote! { __webtau_app: ::tauri::AppHandle });
+
+    let handles: Vec<_> = (0..def.states.len())
+        .map(|i| format_ident!("__webtau_tauri_state_{}", i))
+        .collect();
+    let state_tys = def.states.iter().map(|s| &s.ty);
+    let (lock, state_refs) = if def.states.is_empty() {
+        (quote! {}, Vec::new())
+    } else {
+        native_lock(def, &handles)
+    };
+    let record = record_call(def, &extra_names);
+    // `start_` returns the task id whatever the body does, so the entry
+    // records no failure.
+    let record_outcome = record_outcome(def, quote! { &() });
+    let call = quote! { #inner_name(#(#state_refs,)* #(#extra_names,)* #(#injected_names),*) };
+    let call = if def.is_async {
+        quote! { ::tauri::async_runtime::block_on(#call) }
+    } else {
+        call
+    };
+    let finish = task_finish(def, call);
+
+    quote! {
+        #(#shared)*
+        #(#wrapper_attrs)*
+        #[cfg(not(target_arch = "wasm32"))]
+        #start_attr
+        #vis fn #start(#(#extra_defs,)* #app_param) -> u32 {
+            let __webtau_task = ::webtau::__private::task_start(#name);
+            let __webtau_id = __webtau_task.id();
+            #injected
+            ::tauri::async_runtime::spawn_blocking(move || {
+                __webtau_task.run(move || {
+                    #(
+                        let #handles =
+                            ::tauri::Manager::state::<::std::sync::Mutex<#state_tys>>(&__webtau_app);
+                    )*
+                    #lock
+                    #record
+                    let __webtau_out = { #finish };
+                    #record_outcome
+                    __webtau_out
+                })
+            });
+            __webtau_id
+        }
+
+        #(#shared)*
+        #[doc = #poll_doc]
+        #[cfg(not(target_arch = "wasm32"))]
+        #poll_attr
+        #vis fn #poll(
+            id: u32,
+        ) -> ::std::result::Result<::webtau::task::TaskStatus, ::webtau::Diagnostic> {
+            ::webtau::__private::task_poll(#name, id).map_err(|__webtau_err| *__webtau_err)
+        }
+
+        #(#shared)*
+        #[doc = #cancel_doc]
+        #[cfg(not(target_arch = "wasm32"))]
+        #cancel_attr
+        #vis fn #cancel(id: u32) {
+            ::webtau::__private::task_cancel(#name, id)
+        }
+    }
+}
+
+/// Register a task's commands for `webtau::Router`. `start_` runs the body
+/// to completion before returning the task id, so a `poll_` right after
+/// reports its outcome.
+fn generate_task_route(def: &CommandDef) -> TokenStream2 {
+    let [start, poll, cancel] = task_commands(def);
+    let name = def.command.clone();
+    let inner_name = format_ident!("__webtau_{}", def.name);
+    let cfgs = def.attrs.shared.iter().filter(|a| a.path().is_ident("cfg"));
+
+    let (args_preamble, call_args) = route_args(def, &start);
+    let (state_param, state_arg, route_ty) = route_state(def);
+
+    let injected = injected_values(def, Target::Route);
+    let injected_names = def.injected.iter().map(|p| &p.ident);
+    let call = quote! { #inner_name(#state_arg #(#call_args,)* #(#injected_names),*) };
+    let call = if def.is_async {
+        quote! { ::webtau::__private::block_on(#call) }
+    } else {
+        call
+    };
+    let finish = task_finish(def, call);
+    let json = quote! {
+        ::std::result::Result<
+            ::webtau::__private::serde_json::Value,
+            ::std::boxed::Box<::webtau::Diagnostic>,
+        >
+    };
+
+    quote! {
+        #(#cfgs)*
+        #[cfg(not(target_arch = "wasm32"))]
+        const _: () = {
+            #[allow(deprecated)]
+            fn __webtau_route_start(
+                #state_param
+                __webtau_args: ::webtau::__private::serde_json::Value,
+            ) -> #json {
+                #args_preamble
+                let __webtau_task = ::webtau::__private::task_start(#name);
+                let __webtau_id = __webtau_task.id();
+                #injected
+                __webtau_task.run(|| { #finish });
+                Ok(::webtau::__private::serde_json::Value::from(__webtau_id))
+            }
+            fn __webtau_route_poll(__webtau_args: ::webtau::__private::serde_json::Value) -> #json {
+                ::webtau::__private::route_task_poll(#name, __webtau_args)
+            }
+            fn __webtau_route_cancel(__webtau_args: ::webtau::__private::serde_json::Value) -> #json {
+                ::webtau::__private::route_task_cancel(#name, __webtau_args)
+            }
+            ::webtau::__private::inventory::submit! {
+                ::webtau::__private::RouteCommand {
+                    name: #start,
+                    route: &(__webtau_route_start as #route_ty),
+                }
+            }
+            ::webtau::__private::inventory::submit! {
+                ::webtau::__private::RouteCommand {
+                    name: #poll,
+                    route: &(__webtau_route_poll as ::webtau::__private::StatelessRoute),
+                }
+            }
+            ::webtau::__private::inventory::submit! {
+                ::webtau::__private::RouteCommand {
+                    name: #cancel,
+                    route: &(__webtau_route_cancel as ::webtau::__private::StatelessRoute),
+                }
+            }
+        };
+    }
+}
+
+/// Emit the `#[wasm_bindgen]` exports of a task. `start_` deserializes the
+/// args and returns the task id; the body runs from a later event-loop turn
+/// and borrows its states only then.
+fn generate_task_wasm(def: &CommandDef) -> TokenStream2 {
+    let [start, poll, cancel] = task_names(def);
+    let name = def.command.clone();
+    let command = start.to_string();
+    let inner_name = format_ident!("__webtau_{}", def.name);
+    let (poll_doc, cancel_doc) = task_docs(def);
+
+    let (wasm_param, args_preamble, call_args) = wasm_args(def, &command);
+    let (state_preamble, state_args) = wasm_state_borrows(def, &command, |diagnostic| diagnostic);
+    let record = record_call(def, &call_args);
+    // See `generate_task_native`.
+    let record_outcome = record_outcome(def, quote! { &() });
+    let injected = injected_values(def, Target::Wasm);
+    let injected_names = def.injected.iter().map(|p| {
+        let ident = &p.ident;
+        quote! { #ident }
+    });
+    let inner_args = state_args
+        .into_iter()
+        .chain(call_args)
+        .chain(injected_names);
+    let call = if def.is_async {
+        quote! { #inner_name(#(#inner_args),*).await }
+    } else {
+        quote! { #inner_name(#(#inner_args),*) }
+    };
+    let finish = task_finish(def, call);
+
+    let shared = &def.attrs.shared;
+    let shim = format_ident!("__webtau_wasm_{}", start);
+    let forward_args = if wasm_param.is_empty() {
+        quote! {}
+    } else {
+        quote! { args }
+    };
+    let js = quote! { ::wasm_bindgen::JsValue };
+    let start_export = wasm_export(
+        def,
+        &start,
+        &def.attrs.wrapper,
+        quote! { (#wasm_param) -> ::std::result::Result<#js, #js> },
+        quote! { super::#shim(#forward_args) },
+    );
+    let poll_export = wasm_export(
+        def,
+        &poll,
+        &[syn::parse_quote! { #[doc = #poll_doc] }],
+        quote! { (args: #js) -> ::std::result::Result<#js, #js> },
+        quote! { ::webtau::__private::wasm_task_poll(#name, args) },
+    );
+    let cancel_export = wasm_export(
+        def,
+        &cancel,
+        &[syn::parse_quote! { #[doc = #cancel_doc] }],
+        quote! { (args: #js) -> ::std::result::Result<(), #js> },
+        quote! { ::webtau::__private::wasm_task_cancel(#name, args) },
+    );
+
+    quote! {
+        #(#shared)*
+        #[cfg(target_arch = "wasm32")]
+        #[doc(hidden)]
+        // The body holds its state borrows across `.await` by design.
+        #[allow(clippy::await_holding_refcell_ref)]
+        fn #shim(#wasm_param) -> ::std::result::Result<#js, #js> {
+            #args_preamble
+            let __webtau_task = ::webtau::__private::task_start(#name);
+            let __webtau_id = __webtau_task.id();
+            #injected
+            ::webtau::__private::wasm_task_spawn(__webtau_task, async move {
+                #state_preamble
+                #record
+                let __webtau_out = { #finish };
+                #record_outcome
+                __webtau_out
+            });
+            Ok(#js::from(__webtau_id))
+        }
+
+        #start_export
+        #poll_export
+        #cancel_export
+    }
+}
+
+/// A `pub` `#[wasm_bindgen]` fn in a hidden module, re-exported at the
+/// command's visibility; see `generate_wasm`.
+fn wasm_export(
+    def: &CommandDef,
+    pub_name: &syn::Ident,
+    docs: &[syn::Attribute],
+    signature: TokenStream2,
+    body: TokenStream2,
+) -> TokenStream2 {
+    let vis = &def.vis;
+    let shared = &def.attrs.shared;
+    let module = format_ident!("__webtau_export_{}", pub_name);
+    let cfgs = shared.iter().filter(|a| a.path().is_ident("cfg"));
+    quote! {
+        #(#shared)*
+        #[cfg(target_arch = "wasm32")]
+        #[doc(hidden)]
+        #[allow(deprecated)]
+        mod #module {
+            #(#docs)*
+            #[::wasm_bindgen::prelude::wasm_bindgen]
+            pub fn #pub_name #signature {
+                #body
+            }
+        }
+
+        #(#cfgs)*
+        #[cfg(target_arch = "wasm32")]
+        #[allow(deprecated)]
+        #vis use self::#module::#pub_name;
+    }
+}
+
+/// `webtau::__private::diagnostic(DiagnosticCode::<code>, command, message)`.
+fn diagnostic_expr(code: &str, command: &str, message: TokenStream2) -> TokenStream2 {
+    let code = format_ident!("{}", code);
+    quote! {
+        ::webtau::__private::diagnostic(::webtau::DiagnosticCode::#code, #command, #message)
+    }
+}
+
+/// A diagnostic as the `JsValue` a `#[wasm_bindgen]` wrapper throws.
+fn js_error(diagnostic: TokenStream2) -> TokenStream2 {
+    quote! { ::wasm_bindgen::JsValue::from(#diagnostic) }
+}
+
+/// Deserialize the WASM args object: the wrapper's `args` param, the
+/// statements, and the field expressions to pass inward.
+fn wasm_args(def: &CommandDef, command: &str) -> (TokenStream2, TokenStream2, Vec<TokenStream2>) {
+    if def.extra_params.is_empty() {
+        return (quote! {}, quote! {}, vec![]);
+    }
+    let args_invalid = js_error(diagnostic_expr("ArgsInvalid", command, quote! { e }));
+    let struct_name = format_ident!("__Webtau{}Args", to_pascal_case(&def.export.to_string()));
+    let rename_all = &def.rename_all;
+
+    // The `Cancel` arg is the optional `AbortSignal` behind the token.
+    let field_defs: Vec<_> = def
+        .extra_params
+        .iter()
+        .map(|(_, id, ty)| {
+            if is_cancel(ty) {
+                quote! { #[serde(default)] #id: ::webtau::__private::CancelSignal }
+            } else {
+                quote! { #id: #ty }
+            }
+        })
+        .collect();
+    let field_refs: Vec<_> = def
+        .extra_params
+        .iter()
+        .map(|(_, id, ty)| {
+            if is_cancel(ty) {
+                quote! { __webtau_cancel.token() }
+            } else {
+                quote! { __args.#id }
+            }
+        })
+        .collect();
+    let cancel = def.cancel.as_ref().map(|id| {
+        let timeout_ms = timeout_expr(def);
+        quote! {
+            let __webtau_cancel = ::webtau::__private::cancel_scope(#command, __args.#id, #timeout_ms);
+        }
+    });
+
+    (
+        quote! { args: ::wasm_bindgen::JsValue },
+        quote! {
+            #[derive(::serde::Deserialize)]
+            #[serde(rename_all = #rename_all)]
+            struct #struct_name { #(#field_defs,)* }
+            let __args: #struct_name =
+                ::serde_wasm_bindgen::from_value(args).map_err(|e| #args_invalid)?;
+            #cancel
+        },
+        field_refs,
+    )
+}
+
+/// Borrow each state's `wasm_state!` cell for the rest of the enclosing
+/// block, returning the statements and the state args to pass inward.
+/// `try_borrow*` keeps a command that overlaps an in-flight async command
+/// from panicking; `fail` turns a diagnostic into the error `?` returns.
+fn wasm_state_borrows(
+    def: &CommandDef,
+    command: &str,
+    fail: fn(TokenStream2) -> TokenStream2,
+) -> (TokenStream2, Vec<TokenStream2>) {
+    let throw = |code: &str, message: TokenStream2| fail(diagnostic_expr(code, command, message));
+    let alias_check = (def.states.len() > 1).then(|| {
+        let state_tys = def.states.iter().map(|s| &s.ty);
+        let aliased = throw(
+            "StateBusy",
+            quote! { format!("state `{}` is borrowed more than once", __webtau_ty) },
+        );
+        quote! {
+            ::webtau::__private::lock_order([
+                #((
+                    ::std::any::TypeId::of::<#state_tys>(),
+                    ::std::any::type_name::<#state_tys>(),
+                )),*
+            ])
+            .map_err(|__webtau_ty| #aliased)?;
+        }
+    });
+    let mut state_preamble = Vec::new();
+    let mut state_args = Vec::new();
+    for (i, state) in def.states.iter().enumerate() {
+        let ty = &state.ty;
+        let cell = format_ident!("__webtau_cell_{}", i);
+        let borrow = format_ident!("__webtau_borrow_{}", i);
+        let state_arg = format_ident!("__webtau_state_{}", i);
+        let (borrow_stmt, as_ref) = if state.mutable {
+            (
+                quote! { let mut #borrow = #cell.try_borrow_mut() },
+                quote! { as_mut },
+            )
+        } else {
+            (
+                quote! { let #borrow = #cell.try_borrow() },
+                quote! { as_ref },
+            )
+        };
+        let busy = throw(
+            "StateBusy",
+            quote! {
+                format!(
+                    "state `{}` is busy — another command is still using it",
+                    ::std::any::type_name::<#ty>()
+                )
+            },
+        );
+        let setter = match &state.instance {
+            Some(instance) => format!("set_{}()", instance.value()),
+            None => "its wasm_state! setter (e.g. set_state())".to_string(),
+        };
+        let uninitialized = throw(
+            "StateUninitialized",
+            quote! {
+                format!(
+                    concat!("state `{}` not initialized — call ", #setter, " first"),
+                    ::std::any::type_name::<#ty>()
+                )
+            },
+        );
+        let find = match &state.instance {
+            Some(instance) => quote! {
+                let #cell = ::webtau::__private::state_cell::<#ty>(#instance);
+            },
+            None => {
+                let ambiguous = throw("StateAmbiguous", quote! { __webtau_message });
+                quote! {
+                    let #cell = ::webtau::__private::state_cell_of::<#ty>()
+                        .map_err(|__webtau_message| #ambiguous)?;
+                }
+            }
+        };
+        state_preamble.push(quote! {
+            #find
+            #borrow_stmt.map_err(|_| #busy)?;
+            let #state_arg = #borrow.#as_ref().ok_or_else(|| #uninitialized)?;
+        });
+        state_args.push(quote! { #state_arg });
+    }
+    (quote! { #alias_check #(#state_preamble)* }, state_args)
+}
+
+// ── wasm_state! accessors ─────────────────────────────────────────────
+
+struct WasmStateInput {
+    krate: TokenTree,
+    name: syn::Ident,
+    ty: Type,
+}
+
+impl Parse for WasmStateInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // `$crate` arrives as a single token; keep it verbatim so paths resolve
+        // to the `webtau` crate that expanded `wasm_state!`.
+        let krate = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let ty = input.parse()?;
+        Ok(Self { krate, name, ty })
+    }
+}
+
+fn expand_wasm_state(input: WasmStateInput) -> TokenStream2 {
+    let WasmStateInput { krate, name, ty } = input;
+    let set = format_ident!("set_{}", name);
+    let with = format_ident!("with_{}", name);
+    let with_mut = format_ident!("with_{}_mut", name);
+    let try_with = format_ident!("try_with_{}", name);
+    let try_with_mut = format_ident!("try_with_{}_mut", name);
+
+    let (subject, uninit) = if name == "state" {
+        (
+            "the game state".to_string(),
+            "webtau: state not initialized — call set_state() first".to_string(),
+        )
+    } else {
+        (
+            format!("the `{name}` state"),
+            format!("webtau: `{name}` state not initialized — call {set}() first"),
+        )
+    };
+    let cell_name = name.to_string();
+    let set_doc = format!(" Initialize or replace {subject}.");
+    let with_doc = format!(" Read-only access to {subject}.");
+    let with_mut_doc = format!(" Mutable access to {subject}.");
+    let panic_doc = format!(" Panics if `{set}()` has not been called.");
+    let try_with_doc =
+        format!(" Read-only access to {subject}. Returns `None` if not initialized.");
+    let try_with_mut_doc =
+        format!(" Mutable access to {subject}. Returns `None` if not initialized.");
+
+    quote! {
+        #[doc = #set_doc]
+        #[allow(dead_code)]
+        fn #set(val: #ty) {
+            *#krate::__private::state_cell::<#ty>(#cell_name).borrow_mut() = Some(val);
+        }
+
+        #[doc = #with_doc]
+        ///
+        /// # Panics
+        #[doc = #panic_doc]
+        #[allow(dead_code)]
+        fn #with<__F, __R>(f: __F) -> __R
+        where
+            __F: FnOnce(&#ty) -> __R,
+        {
+            let cell = #krate::__private::state_cell::<#ty>(#cell_name);
+            let borrow = cell.borrow();
+            let state = borrow.as_ref().expect(#uninit);
+            f(state)
+        }
+
+        #[doc = #with_mut_doc]
+        ///
+        /// # Panics
+        #[doc = #panic_doc]
+        #[allow(dead_code)]
+        fn #with_mut<__F, __R>(f: __F) -> __R
+        where
+            __F: FnOnce(&mut #ty) -> __R,
+        {
+            let cell = #krate::__private::state_cell::<#ty>(#cell_name);
+            let mut borrow = cell.borrow_mut();
+            let state = borrow.as_mut().expect(#uninit);
+            f(state)
+        }
+
+        #[doc = #try_with_doc]
+        #[allow(dead_code)]
+        fn #try_with<__F, __R>(f: __F) -> Option<__R>
+        where
+            __F: FnOnce(&#ty) -> __R,
+        {
+            let cell = #krate::__private::state_cell::<#ty>(#cell_name);
+            let borrow = cell.borrow();
+            borrow.as_ref().map(f)
+        }
+
+        #[doc = #try_with_mut_doc]
+        #[allow(dead_code)]
+        fn #try_with_mut<__F, __R>(f: __F) -> Option<__R>
+        where
+            __F: FnOnce(&mut #ty) -> __R,
+        {
+            let cell = #krate::__private::state_cell::<#ty>(#cell_name);
+            let mut borrow = cell.borrow_mut();
+            borrow.as_mut().map(f)
+        }
+    }
+}
+
+// ── snapshot_commands! ────────────────────────────────────────────────
+
+struct SnapshotInput {
+    krate: TokenTree,
+    name: syn::Ident,
+    ty: Type,
+    version: syn::Expr,
+}
+
+impl Parse for SnapshotInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let name = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let ty = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let version = input.parse()?;
+        Ok(Self {
+            krate,
+            name,
+            ty,
+            version,
+        })
+    }
+}
+
+/// Emit `export_<name>` / `import_<name>` as ordinary `#[webtau::command]`s,
+/// so they get the Tauri, WASM, router and TypeScript wrappers like any other.
+fn expand_snapshot_commands(input: SnapshotInput) -> TokenStream2 {
+    let SnapshotInput {
+        krate,
+        name,
+        ty,
+        version,
+    } = input;
+    let export = format_ident!("export_{}", name);
+    let import = format_ident!("import_{}", name);
+    let subject = if name == "state" {
+        "the game state".to_string()
+    } else {
+        format!("the `{name}` state")
+    };
+    let export_doc = format!(
+        " Serializes {subject} as a base64 `webtau::snapshot`: JSON, or MessagePack \
+         when `encoding` is `\"binary\"`."
+    );
+    let import_doc = format!(
+        " Replaces {subject} with a snapshot from `{export}`, in either encoding. \
+         Snapshots of another schema version are rejected."
+    );
+
+    quote! {
+        #[doc = #export_doc]
+        #[#krate::command]
+        pub fn #export(
+            state: &#ty,
+            encoding: ::std::option::Option<#krate::snapshot::Encoding>,
+        ) -> ::std::result::Result<::std::string::String, #krate::snapshot::SnapshotError> {
+            #krate::snapshot::export_base64(state, #version, encoding.unwrap_or_default())
+        }
+
+        #[doc = #import_doc]
+        #[#krate::command]
+        pub fn #import(
+            state: &mut #ty,
+            snapshot: ::std::string::String,
+        ) -> ::std::result::Result<(), #krate::snapshot::SnapshotError> {
+            let bytes = #krate::snapshot::from_base64(&snapshot)?;
+            *state = #krate::snapshot::import(&bytes, #version)?;
+            Ok(())
+        }
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────
+
+fn ret_tokens(shape: &ReturnShape) -> TokenStream2 {
+    match shape {
+        ReturnShape::Unit => quote! {},
+        ReturnShape::Plain(ty) => quote! { -> #ty },
+        ReturnShape::Result { ty, .. } => quote! { -> #ty },
+    }
+}
+
+/// The native wrapper's return type. Cancellable commands always return a
+/// `Result`, which carries the `TIMEOUT` or `CANCELLED` diagnostic.
+fn native_wrapper_ret(def: &CommandDef) -> TokenStream2 {
+    match &def.ret {
+        ReturnShape::Unit if def.cancel.is_some() => {
+            quote! { -> ::std::result::Result<(), ::webtau::Diagnostic> }
+        }
+        ReturnShape::Plain(ty) if def.cancel.is_some() => {
+            quote! { -> ::std::result::Result<#ty, ::webtau::Diagnostic> }
+        }
+        shape => native_ret_tokens(shape),
+    }
+}
+
+/// Whether the native wrapper already returns `Result<_, Diagnostic>`.
+fn native_returns_result(def: &CommandDef) -> bool {
+    def.cancel.is_some() || matches!(def.ret, ReturnShape::Result { .. })
+}
+
+/// `-> Result<T, Diagnostic>` for an infallible command returning `T`, for
+/// wrappers that can fail outside the body.
+fn native_join_ret(def: &CommandDef) -> TokenStream2 {
+    match &def.ret {
+        ReturnShape::Plain(ty) => quote! { -> ::std::result::Result<#ty, ::webtau::Diagnostic> },
+        _ => quote! { -> ::std::result::Result<(), ::webtau::Diagnostic> },
+    }
+}
+
+fn native_ret_tokens(shape: &ReturnShape) -> TokenStream2 {
+    match shape {
+        ReturnShape::Result { ok: Some(ok), .. } => {
+            quote! { -> ::std::result::Result<#ok, ::webtau::Diagnostic> }
+        }
+        // Aliases without a readable success type, e.g. `type Saved = Result<Save, E>`.
+        ReturnShape::Result { ty, ok: None, .. } => quote! {
+            -> ::std::result::Result<<#ty as ::webtau::__private::Fallible>::Ok, ::webtau::Diagnostic>
+        },
+        other => ret_tokens(other),
+    }
+}
+
+/// The name JS passes to `invoke()` for the WASM export.
+fn command_name(def: &CommandDef) -> String {
+    def.js_name
+        .as_ref()
+        .map_or_else(|| def.command.clone(), LitStr::value)
+}
+
 fn to_pascal_case(s: &str) -> String {
     s.split('_')
         .map(|word| {
@@ -0,0 +1,769 @@
+//! `#[derive(TypeScript)]` and the TypeScript signature `#[command]` records.
+//!
+//! The derive mirrors serde's JSON representation: it reads the same
+//! `#[serde(...)]` attributes (`rename`, `rename_all`, `rename_all_fields`,
+//! `skip`, `skip_serializing`, `skip_serializing_if`, `flatten`, `tag`,
+//! `content`, `untagged`, `transparent`) and ignores the rest. `#[ts(type =
+//! "...")]` on a field replaces its TypeScript type.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    meta::ParseNestedMeta, spanned::Spanned, Data, DeriveInput, Fields, GenericParam, LitStr,
+    Token, Type,
+};
+
+use crate::{is_js_ident, CommandDef, ReturnShape};
+
+// ── Command signatures ────────────────────────────────────────────────
+
+/// The key of arg `id` in the command's args object, per `rename_all`.
+pub(crate) fn arg_key(def: &CommandDef, id: &syn::Ident) -> String {
+    let name = unraw(id);
+    if def.rename_all.value() == "camelCase" {
+        rename_field(&name, "camelCase")
+    } else {
+        name
+    }
+}
+
+/// Submit the command's args object and return type to `inventory` for
+/// `webtau::ts::render_commands()`. Types without a `TypeScript` impl
+/// become `unknown`.
+pub(crate) fn command_signature(def: &CommandDef, command: &str) -> TokenStream2 {
+    submit_signature(def, command, command_args(def), command_result(def))
+}
+
+/// The signatures of a `#[command(task)]`: `start_` takes the args and
+/// returns the task id, `poll_` and `cancel_` take `{ id }`.
+pub(crate) fn task_signatures(def: &CommandDef) -> TokenStream2 {
+    let [start, poll, cancel] = crate::task_commands(def);
+    let result = command_result(def);
+    let id = id_field();
+    let start = submit_signature(
+        def,
+        &start,
+        command_args(def),
+        quote! { ::std::string::String::from("number") },
+    );
+    let poll = submit_signature(
+        def,
+        &poll,
+        vec![id.clone()],
+        quote! { ::webtau::__private::ts_task_status(&#result) },
+    );
+    let cancel = submit_signature(
+        def,
+        &cancel,
+        vec![id],
+        quote! { ::std::string::String::from("void") },
+    );
+    quote! { #start #poll #cancel }
+}
+
+/// The signature of a cancellable command's `cancel_`, taking `{ id }`.
+pub(crate) fn cancel_signature(def: &CommandDef) -> TokenStream2 {
+    submit_signature(
+        def,
+        &format!("cancel_{}", def.command),
+        vec![id_field()],
+        quote! { ::std::string::String::from("void") },
+    )
+}
+
+/// The `id` field of `poll_` and `cancel_` args.
+fn id_field() -> TokenStream2 {
+    quote! {
+        ::webtau::__private::TsField {
+            key: "id",
+            optional: false,
+            ty: ::std::string::String::from("number"),
+        }
+    }
+}
+
+/// The `TsField`s of the command's args object.
+fn command_args(def: &CommandDef) -> Vec<TokenStream2> {
+    def.extra_params
+        .iter()
+        .map(|(_, id, ty)| {
+            let key = ts_key(&arg_key(def, id));
+            if contains_impl_trait(ty) {
+                return quote! {
+                    ::webtau::__private::TsField {
+                        key: #key,
+                        optional: false,
+                        ty: ::std::string::String::from("unknown"),
+                    }
+                };
+            }
+            quote! { (&&::webtau::__private::TsProbe::<#ty>::new()).ts_field(decls, #key) }
+        })
+        .collect()
+}
+
+/// The TypeScript type of the command's success value.
+fn command_result(def: &CommandDef) -> TokenStream2 {
+    let probe =
+        |ty: TokenStream2| quote! { (&&::webtau::__private::TsProbe::<#ty>::new()).ts_type(decls) };
+    let unknown = quote! { ::std::string::String::from("unknown") };
+    match &def.ret {
+        ReturnShape::Unit => quote! { ::std::string::String::from("void") },
+        ReturnShape::Plain(ty) | ReturnShape::Result { ok: Some(ty), .. } => {
+            if contains_impl_trait(ty) {
+                unknown
+            } else {
+                probe(quote! { #ty })
+            }
+        }
+        ReturnShape::Result { ty, ok: None, .. } => {
+            probe(quote! { <#ty as ::webtau::__private::Fallible>::Ok })
+        }
+    }
+}
+
+fn submit_signature(
+    def: &CommandDef,
+    command: &str,
+    args: Vec<TokenStream2>,
+    result: TokenStream2,
+) -> TokenStream2 {
+    let cfgs = def.attrs.shared.iter().filter(|a| a.path().is_ident("cfg"));
+    quote! {
+        #(#cfgs)*
+        #[cfg(not(target_arch = "wasm32"))]
+        ::webtau::__private::inventory::submit! {
+            ::webtau::__private::TsCommand {
+                name: #command,
+                signature: {
+                    #[allow(deprecated)]
+                    fn __webtau_ts_signature(
+                        decls: &mut ::webtau::ts::Declarations,
+                    ) -> ::webtau::__private::TsSignature {
+                        use ::webtau::__private::{TsKnown as _, TsUnknown as _};
+                        ::webtau::__private::TsSignature {
+                            args: ::std::vec![#(#args),*],
+                            result: #result,
+                        }
+                    }
+                    __webtau_ts_signature
+                },
+            }
+        }
+    }
+}
+
+/// `impl Trait` cannot be named in a turbofish, so such types stay `unknown`.
+fn contains_impl_trait(ty: &Type) -> bool {
+    fn walk(tokens: TokenStream2) -> bool {
+        tokens.into_iter().any(|tt| match tt {
+            proc_macro2::TokenTree::Ident(ident) => ident == "impl",
+            proc_macro2::TokenTree::Group(group) => walk(group.stream()),
+            _ => false,
+        })
+    }
+    walk(quote! { #ty })
+}
+
+// ── #[derive(TypeScript)] ─────────────────────────────────────────────
+
+pub(crate) fn expand_derive(input: DeriveInput) -> syn::Result<TokenStream2> {
+    for param in &input.generics.params {
+        if !matches!(param, GenericParam::Lifetime(_)) {
+            return Err(syn::Error::new(
+                param.span(),
+                "#[derive(TypeScript)] does not support type or const parameters; \
+                 implement `webtau::ts::TypeScript` by hand for generic types",
+            ));
+        }
+    }
+    reject_ts_attrs(&input.attrs)?;
+
+    let container = Container::parse(&input.attrs)?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let ts_name = container.rename.clone().unwrap_or_else(|| unraw(ident));
+
+    if container.transparent {
+        let field = match &input.data {
+            Data::Struct(data) => data
+                .fields
+                .iter()
+                .map(|f| Field::parse(f).map(|parsed| (f, parsed)))
+                .collect::<syn::Result<Vec<_>>>()?
+                .into_iter()
+                .find(|(_, parsed)| !parsed.skip),
+            _ => None,
+        };
+        let Some((field, parsed)) = field else {
+            return Err(syn::Error::new(
+                ident.span(),
+                "#[serde(transparent)] needs a struct with one serialized field",
+            ));
+        };
+        let body = match parsed.ts_type {
+            Some(ts_type) => quote! {
+                fn ts_type() -> ::std::string::String {
+                    ::std::string::String::from(#ts_type)
+                }
+            },
+            None => {
+                let ty = &field.ty;
+                quote! {
+                    fn ts_type() -> ::std::string::String {
+                        <#ty as ::webtau::ts::TypeScript>::ts_type()
+                    }
+                    fn ts_declare(decls: &mut ::webtau::ts::Declarations) {
+                        <#ty as ::webtau::ts::TypeScript>::ts_declare(decls)
+                    }
+                    fn ts_optional() -> bool {
+                        <#ty as ::webtau::ts::TypeScript>::ts_optional()
+                    }
+                }
+            }
+        };
+        return Ok(quote! {
+            impl #impl_generics ::webtau::ts::TypeScript for #ident #ty_generics #where_clause {
+                #body
+            }
+        });
+    }
+
+    let decl = match &input.data {
+        Data::Struct(data) => struct_decl(&ts_name, &data.fields, container.rename_all.as_deref())?,
+        Data::Enum(data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                reject_ts_attrs(&variant.attrs)?;
+                let attrs = VariantAttrs::parse(&variant.attrs)?;
+                if attrs.skip {
+                    continue;
+                }
+                let name = attrs.rename.unwrap_or_else(|| {
+                    let name = unraw(&variant.ident);
+                    match &container.rename_all {
+                        Some(rule) => rename_variant(&name, rule),
+                        None => name,
+                    }
+                });
+                let field_rule = attrs
+                    .rename_all
+                    .or_else(|| container.rename_all_fields.clone());
+                variants.push(variant_expr(
+                    &container,
+                    &name,
+                    &variant.fields,
+                    field_rule.as_deref(),
+                    variant.span(),
+                )?);
+            }
+            quote! {
+                let variants: ::std::vec::Vec<::std::string::String> = ::std::vec![#(#variants),*];
+                if variants.is_empty() {
+                    ::std::format!("export type {} = never;", #ts_name)
+                } else {
+                    let lines: ::std::vec::Vec<::std::string::String> =
+                        variants.iter().map(|v| ::std::format!("  | {}", v)).collect();
+                    ::std::format!("export type {} =\n{};", #ts_name, lines.join("\n"))
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new(
+                data.union_token.span(),
+                "#[derive(TypeScript)] does not support unions",
+            ))
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::webtau::ts::TypeScript for #ident #ty_generics #where_clause {
+            fn ts_type() -> ::std::string::String {
+                ::std::string::String::from(#ts_name)
+            }
+            fn ts_declare(decls: &mut ::webtau::ts::Declarations) {
+                decls.declare(#ts_name, |decls| { #decl });
+            }
+        }
+    })
+}
+
+/// Statements evaluating to a struct's `export interface` / `export type`.
+fn struct_decl(
+    ts_name: &str,
+    fields: &Fields,
+    rename_all: Option<&str>,
+) -> syn::Result<TokenStream2> {
+    Ok(match fields {
+        Fields::Named(_) => {
+            let (props, flattened) = named_fields(fields, rename_all)?;
+            if flattened.is_empty() {
+                quote! {
+                    let fields = ::std::vec![#(#props),*];
+                    ::std::format!(
+                        "export interface {} {}",
+                        #ts_name,
+                        ::webtau::__private::ts_object(&fields, true)
+                    )
+                }
+            } else {
+                quote! {
+                    let fields = ::std::vec![#(#props),*];
+                    let mut parts: ::std::vec::Vec<::std::string::String> = ::std::vec::Vec::new();
+                    if !fields.is_empty() {
+                        parts.push(::webtau::__private::ts_object(&fields, false));
+                    }
+                    #(parts.push(#flattened);)*
+                    ::std::format!("export type {} = {};", #ts_name, parts.join(" & "))
+                }
+            }
+        }
+        Fields::Unnamed(_) => {
+            let items = unnamed_fields(fields)?;
+            if items.len() == 1 {
+                let item = &items[0];
+                quote! { ::std::format!("export type {} = {};", #ts_name, #item) }
+            } else {
+                quote! {
+                    let items: ::std::vec::Vec<::std::string::String> = ::std::vec![#(#items),*];
+                    ::std::format!("export type {} = [{}];", #ts_name, items.join(", "))
+                }
+            }
+        }
+        Fields::Unit => quote! { ::std::format!("export type {} = null;", #ts_name) },
+    })
+}
+
+/// An expression evaluating to one variant's type in the enum's union.
+fn variant_expr(
+    container: &Container,
+    name: &str,
+    fields: &Fields,
+    rename_all: Option<&str>,
+    span: proc_macro2::Span,
+) -> syn::Result<TokenStream2> {
+    let literal = ts_string(name);
+    let key = ts_key(name);
+    let tagged = |tag: &str| format!("{}: {}", ts_key(tag), literal);
+
+    // The variant's payload, if it has one.
+    let payload = match fields {
+        Fields::Unit => None,
+        Fields::Unnamed(_) => {
+            let items = unnamed_fields(fields)?;
+            if items.len() == 1 {
+                let item = &items[0];
+                Some(quote! { #item })
+            } else {
+                if let Tagging::Internal(_) = container.tagging {
+                    return Err(syn::Error::new(
+                        span,
+                        "#[serde(tag = ...)] cannot be used with tuple variants",
+                    ));
+                }
+                Some(quote! { ::std::format!("[{}]", [#(#items),*].join(", ")) })
+            }
+        }
+        Fields::Named(_) => {
+            let (props, flattened) = named_fields(fields, rename_all)?;
+            if !flattened.is_empty() {
+                return Err(syn::Error::new(
+                    span,
+                    "#[derive(TypeScript)] does not support #[serde(flatten)] in enum variants",
+                ));
+            }
+            if let Tagging::Internal(tag) = &container.tagging {
+                let tag_key = ts_key(tag);
+                return Ok(quote! {
+                    ::webtau::__private::ts_object(
+                        &[
+                            ::webtau::__private::TsField {
+                                key: #tag_key,
+                                optional: false,
+                                ty: ::std::string::String::from(#literal),
+                            },
+                            #(#props),*
+                        ],
+                        false,
+                    )
+                });
+            }
+            Some(quote! { ::webtau::__private::ts_object(&[#(#props),*], false) })
+        }
+    };
+
+    Ok(match (&container.tagging, payload) {
+        (Tagging::External, None) => quote! { ::std::string::String::from(#literal) },
+        (Tagging::External, Some(payload)) => {
+            quote! { ::std::format!("{{ {}: {} }}", #key, #payload) }
+        }
+        (Tagging::Internal(tag), None) | (Tagging::Adjacent(tag, _), None) => {
+            let object = format!("{{ {} }}", tagged(tag));
+            quote! { ::std::string::String::from(#object) }
+        }
+        (Tagging::Internal(tag), Some(payload)) => {
+            let object = format!("{{ {} }}", tagged(tag));
+            quote! { ::std::format!("{} & {}", #object, #payload) }
+        }
+        (Tagging::Adjacent(tag, content), Some(payload)) => {
+            let prefix = format!("{{ {}; {}: ", tagged(tag), ts_key(content));
+            quote! { ::std::format!("{}{} }}", #prefix, #payload) }
+        }
+        (Tagging::Untagged, None) => quote! { ::std::string::String::from("null") },
+        (Tagging::Untagged, Some(payload)) => payload,
+    })
+}
+
+/// `TsField` expressions for the serialized fields, plus the type
+/// expressions of `#[serde(flatten)]` fields.
+fn named_fields(
+    fields: &Fields,
+    rename_all: Option<&str>,
+) -> syn::Result<(Vec<TokenStream2>, Vec<TokenStream2>)> {
+    let mut props = Vec::new();
+    let mut flattened = Vec::new();
+    for field in fields {
+        let attrs = Field::parse(field)?;
+        if attrs.skip {
+            continue;
+        }
+        let ty = &field.ty;
+        if attrs.flatten {
+            flattened.push(match attrs.ts_type {
+                Some(ts_type) => quote! { ::std::string::String::from(#ts_type) },
+                None => quote! { decls.reference::<#ty>() },
+            });
+            continue;
+        }
+        let name = attrs.rename.unwrap_or_else(|| {
+            let name = unraw(field.ident.as_ref().expect("named field"));
+            match rename_all {
+                Some(rule) => rename_field(&name, rule),
+                None => name,
+            }
+        });
+        let key = ts_key(&name);
+        let optional = attrs.optional;
+        props.push(match attrs.ts_type {
+            Some(ts_type) => quote! {
+                ::webtau::__private::TsField {
+                    key: #key,
+                    optional: #optional,
+                    ty: ::std::string::String::from(#ts_type),
+                }
+            },
+            None => quote! { ::webtau::__private::ts_field::<#ty>(decls, #key, #optional) },
+        });
+    }
+    Ok((props, flattened))
+}
+
+/// Type expressions for the serialized fields of a tuple struct or variant.
+fn unnamed_fields(fields: &Fields) -> syn::Result<Vec<TokenStream2>> {
+    let mut items = Vec::new();
+    for field in fields {
+        let attrs = Field::parse(field)?;
+        if attrs.skip {
+            continue;
+        }
+        let ty = &field.ty;
+        items.push(match attrs.ts_type {
+            Some(ts_type) => quote! { ::std::string::String::from(#ts_type) },
+            None => quote! { decls.reference::<#ty>() },
+        });
+    }
+    Ok(items)
+}
+
+// ── serde attributes ──────────────────────────────────────────────────
+
+enum Tagging {
+    External,
+    Internal(String),
+    Adjacent(String, String),
+    Untagged,
+}
+
+struct Container {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    rename_all_fields: Option<String>,
+    tagging: Tagging,
+    transparent: bool,
+}
+
+impl Container {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut rename = None;
+        let mut rename_all = None;
+        let mut rename_all_fields = None;
+        let mut tag = None;
+        let mut content = None;
+        let mut untagged = false;
+        let mut transparent = false;
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    rename = serialize_name(&meta)?.map(|lit| lit.value());
+                } else if meta.path.is_ident("rename_all") {
+                    rename_all = serialize_name(&meta)?.map(rename_rule).transpose()?;
+                } else if meta.path.is_ident("rename_all_fields") {
+                    rename_all_fields = serialize_name(&meta)?.map(rename_rule).transpose()?;
+                } else if meta.path.is_ident("tag") {
+                    tag = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("content") {
+                    content = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("untagged") {
+                    untagged = true;
+                } else if meta.path.is_ident("transparent") {
+                    transparent = true;
+                } else {
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        let tagging = match (untagged, tag, content) {
+            (true, _, _) => Tagging::Untagged,
+            (false, Some(tag), Some(content)) => Tagging::Adjacent(tag, content),
+            (false, Some(tag), None) => Tagging::Internal(tag),
+            (false, None, _) => Tagging::External,
+        };
+        Ok(Self {
+            rename,
+            rename_all,
+            rename_all_fields,
+            tagging,
+            transparent,
+        })
+    }
+}
+
+#[derive(Default)]
+struct VariantAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    skip: bool,
+}
+
+impl VariantAttrs {
+    fn parse(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    parsed.rename = serialize_name(&meta)?.map(|lit| lit.value());
+                } else if meta.path.is_ident("rename_all") {
+                    parsed.rename_all = serialize_name(&meta)?.map(rename_rule).transpose()?;
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                    parsed.skip = true;
+                } else {
+                    skip_meta(&meta)?;
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+#[derive(Default)]
+struct Field {
+    rename: Option<String>,
+    skip: bool,
+    optional: bool,
+    flatten: bool,
+    ts_type: Option<LitStr>,
+}
+
+impl Field {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        for attr in &field.attrs {
+            if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        parsed.rename = serialize_name(&meta)?.map(|lit| lit.value());
+                    } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                        parsed.skip = true;
+                    } else if meta.path.is_ident("skip_serializing_if") {
+                        parsed.optional = true;
+                        skip_meta(&meta)?;
+                    } else if meta.path.is_ident("flatten") {
+                        parsed.flatten = true;
+                    } else {
+                        skip_meta(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("ts") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("type") {
+                        parsed.ts_type = Some(meta.value()?.parse()?);
+                        Ok(())
+                    } else {
+                        let path = &meta.path;
+                        Err(meta.error(format!(
+                            "unsupported #[ts] argument `{}`; expected `type`",
+                            quote!(#path).to_string().replace(' ', "")
+                        )))
+                    }
+                })?;
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn reject_ts_attrs(attrs: &[syn::Attribute]) -> syn::Result<()> {
+    match attrs.iter().find(|a| a.path().is_ident("ts")) {
+        Some(attr) => Err(syn::Error::new(
+            attr.span(),
+            "#[ts(...)] is only supported on fields",
+        )),
+        None => Ok(()),
+    }
+}
+
+/// `key = "name"` or `key(serialize = "name", deserialize = "...")`.
+fn serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        if inner.path.is_ident("serialize") {
+            name = Some(inner.value()?.parse()?);
+        } else {
+            skip_meta(&inner)?;
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+/// Consume a serde argument this derive does not need.
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream2>()?;
+    }
+    Ok(())
+}
+
+// ── Naming ────────────────────────────────────────────────────────────
+
+const RENAME_RULES: [&str; 8] = [
+    "lowercase",
+    "UPPERCASE",
+    "PascalCase",
+    "camelCase",
+    "snake_case",
+    "SCREAMING_SNAKE_CASE",
+    "kebab-case",
+    "SCREAMING-KEBAB-CASE",
+];
+
+fn rename_rule(lit: LitStr) -> syn::Result<String> {
+    let rule = lit.value();
+    if RENAME_RULES.contains(&rule.as_str()) {
+        Ok(rule)
+    } else {
+        Err(syn::Error::new(
+            lit.span(),
+            format!("unknown rename rule `{}`", rule),
+        ))
+    }
+}
+
+/// serde's `RenameRule::apply_to_variant`: variants are `PascalCase`.
+fn rename_variant(name: &str, rule: &str) -> String {
+    let snake = || {
+        let mut snake = String::new();
+        for (i, ch) in name.char_indices() {
+            if i > 0 && ch.is_uppercase() {
+                snake.push('_');
+            }
+            snake.push(ch.to_ascii_lowercase());
+        }
+        snake
+    };
+    match rule {
+        "lowercase" => name.to_ascii_lowercase(),
+        "UPPERCASE" => name.to_ascii_uppercase(),
+        "camelCase" => lower_first(name),
+        "snake_case" => snake(),
+        "SCREAMING_SNAKE_CASE" => snake().to_ascii_uppercase(),
+        "kebab-case" => snake().replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake().to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_string(),
+    }
+}
+
+/// serde's `RenameRule::apply_to_field`: fields are `snake_case`.
+fn rename_field(name: &str, rule: &str) -> String {
+    let pascal = || {
+        let mut pascal = String::new();
+        let mut capitalize = true;
+        for ch in name.chars() {
+            if ch == '_' {
+                capitalize = true;
+            } else if capitalize {
+                pascal.push(ch.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                pascal.push(ch);
+            }
+        }
+        pascal
+    };
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => lower_first(&pascal()),
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_string(),
+    }
+}
+
+fn lower_first(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+/// The name serde sees: `r#type` serializes as `type`.
+fn unraw(ident: &syn::Ident) -> String {
+    let name = ident.to_string();
+    name.strip_prefix("r#").map(str::to_string).unwrap_or(name)
+}
+
+/// An object key, quoted unless it is a plain identifier.
+fn ts_key(name: &str) -> String {
+    if is_js_ident(name) {
+        name.to_string()
+    } else {
+        ts_string(name)
+    }
+}
+
+/// A TypeScript string literal type.
+fn ts_string(value: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
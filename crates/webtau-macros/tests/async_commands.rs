@@ -0,0 +1,76 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+use webtau::DiagnosticCode;
+
+#[derive(Debug, Default)]
+struct Oven {
+    loaves: u32,
+}
+
+#[derive(Debug, Default)]
+struct Pantry {
+    flour: u32,
+}
+
+/// Pending once, so bodies hold their states across a real suspension.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if std::mem::replace(&mut self.0, true) {
+            return Poll::Ready(());
+        }
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+fn oven_is_locked() -> bool {
+    tauri::Manager::state::<Mutex<Oven>>(&tauri::AppHandle)
+        .try_lock()
+        .is_err()
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub async fn bake(oven: &mut Oven, pantry: &Pantry, loaves: u32) -> Result<u32, String> {
+        if pantry.flour < loaves {
+            return Err(format!("flour for {} loaves only", pantry.flour));
+        }
+        YieldNow(false).await;
+        assert!(oven_is_locked(), "the oven is released mid-body");
+        oven.loaves += loaves;
+        Ok(oven.loaves)
+    }
+
+    #[webtau::command]
+    pub async fn burn(oven: &mut Oven) -> u32 {
+        YieldNow(false).await;
+        panic!("{} loaves burnt", oven.loaves);
+    }
+}
+
+#[test]
+fn async_bodies_run_on_the_runtime_with_their_states_locked() {
+    tauri::manage(Mutex::new(Oven::default()));
+    tauri::manage(Mutex::new(Pantry { flour: 5 }));
+
+    let baked = tauri::async_runtime::block_on(commands::bake(3, tauri::AppHandle));
+    assert_eq!(baked.unwrap(), 3);
+    assert!(!oven_is_locked());
+
+    let err = tauri::async_runtime::block_on(commands::bake(9, tauri::AppHandle)).unwrap_err();
+    assert_eq!(err.message, "flour for 5 loaves only");
+
+    let err = tauri::async_runtime::block_on(commands::burn(tauri::AppHandle)).unwrap_err();
+    assert_eq!(err.code, DiagnosticCode::CommandError);
+    assert!(err.message.contains("3 loaves burnt"), "{}", err.message);
+    assert!(!oven_is_locked());
+}
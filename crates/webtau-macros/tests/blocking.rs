@@ -0,0 +1,74 @@
+use std::sync::Mutex;
+
+use webtau::testing::{json, Harness};
+use webtau::{Cancel, DiagnosticCode};
+
+#[derive(Debug, Default)]
+struct Navmesh {
+    cells: Vec<u32>,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command(blocking)]
+    pub fn rebuild(navmesh: &mut Navmesh, size: u32) -> usize {
+        navmesh.cells = (0..size * size).collect();
+        navmesh.cells.len()
+    }
+
+    #[webtau::command(blocking)]
+    pub fn checksum(bytes: Vec<u8>) -> Result<u32, String> {
+        if bytes.is_empty() {
+            return Err("nothing to check".to_string());
+        }
+        Ok(bytes.iter().map(|&b| u32::from(b)).sum())
+    }
+
+    #[webtau::command(blocking)]
+    pub fn shatter(size: u32) -> usize {
+        panic!("navmesh of {} cells shattered", size);
+    }
+
+    #[webtau::command(blocking, timeout_ms = 60_000)]
+    pub fn trim(navmesh: &mut Navmesh, cancel: Cancel) {
+        while navmesh.cells.len() > 4 && !cancel.is_cancelled() {
+            navmesh.cells.pop();
+        }
+    }
+}
+
+#[test]
+fn tauri_wrappers_run_the_body_off_the_calling_thread() {
+    tauri::manage(Mutex::new(Navmesh::default()));
+    let rebuilt = tauri::async_runtime::block_on(commands::rebuild(3, tauri::AppHandle));
+    assert_eq!(rebuilt.unwrap(), 9);
+
+    tauri::async_runtime::block_on(commands::trim(None, tauri::AppHandle)).unwrap();
+    let navmesh = tauri::Manager::state::<Mutex<Navmesh>>(&tauri::AppHandle);
+    assert_eq!(navmesh.lock().unwrap().cells, [0, 1, 2, 3]);
+
+    let sum = tauri::async_runtime::block_on(commands::checksum(vec![1, 2, 3]));
+    assert_eq!(sum.unwrap(), 6);
+    let err = tauri::async_runtime::block_on(commands::checksum(Vec::new())).unwrap_err();
+    assert_eq!(err.message, "nothing to check");
+}
+
+#[test]
+fn panics_fail_the_command_with_a_diagnostic() {
+    let err = tauri::async_runtime::block_on(commands::shatter(9)).unwrap_err();
+    assert_eq!(err.code, DiagnosticCode::CommandError);
+    assert_eq!(err.command, "shatter");
+    assert!(
+        err.message.contains("navmesh of 9 cells shattered"),
+        "{}",
+        err.message
+    );
+}
+
+#[test]
+fn routes_are_unchanged() {
+    let mut app = Harness::new(Navmesh::default());
+    assert_eq!(app.invoke("rebuild", json!({ "size": 2 })), json!(4));
+    assert_eq!(app.invoke("checksum", json!({ "bytes": [4, 5] })), json!(9));
+}
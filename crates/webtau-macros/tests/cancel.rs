@@ -0,0 +1,104 @@
+use std::sync::Mutex;
+
+use webtau::testing::{json, Harness};
+use webtau::{Cancel, DiagnosticCode};
+
+#[derive(Debug, Default)]
+struct Lightmap {
+    /// Baked chunks, each pushed as a `(chunk, texels)` pair.
+    texels: Vec<u32>,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command(timeout_ms = 60_000)]
+    pub fn bake(lightmap: &mut Lightmap, chunks: u32, cancel: Cancel) -> usize {
+        for chunk in 0..chunks {
+            if cancel.is_cancelled() {
+                break;
+            }
+            lightmap.texels.extend([chunk, 64]);
+        }
+        lightmap.texels.len() / 2
+    }
+
+    #[webtau::command(timeout_ms = 5)]
+    pub fn bake_forever(lightmap: &mut Lightmap, cancel: Cancel) -> Result<u32, String> {
+        let mut chunk = 0;
+        while !cancel.is_cancelled() {
+            lightmap.texels.extend([chunk, 64]);
+            chunk += 1;
+        }
+        Ok(chunk)
+    }
+
+    #[webtau::command]
+    pub async fn wait_for_cancel(lightmap: &Lightmap, cancel: Cancel) {
+        while !cancel.is_cancelled() {
+            cancel.yield_now().await;
+        }
+        let _ = lightmap;
+    }
+}
+
+#[test]
+fn commands_finish_within_their_timeout() {
+    let mut app = Harness::new(Lightmap::default());
+    assert_eq!(app.invoke("bake", json!({ "chunks": 3 })), json!(3));
+    // The `Cancel` arg is optional, and the router ignores whatever is passed.
+    assert_eq!(
+        app.invoke("bake", json!({ "chunks": 1, "cancel": 7 })),
+        json!(4)
+    );
+}
+
+#[test]
+fn timeouts_report_a_diagnostic_and_keep_state_whole() {
+    let mut app = Harness::new(Lightmap::default());
+    let err = app.assert_diagnostic("bake_forever", json!(null), DiagnosticCode::Timeout);
+    assert_eq!(err.command, "bake_forever");
+    assert_eq!(err.message, "`bake_forever` timed out after 5 ms");
+    assert!(!app.state().texels.is_empty());
+    assert_eq!(app.state().texels.len() % 2, 0);
+}
+
+#[test]
+fn cancellable_commands_have_typescript_signatures() {
+    let rendered = webtau::ts::render_commands();
+    for signature in [
+        "bake: { args: { chunks: number; cancel?: AbortSignal }; result: number }",
+        "cancel_bake: { args: { id: number }; result: void }",
+        "wait_for_cancel: { args: { cancel?: AbortSignal }; result: void }",
+    ] {
+        assert!(rendered.contains(signature), "{}\n{}", signature, rendered);
+    }
+}
+
+#[test]
+fn tauri_cancel_commands_stop_the_running_body() {
+    let lightmap = Mutex::new(Lightmap::default());
+
+    // `invoke()` sends `cancel_*` alongside the command; it may arrive first.
+    let canceller = std::thread::spawn(|| {
+        tauri::async_runtime::block_on(commands::cancel_bake_forever(41));
+    });
+    let err = commands::bake_forever(Some(41), tauri::State::new(&lightmap)).unwrap_err();
+    canceller.join().unwrap();
+    assert!(
+        matches!(
+            err.code,
+            DiagnosticCode::Cancelled | DiagnosticCode::Timeout
+        ),
+        "{:?}",
+        err
+    );
+
+    tauri::async_runtime::block_on(commands::cancel_bake(42));
+    let err = commands::bake(3, Some(42), tauri::State::new(&lightmap)).unwrap_err();
+    assert_eq!(err.code, DiagnosticCode::Cancelled);
+    assert_eq!(err.message, "`bake` was cancelled");
+
+    let baked = commands::bake(2, None, tauri::State::new(&lightmap)).unwrap();
+    assert_eq!(baked, lightmap.lock().unwrap().texels.len() / 2);
+}
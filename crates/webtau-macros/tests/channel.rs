@@ -0,0 +1,105 @@
+use serde::Serialize;
+use webtau::channel::SendError;
+use webtau::testing::{json, ChannelItem, Harness};
+use webtau::{Channel, TaskContext};
+
+#[derive(Debug, Default)]
+struct Maze {
+    walls: Vec<u32>,
+}
+
+#[derive(Serialize, webtau::TypeScript)]
+struct Step {
+    tile: u32,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn walk(maze: &Maze, to: u32, steps: Channel<Step>) -> Result<u32, SendError> {
+        let mut walked = 0;
+        for tile in (0..=to).filter(|tile| !maze.walls.contains(tile)) {
+            steps.send(Step { tile })?;
+            walked += 1;
+        }
+        Ok(walked)
+    }
+
+    #[webtau::command(rename_all = "camelCase")]
+    pub async fn count_down(from: u32, on_tick: Channel<u32>) {
+        for n in (1..=from).rev() {
+            let _ = on_tick.send(n);
+        }
+    }
+
+    #[webtau::command(task)]
+    pub fn scan(maze: &mut Maze, ctx: TaskContext, found: Channel<u32>, limit: u32) -> usize {
+        for tile in 0..limit {
+            if tile % 3 == 0 {
+                maze.walls.push(tile);
+                let _ = found.send(tile);
+            }
+            ctx.report_progress(100.0 * f64::from(tile + 1) / f64::from(limit), "");
+        }
+        maze.walls.len()
+    }
+}
+
+fn item(channel: &str, item: serde_json::Value) -> ChannelItem {
+    ChannelItem {
+        channel: channel.to_string(),
+        item,
+    }
+}
+
+#[test]
+fn harness_collects_channel_items() {
+    let mut app = Harness::new(Maze { walls: vec![1] });
+    assert_eq!(app.invoke("walk", json!({ "to": 2 })), json!(2));
+    assert_eq!(
+        app.take_channel_items(),
+        [
+            item("steps", json!({ "tile": 0 })),
+            item("steps", json!({ "tile": 2 })),
+        ]
+    );
+
+    app.invoke("count_down", json!({ "from": 2, "onTick": null }));
+    assert_eq!(
+        app.channel_items(),
+        [item("onTick", json!(2)), item("onTick", json!(1))]
+    );
+}
+
+#[test]
+fn task_bodies_stream_through_channels() {
+    let mut app = Harness::new(Maze::default());
+    let id = app.invoke("start_scan", json!({ "limit": 7 }));
+    assert_eq!(
+        app.invoke("poll_scan", json!({ "id": id })),
+        json!({ "state": "completed", "result": 3 })
+    );
+    let found: Vec<_> = app.channel_items().iter().map(|i| &i.item).collect();
+    assert_eq!(found, [&json!(0), &json!(3), &json!(6)]);
+}
+
+#[test]
+fn channels_are_typed_args() {
+    let rendered = webtau::ts::render_commands();
+    for signature in [
+        "walk: { args: { to: number; steps: import(\"webtau\").Channel<Step> }; result: number }",
+        "count_down: { args: { from: number; onTick: import(\"webtau\").Channel<number> }; result: void }",
+    ] {
+        assert!(rendered.contains(signature), "{}\n{}", signature, rendered);
+    }
+}
+
+#[test]
+fn tauri_wrappers_send_through_the_ipc_channel() {
+    let maze = std::sync::Mutex::new(Maze::default());
+    let steps = tauri::ipc::Channel::new();
+    let walked = commands::walk(1, steps.clone(), tauri::State::new(&maze)).unwrap();
+    assert_eq!(walked, 2);
+    assert_eq!(steps.take(), [json!({ "tile": 0 }), json!({ "tile": 1 })]);
+}
@@ -0,0 +1,8 @@
+struct MyState;
+
+#[webtau_macros::command(rename_all = "kebab-case")]
+fn tick(state: &mut MyState, delta_time: f64) {
+    let _ = (state, delta_time);
+}
+
+fn main() {}
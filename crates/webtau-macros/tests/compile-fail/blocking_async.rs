@@ -0,0 +1,8 @@
+struct World;
+
+#[webtau_macros::command(blocking)]
+async fn tick_world(state: &mut World) {
+    let _ = state;
+}
+
+fn main() {}
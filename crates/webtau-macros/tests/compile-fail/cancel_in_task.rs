@@ -0,0 +1,8 @@
+struct World;
+
+#[webtau_macros::command(task)]
+fn bake(world: &mut World, ctx: webtau::TaskContext, cancel: webtau::Cancel) {
+    let _ = (world, ctx, cancel);
+}
+
+fn main() {}
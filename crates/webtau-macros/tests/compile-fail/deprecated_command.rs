@@ -0,0 +1,13 @@
+#![deny(deprecated)]
+
+mod commands {
+    #[webtau_macros::command]
+    #[deprecated(note = "use version_v2")]
+    pub fn version() -> u32 {
+        1
+    }
+}
+
+fn main() {
+    let _ = commands::version();
+}
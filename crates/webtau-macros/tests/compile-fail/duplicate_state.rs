@@ -0,0 +1,9 @@
+struct World { tick: u64 }
+
+#[webtau_macros::command]
+fn merge(a: &mut World, b: &World) -> u64 {
+    a.tick += b.tick;
+    a.tick
+}
+
+fn main() {}
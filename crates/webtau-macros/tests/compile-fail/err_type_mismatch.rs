@@ -0,0 +1,20 @@
+struct Saves;
+
+#[derive(Debug)]
+struct SaveError;
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("save error")
+    }
+}
+
+type Loaded = Result<u32, String>;
+
+#[webtau_macros::command(err = SaveError)]
+fn load(saves: &Saves) -> Loaded {
+    let _ = saves;
+    Ok(1)
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+struct MyState;
+
+#[webtau_macros::command(fallible)]
+fn reset(state: &mut MyState) {
+    let _ = state;
+}
+
+fn main() {}
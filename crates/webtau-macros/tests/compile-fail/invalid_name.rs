@@ -0,0 +1,8 @@
+struct World;
+
+#[webtau_macros::command(name = "world/tick")]
+fn tick(world: &mut World) {
+    let _ = world;
+}
+
+fn main() {}
@@ -6,7 +6,8 @@ struct View { value: i32 }
 struct MyState { value: i32 }
 
 #[webtau_macros::command]
-async fn tick(state: &MyState) -> View {
+fn tick(dt: f64, state: &mut MyState) -> View {
+    state.value += dt as i32;
     View { value: state.value }
 }
 
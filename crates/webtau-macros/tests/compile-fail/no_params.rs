@@ -1,6 +0,0 @@
-#[webtau_macros::command]
-fn tick() -> i32 {
-    42
-}
-
-fn main() {}
@@ -0,0 +1,16 @@
+struct Shop;
+
+// Neither `Serialize` nor `Display`: the error cannot reach JS.
+struct Opaque;
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub fn buy(shop: &mut Shop) -> Result<(), Opaque> {
+        let _ = shop;
+        Err(Opaque)
+    }
+}
+
+fn main() {}
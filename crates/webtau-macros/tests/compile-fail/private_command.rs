@@ -0,0 +1,14 @@
+struct World;
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    fn reset(world: &mut World) {
+        let _ = world;
+    }
+}
+
+fn main() {
+    let _ = commands::reset;
+}
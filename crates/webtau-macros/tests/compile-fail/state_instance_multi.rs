@@ -0,0 +1,9 @@
+struct World;
+struct Profile;
+
+#[webtau_macros::command(state = "rival")]
+fn raid(world: &mut World, profile: &Profile) {
+    let _ = (world, profile);
+}
+
+fn main() {}
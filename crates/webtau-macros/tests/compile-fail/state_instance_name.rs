@@ -0,0 +1,8 @@
+struct Profile;
+
+#[webtau_macros::command(state = "rival-2")]
+fn gold(profile: &Profile) {
+    let _ = profile;
+}
+
+fn main() {}
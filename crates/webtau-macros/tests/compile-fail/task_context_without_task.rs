@@ -0,0 +1,8 @@
+struct Terrain;
+
+#[webtau_macros::command]
+fn generate(terrain: &mut Terrain, ctx: webtau::TaskContext) {
+    let _ = (terrain, ctx);
+}
+
+fn main() {}
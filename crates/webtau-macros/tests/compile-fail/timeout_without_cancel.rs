@@ -0,0 +1,8 @@
+struct World;
+
+#[webtau_macros::command(timeout_ms = 500)]
+fn bake(world: &mut World) {
+    let _ = world;
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+struct MyState;
+
+#[webtau_macros::command(rename = "camelCase")]
+fn tick(state: &mut MyState) {
+    let _ = state;
+}
+
+fn main() {}
@@ -0,0 +1,57 @@
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Serialize, Clone)]
+struct View { value: i32 }
+
+#[derive(Serialize, Clone, Debug)]
+struct LoadError(String);
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+struct Game { value: i32 }
+
+async fn fetch_delta(by: i32) -> i32 {
+    by
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub async fn peek(state: &Game) -> View {
+        let delta = fetch_delta(0).await;
+        View { value: state.value + delta }
+    }
+
+    #[webtau_macros::command]
+    pub async fn load(state: &mut Game, by: i32) -> Result<View, LoadError> {
+        if by < 0 {
+            return Err(LoadError("negative".into()));
+        }
+        state.value += fetch_delta(by).await;
+        Ok(View { value: state.value })
+    }
+
+    #[webtau_macros::command]
+    pub async fn clear(state: &mut Game) {
+        fetch_delta(0).await;
+        state.value = 0;
+    }
+}
+
+fn assert_send<T: Send>(_: T) {}
+
+// Tauri requires async command futures to be `Send`; the state lock must not
+// be held inside the wrapper future.
+fn _wrappers_are_send(app: tauri::AppHandle) {
+    assert_send(commands::peek(app.clone()));
+    assert_send(commands::load(1, app.clone()));
+    assert_send(commands::clear(app));
+}
+
+fn main() {}
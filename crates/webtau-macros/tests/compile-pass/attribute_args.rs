@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone)]
+struct View {
+    tick: u64,
+}
+
+struct World {
+    tick: u64,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command(name = "tick_world", rename_all = "camelCase")]
+    fn tick(world: &mut World, delta_ticks: u64) -> View {
+        world.tick += delta_ticks;
+        View { tick: world.tick }
+    }
+
+    #[webtau_macros::command(js_name = "getView")]
+    fn get_view(world: &World) -> View {
+        View { tick: world.tick }
+    }
+
+    #[webtau_macros::command(name = "reset_world", js_name = "resetWorld", rename_all = "snake_case")]
+    async fn reset(world: &mut World, start_tick: u64) {
+        world.tick = start_tick;
+    }
+
+    // The wrappers are registered under the overridden names.
+    #[allow(dead_code)]
+    fn _registered_names() {
+        let _ = tick_world;
+        let _ = get_view;
+        let _ = reset_world;
+    }
+}
+
+fn main() {}
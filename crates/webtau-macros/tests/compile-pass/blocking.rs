@@ -0,0 +1,34 @@
+struct World {
+    tick: u64,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command(blocking)]
+    pub fn tick_world(state: &mut World, steps: u64) -> u64 {
+        state.tick += steps;
+        state.tick
+    }
+
+    #[webtau_macros::command(blocking, rename_all = "camelCase")]
+    pub fn hash_save(save_bytes: Vec<u8>, emitter: webtau::Emitter) -> Result<u64, String> {
+        let _ = emitter.emit("hashing", save_bytes.len());
+        Ok(save_bytes.iter().map(|&b| u64::from(b)).sum())
+    }
+
+    #[webtau_macros::command(blocking)]
+    pub fn reset(state: &mut World) {
+        state.tick = 0;
+    }
+}
+
+fn assert_send<T: Send>(_: T) {}
+
+fn _wrappers(app: tauri::AppHandle) {
+    assert_send(commands::tick_world(1, app.clone()));
+    assert_send(commands::hash_save(Vec::new(), app.clone()));
+    assert_send(commands::reset(app));
+}
+
+fn main() {}
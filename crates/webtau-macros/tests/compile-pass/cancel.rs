@@ -0,0 +1,40 @@
+struct World {
+    baked: u32,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command(timeout_ms = 250)]
+    pub fn bake(state: &mut World, cancel: webtau::Cancel, chunks: u32) -> u32 {
+        for _ in 0..chunks {
+            if cancel.is_cancelled() {
+                break;
+            }
+            state.baked += 1;
+        }
+        state.baked
+    }
+
+    #[webtau_macros::command(rename_all = "camelCase", timeout_ms = 1_000)]
+    pub async fn bake_later(state: &mut World, cancel: webtau::Cancel) -> Result<(), String> {
+        cancel.yield_now().await;
+        state.baked = 0;
+        Ok(())
+    }
+
+    #[webtau_macros::command(js_name = "pollLights")]
+    pub fn poll_lights(cancel: webtau::Cancel) {
+        let _ = cancel.is_timed_out();
+    }
+}
+
+fn assert_send<T: Send>(_: T) {}
+
+fn _wrappers(app: tauri::AppHandle) {
+    assert_send(commands::bake_later(None, app));
+    assert_send(commands::cancel_bake_later(1));
+    let _: Result<(), webtau::Diagnostic> = commands::poll_lights(Some(2));
+}
+
+fn main() {}
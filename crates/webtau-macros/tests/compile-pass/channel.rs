@@ -0,0 +1,41 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Chunk { bytes: Vec<u8> }
+
+struct Archive { chunks: Vec<Vec<u8>> }
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub fn stream(state: &Archive, mut out: webtau::Channel<Chunk>) -> usize {
+        for bytes in &state.chunks {
+            let _ = out.send(Chunk { bytes: bytes.clone() });
+        }
+        out = out.clone();
+        drop(out);
+        state.chunks.len()
+    }
+
+    #[webtau_macros::command]
+    pub async fn stream_later(state: &mut Archive, out: webtau::Channel<u32>) -> Result<(), webtau::channel::SendError> {
+        out.send(state.chunks.len() as u32)
+    }
+
+    #[webtau_macros::command(task)]
+    pub fn unpack(state: &mut Archive, ctx: webtau::TaskContext, progress: webtau::Channel<f64>) {
+        let _ = progress.send(50.0);
+        ctx.report_progress(100.0, "");
+        state.chunks.clear();
+    }
+}
+
+fn assert_send<T: Send>(_: T) {}
+
+fn _wrappers(app: tauri::AppHandle) {
+    assert_send(commands::stream_later(tauri::ipc::Channel::new(), app.clone()));
+    let _ = commands::start_unpack(tauri::ipc::Channel::new(), app);
+}
+
+fn main() {}
@@ -0,0 +1,32 @@
+struct Inventory {
+    slots: u32,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub fn take_slot(inventory: &mut Inventory) -> Result<u32, String> {
+        inventory.slots = inventory.slots.checked_sub(1).ok_or("inventory is empty")?;
+        Ok(inventory.slots)
+    }
+
+    #[webtau_macros::command]
+    pub async fn validate(code: String) -> Result<(), std::io::Error> {
+        if code.is_empty() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "empty code"));
+        }
+        Ok(())
+    }
+}
+
+// Native wrappers report command errors as `webtau::Diagnostic`.
+fn _native_error_type(state: tauri::State<'_, std::sync::Mutex<Inventory>>) {
+    let _: Result<u32, webtau::Diagnostic> = commands::take_slot(state);
+}
+
+async fn _native_async_error_type() {
+    let _: Result<(), webtau::Diagnostic> = commands::validate(String::new()).await;
+}
+
+fn main() {}
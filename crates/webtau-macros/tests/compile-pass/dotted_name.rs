@@ -0,0 +1,47 @@
+use tauri::ipc::Invoke;
+
+#[derive(Default)]
+struct World {
+    tick: u64,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command(name = "world.tick")]
+    pub fn tick(world: &mut World, ticks: u64) -> u64 {
+        world.tick += ticks;
+        world.tick
+    }
+
+    #[webtau_macros::command(name = "world.reset.all")]
+    pub fn reset(world: &mut World) {
+        world.tick = 0;
+    }
+}
+
+fn dispatch(command: &str) -> Option<String> {
+    let handler = webtau::generate_handler!();
+    let (invoke, resolved) = Invoke::new(command);
+    handler(invoke).then(|| resolved.recv().unwrap())
+}
+
+fn main() {
+    // The Tauri command is renamed back to the dotted name.
+    assert_eq!(dispatch("world.tick").as_deref(), Some("world_tick"));
+    assert_eq!(dispatch("world.reset.all").as_deref(), Some("world_reset_all"));
+    assert_eq!(dispatch("world_tick"), None);
+
+    let world = std::sync::Mutex::new(World::default());
+    assert_eq!(commands::world_tick(2, tauri::State::new(&world)), 2);
+
+    let router = webtau::Router::<World>::new();
+    let mut state = World::default();
+    let ticked = router
+        .dispatch(&mut state, "world.tick", serde_json::json!({ "ticks": 3 }))
+        .unwrap();
+    assert_eq!(ticked, serde_json::json!(3));
+
+    let rendered = webtau::ts::render_commands();
+    assert!(rendered.contains("\"world.tick\": { args: { ticks: number }; result: number }"));
+}
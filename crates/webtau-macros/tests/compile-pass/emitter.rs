@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone)]
+struct Hit { id: u32 }
+
+struct Game { hits: u32 }
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub fn hit(state: &mut Game, events: webtau::Emitter, id: u32) {
+        state.hits += 1;
+        let _ = events.emit("hit", Hit { id });
+    }
+
+    #[webtau_macros::command]
+    pub async fn hit_later(state: &mut Game, id: u32, events: webtau::Emitter) -> u32 {
+        let _ = events.emit("hit", &Hit { id });
+        state.hits
+    }
+
+    #[webtau_macros::command]
+    pub fn notify(events: webtau::Emitter) -> Result<(), webtau::events::EmitError> {
+        events.emit("notify", ())
+    }
+}
+
+fn assert_send<T: Send>(_: T) {}
+
+fn _wrappers_are_send(app: tauri::AppHandle) {
+    assert_send(commands::hit_later(1, app));
+}
+
+fn main() {}
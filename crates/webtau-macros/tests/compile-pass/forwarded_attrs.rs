@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone)]
+struct View {
+    score: i32,
+}
+
+struct World {
+    score: i32,
+}
+
+mod commands {
+    use super::*;
+
+    /// Returns the current view of the world.
+    #[webtau_macros::command]
+    pub fn get_view(world: &World) -> View {
+        View { score: world.score }
+    }
+
+    /// Crate-internal command.
+    #[webtau_macros::command]
+    #[allow(clippy::needless_pass_by_value)]
+    pub(crate) fn add_score(world: &mut World, amount: String) {
+        world.score += amount.len() as i32;
+    }
+
+    #[webtau_macros::command]
+    #[inline]
+    fn private_reset(world: &mut World) {
+        world.score = 0;
+    }
+
+    // `#[cfg]` gates the inner fn and both wrappers: this body names a type
+    // that does not exist, so any leaked item would fail to compile.
+    #[webtau_macros::command]
+    #[cfg(any())]
+    pub fn dev_only(world: &World) -> DoesNotExist {
+        DoesNotExist::from(world)
+    }
+
+    #[webtau_macros::command]
+    #[deprecated(note = "use get_view")]
+    pub async fn legacy_view(world: &World) -> View {
+        View { score: world.score }
+    }
+
+    pub(crate) fn _private_is_usable_in_module() {
+        let _ = private_reset;
+    }
+}
+
+fn main() {
+    let _ = commands::get_view;
+    let _ = commands::add_score;
+}
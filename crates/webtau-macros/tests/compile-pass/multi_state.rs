@@ -0,0 +1,40 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone)]
+struct Receipt { gold: u32, items: usize }
+
+struct World { tick: u64 }
+struct Profile { gold: u32, items: Vec<u32> }
+struct Settings { price: u32 }
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub fn buy(
+        world: &mut World,
+        profile: &mut Profile,
+        settings: &Settings,
+        item: u32,
+    ) -> Result<Receipt, String> {
+        if profile.gold < settings.price {
+            return Err("not enough gold".into());
+        }
+        world.tick += 1;
+        profile.gold -= settings.price;
+        profile.items.push(item);
+        Ok(Receipt { gold: profile.gold, items: profile.items.len() })
+    }
+
+    #[webtau_macros::command]
+    pub fn summary(profile: &Profile, world: &World) -> u64 {
+        world.tick + profile.gold as u64
+    }
+
+    #[webtau_macros::command]
+    pub async fn sync_profile(profile: &mut Profile, world: &World) {
+        profile.gold += world.tick as u32;
+    }
+}
+
+fn main() {}
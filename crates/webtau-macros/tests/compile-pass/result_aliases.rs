@@ -0,0 +1,69 @@
+use serde::Serialize;
+
+struct Saves {
+    slots: Vec<String>,
+}
+
+#[derive(Serialize, Clone)]
+struct Save {
+    name: String,
+}
+
+#[derive(Serialize, Debug)]
+enum SaveError {
+    Missing,
+}
+
+mod local {
+    pub type Result<T> = std::result::Result<T, super::SaveError>;
+}
+
+type AppResult<T> = Result<T, SaveError>;
+type Loaded = Result<Save, SaveError>;
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub fn load(saves: &Saves, slot: usize) -> anyhow::Result<Save> {
+        let name = saves
+            .slots
+            .get(slot)
+            .ok_or_else(|| anyhow::anyhow!("slot {slot} is empty"))?;
+        Ok(Save { name: name.clone() })
+    }
+
+    #[webtau_macros::command]
+    pub async fn export(saves: &Saves) -> std::io::Result<usize> {
+        Ok(saves.slots.len())
+    }
+
+    #[webtau_macros::command]
+    pub fn first(saves: &Saves) -> local::Result<Save> {
+        let name = saves.slots.first().ok_or(SaveError::Missing)?;
+        Ok(Save { name: name.clone() })
+    }
+
+    #[webtau_macros::command(fallible)]
+    pub fn last(saves: &Saves) -> AppResult<Save> {
+        let name = saves.slots.last().ok_or(SaveError::Missing)?;
+        Ok(Save { name: name.clone() })
+    }
+
+    #[webtau_macros::command(err = SaveError)]
+    pub fn newest(saves: &Saves) -> Loaded {
+        let name = saves.slots.last().ok_or(SaveError::Missing)?;
+        Ok(Save { name: name.clone() })
+    }
+}
+
+type SavesState<'a> = tauri::State<'a, std::sync::Mutex<Saves>>;
+
+fn _native_error_types() {
+    let _: fn(usize, SavesState<'_>) -> Result<Save, webtau::Diagnostic> = commands::load;
+    let _: fn(SavesState<'_>) -> Result<Save, webtau::Diagnostic> = commands::first;
+    let _: fn(SavesState<'_>) -> Result<Save, webtau::Diagnostic> = commands::last;
+    let _: fn(SavesState<'_>) -> Result<Save, webtau::Diagnostic> = commands::newest;
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+struct World { tick: u64 }
+struct Profile { gold: u32 }
+
+mod commands {
+    use super::*;
+
+    // Borrows the `wasm_state!(rival: Profile)` instance on WASM
+    #[webtau_macros::command(state = "rival")]
+    pub fn rival_gold(profile: &Profile) -> u32 {
+        profile.gold
+    }
+
+    // Names the instance per state parameter
+    #[webtau_macros::command(state(profile = "rival", world = "state"))]
+    pub fn raid(world: &mut World, profile: &mut Profile) {
+        world.tick += 1;
+        profile.gold += 1;
+    }
+
+    #[webtau_macros::command(state(profile = "rival"))]
+    pub async fn rival_tick(world: &World, profile: &Profile) -> u64 {
+        world.tick + profile.gold as u64
+    }
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Clone)]
+struct VersionInfo { major: u32, minor: u32 }
+
+#[derive(Deserialize)]
+struct Point { x: f64, y: f64 }
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    fn version() -> VersionInfo {
+        VersionInfo { major: 0, minor: 7 }
+    }
+
+    #[webtau_macros::command]
+    fn distance(a: Point, b: Point) -> f64 {
+        ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+    }
+
+    #[webtau_macros::command]
+    fn clamp(value: i32, min: i32, max: i32) -> Result<i32, String> {
+        if min > max {
+            return Err("min must not exceed max".into());
+        }
+        Ok(value.clamp(min, max))
+    }
+
+    #[webtau_macros::command]
+    fn ping() {}
+
+    #[webtau_macros::command]
+    async fn lookup(id: u32) -> String {
+        format!("item-{id}")
+    }
+}
+
+fn main() {}
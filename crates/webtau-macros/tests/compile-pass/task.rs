@@ -0,0 +1,40 @@
+use webtau::TaskContext;
+
+struct Terrain { rows: u32 }
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command(task)]
+    pub fn generate(state: &mut Terrain, ctx: TaskContext, rows: u32) -> u32 {
+        for _ in 0..rows {
+            if ctx.is_cancelled() {
+                break;
+            }
+            state.rows += 1;
+            ctx.report_progress(50.0, "rows");
+        }
+        state.rows
+    }
+
+    #[webtau_macros::command(task)]
+    pub async fn settle(state: &Terrain, ctx: TaskContext, events: webtau::Emitter) -> Result<u32, String> {
+        ctx.yield_now().await;
+        let _ = events.emit("settled", state.rows);
+        Ok(state.rows)
+    }
+
+    /// Tasks need not report anything.
+    #[webtau_macros::command(task)]
+    pub fn warm_cache() {}
+}
+
+fn _wrappers(app: tauri::AppHandle) {
+    let _: u32 = commands::start_generate(3, app.clone());
+    let _: u32 = commands::start_settle(app);
+    let id = commands::start_warm_cache();
+    let _ = commands::poll_warm_cache(id);
+    commands::cancel_warm_cache(id);
+}
+
+fn main() {}
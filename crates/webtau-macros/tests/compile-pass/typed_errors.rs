@@ -0,0 +1,52 @@
+use serde::Serialize;
+
+struct Shop {
+    gold: u32,
+}
+
+/// Serialize-only error: serialized into the diagnostic's `details`.
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+enum BuyError {
+    NotEnoughGold { short: u32 },
+}
+
+/// Serialize + Display error: `Display` provides the diagnostic's message.
+#[derive(Serialize)]
+struct Rejected {
+    reason: String,
+}
+
+impl std::fmt::Display for Rejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau_macros::command]
+    pub fn buy(shop: &mut Shop, cost: u32) -> Result<u32, BuyError> {
+        if cost > shop.gold {
+            return Err(BuyError::NotEnoughGold { short: cost - shop.gold });
+        }
+        shop.gold -= cost;
+        Ok(shop.gold)
+    }
+
+    #[webtau_macros::command]
+    pub async fn sell(shop: &mut Shop, price: u32) -> Result<u32, Rejected> {
+        if price == 0 {
+            return Err(Rejected { reason: "nothing to sell".into() });
+        }
+        shop.gold += price;
+        Ok(shop.gold)
+    }
+}
+
+fn _native_error_type(state: tauri::State<'_, std::sync::Mutex<Shop>>) {
+    let _: Result<u32, webtau::Diagnostic> = commands::buy(1, state);
+}
+
+fn main() {}
@@ -0,0 +1,178 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use webtau::testing::{json, EmittedEvent, Harness};
+use webtau::Emitter;
+
+#[derive(Debug, Default)]
+struct Arena {
+    enemies: Vec<u32>,
+}
+
+#[derive(Serialize)]
+struct Destroyed {
+    id: u32,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn strike(arena: &mut Arena, events: Emitter, damage: u32) -> usize {
+        let (destroyed, alive) = arena.enemies.iter().partition(|hp| **hp <= damage);
+        let destroyed: Vec<u32> = destroyed;
+        for id in destroyed {
+            events.emit("enemy-destroyed", Destroyed { id }).unwrap();
+        }
+        arena.enemies = alive;
+        arena.enemies.len()
+    }
+
+    #[webtau::command]
+    pub async fn spawn(arena: &mut Arena, hp: u32, events: Emitter) {
+        arena.enemies.push(hp);
+        events.emit("enemy-spawned", hp).unwrap();
+    }
+
+    #[webtau::command]
+    pub fn announce(events: Emitter, text: String) -> Result<(), webtau::events::EmitError> {
+        events.emit("announcement", &text)
+    }
+}
+
+fn event(event: &str, payload: serde_json::Value) -> EmittedEvent {
+    EmittedEvent {
+        event: event.to_string(),
+        payload,
+    }
+}
+
+#[test]
+fn harness_collects_emitted_events() {
+    let mut app = Harness::new(Arena::default());
+    for hp in [3, 9, 1] {
+        app.invoke("spawn", json!({ "hp": hp }));
+    }
+    assert_eq!(app.take_events().len(), 3);
+
+    assert_eq!(app.invoke("strike", json!({ "damage": 3 })), json!(1));
+    assert_eq!(
+        app.events(),
+        [
+            event("enemy-destroyed", json!({ "id": 3 })),
+            event("enemy-destroyed", json!({ "id": 1 })),
+        ]
+    );
+
+    app.invoke("announce", json!({ "text": "wave 2" }));
+    assert_eq!(
+        app.events().last(),
+        Some(&event("announcement", json!("wave 2")))
+    );
+}
+
+#[test]
+fn emitter_is_not_an_arg() {
+    let mut app = Harness::new(Arena::default());
+    app.invoke("strike", json!({ "damage": 1, "events": "ignored" }));
+    let signature = webtau::ts::render_commands();
+    assert!(
+        signature.contains("strike: { args: { damage: number }; result: number }"),
+        "{}",
+        signature
+    );
+}
+
+#[test]
+fn tauri_wrappers_emit_through_the_app_handle() {
+    let arena = Mutex::new(Arena {
+        enemies: vec![2, 5],
+    });
+    tauri::take_emitted();
+
+    let left = commands::strike(2, tauri::State::new(&arena), tauri::AppHandle);
+    assert_eq!(left, 1);
+    commands::announce("hi".to_string(), tauri::AppHandle).unwrap();
+    assert_eq!(
+        tauri::take_emitted(),
+        [
+            ("enemy-destroyed".to_string(), json!({ "id": 2 })),
+            ("announcement".to_string(), json!("hi")),
+        ]
+    );
+}
+
+// ── Listening ─────────────────────────────────────────────────────────
+
+#[derive(Debug, Default, PartialEq, serde::Deserialize)]
+struct Settings {
+    volume: f32,
+}
+
+#[webtau::command]
+fn volume(settings: &Settings) -> f32 {
+    settings.volume
+}
+
+#[test]
+fn harness_delivers_frontend_events_to_listeners() {
+    let id = webtau::events::listen("enemy-healed", |arena: &mut Arena, hp: u32| {
+        arena.enemies.push(hp);
+    });
+    webtau::events::listen(
+        "settings-changed-harness",
+        |settings: &mut Settings, patch: Settings| {
+            *settings = patch;
+        },
+    );
+
+    let mut app = Harness::new(Arena::default());
+    app.emit_from_frontend("enemy-healed", json!(4)).unwrap();
+    // Listeners for other state types are not the harness's to run.
+    app.emit_from_frontend("settings-changed-harness", json!({ "volume": 1.0 }))
+        .unwrap();
+    assert_eq!(app.state().enemies, [4]);
+
+    let err = app
+        .emit_from_frontend("enemy-healed", json!("lots"))
+        .unwrap_err();
+    assert_eq!(err.code, webtau::DiagnosticCode::ArgsInvalid);
+    assert_eq!(err.command, "enemy-healed");
+
+    webtau::events::unlisten(id);
+    app.emit_from_frontend("enemy-healed", json!(5)).unwrap();
+    assert_eq!(app.state().enemies, [4]);
+}
+
+#[test]
+fn tauri_bridge_locks_the_managed_state() {
+    tauri::manage(Mutex::new(Settings::default()));
+    let volume = || {
+        tauri::Manager::try_state::<Mutex<Settings>>(&tauri::AppHandle)
+            .unwrap()
+            .lock()
+            .unwrap()
+            .volume
+    };
+
+    webtau::events::listen(
+        "settings-changed",
+        |settings: &mut Settings, patch: Settings| {
+            *settings = patch;
+        },
+    );
+    webtau::attach_listeners!(&tauri::AppHandle);
+    tauri::emit_from_frontend("settings-changed", r#"{ "volume": 0.25 }"#);
+    assert_eq!(volume(), 0.25);
+
+    // Listeners added after attaching subscribe on the spot.
+    webtau::events::listen("settings-muted", |settings: &mut Settings, (): ()| {
+        settings.volume = 0.0;
+    });
+    tauri::emit_from_frontend("settings-muted", "null");
+    assert_eq!(volume(), 0.0);
+
+    // A bad payload is reported, not delivered.
+    tauri::emit_from_frontend("settings-changed", r#"{ "volume": "max" }"#);
+    assert_eq!(volume(), 0.0);
+}
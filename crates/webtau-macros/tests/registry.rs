@@ -0,0 +1,60 @@
+use tauri::ipc::Invoke;
+
+struct World {
+    tick: u64,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn ping() -> String {
+        "pong".to_string()
+    }
+
+    #[webtau::command(name = "tick_world")]
+    pub fn tick(world: &mut World, ticks: u64) -> u64 {
+        world.tick += ticks;
+        world.tick
+    }
+
+    #[webtau::command]
+    async fn reset(world: &mut World) {
+        world.tick = 0;
+    }
+
+    #[cfg(any())]
+    #[webtau::command]
+    pub fn disabled() {}
+}
+
+mod crate_root {
+    webtau::export_commands!(super::commands);
+}
+
+/// Routes `command` through `webtau::generate_handler!` and returns what the
+/// matched Tauri handler resolved with.
+fn dispatch(command: &str) -> Option<String> {
+    let handler = webtau::generate_handler!();
+    let (invoke, resolved) = Invoke::new(command);
+    handler(invoke).then(|| resolved.recv().unwrap())
+}
+
+#[test]
+fn commands_register_themselves() {
+    assert_eq!(dispatch("ping").as_deref(), Some("ping"));
+    assert_eq!(dispatch("tick_world").as_deref(), Some("tick_world"));
+    assert_eq!(dispatch("reset").as_deref(), Some("reset"));
+}
+
+#[test]
+fn unknown_and_disabled_commands_are_not_handled() {
+    assert_eq!(dispatch("tick"), None);
+    assert_eq!(dispatch("disabled"), None);
+    assert_eq!(dispatch("nope"), None);
+}
+
+#[test]
+fn export_commands_reexports_public_wrappers() {
+    assert_eq!(crate_root::ping(), "pong");
+}
@@ -0,0 +1,237 @@
+use std::sync::{Mutex, MutexGuard};
+
+use serde_json::json;
+use webtau::replay::{self, CommandLog, LogError, Recorder};
+use webtau::DiagnosticCode;
+
+#[derive(Debug, Default, PartialEq)]
+struct World {
+    tick: u64,
+    log: Vec<String>,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn get_tick(world: &World) -> u64 {
+        world.tick
+    }
+
+    #[webtau::command(name = "advance")]
+    pub fn tick(world: &mut World, ticks: u64, label: Option<String>) -> u64 {
+        world.tick += ticks;
+        world.log.extend(label);
+        world.tick
+    }
+
+    #[webtau::command(rename_all = "camelCase")]
+    pub fn set_tick(world: &mut World, new_tick: u64) {
+        world.tick = new_tick;
+    }
+
+    #[webtau::command]
+    pub fn rewind(world: &mut World, ticks: u64) -> Result<u64, String> {
+        world.tick = world
+            .tick
+            .checked_sub(ticks)
+            .ok_or_else(|| format!("cannot rewind {} ticks", ticks))?;
+        Ok(world.tick)
+    }
+
+    #[webtau::command]
+    pub fn join(lobby: &mut Lobby, player: String) {
+        lobby.players.push(player);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Lobby {
+    players: Vec<String>,
+}
+
+/// The recorder is process-wide; tests that start one take turns.
+fn exclusive() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    LOCK.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+/// Plays a session through the Tauri wrappers, as the desktop app would.
+fn play(world: &Mutex<World>) {
+    let state = || tauri::State::new(world);
+    commands::advance(3, Some("warp".into()), state());
+    commands::get_tick(state());
+    commands::rewind(10, state()).unwrap_err();
+    commands::set_tick(40, state());
+    commands::advance(2, None, state());
+}
+
+#[test]
+fn records_mutating_commands_in_call_order() {
+    let _guard = exclusive();
+    let world = Mutex::new(World::default());
+
+    assert!(!replay::is_recording());
+    replay::start(Recorder::ring(16)).unwrap();
+    assert!(replay::is_recording());
+    play(&world);
+    let log = replay::stop();
+    assert!(!replay::is_recording());
+
+    let recorded: Vec<_> = log
+        .entries
+        .iter()
+        .map(|entry| (entry.seq, entry.command.as_str(), entry.args.clone()))
+        .collect();
+    assert_eq!(
+        recorded,
+        [
+            (0, "advance", json!({ "ticks": 3, "label": "warp" })),
+            (1, "rewind", json!({ "ticks": 10 })),
+            (2, "set_tick", json!({ "newTick": 40 })),
+            (3, "advance", json!({ "ticks": 2, "label": null })),
+        ]
+    );
+    assert!(log.entries.iter().all(|entry| entry.timestamp > 0));
+    let errors: Vec<_> = log
+        .entries
+        .iter()
+        .map(|entry| entry.error.as_ref())
+        .collect();
+    assert!(matches!(
+        errors[..],
+        [None, Some(error), None, None]
+            if error.code == DiagnosticCode::CommandError && error.message == "cannot rewind 10 ticks"
+    ));
+    assert_eq!(log.header.webtau, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn replay_rebuilds_state_from_a_fresh_initializer() {
+    let _guard = exclusive();
+    let world = Mutex::new(World::default());
+
+    replay::start(Recorder::ring(16)).unwrap();
+    play(&world);
+    let log = CommandLog::from_jsonl(&replay::stop().to_jsonl()).unwrap();
+
+    let rebuilt = replay::replay(&log, World::default).unwrap();
+    assert_eq!(rebuilt, world.into_inner().unwrap());
+    assert_eq!(rebuilt.tick, 42);
+}
+
+#[test]
+fn wrapped_ring_cannot_replay_from_scratch() {
+    let _guard = exclusive();
+    let world = Mutex::new(World::default());
+
+    replay::start(Recorder::ring(2)).unwrap();
+    play(&world);
+    assert_eq!(replay::recorded().entries.len(), 2);
+    let log = replay::stop();
+
+    assert!(!log.is_complete());
+    assert!(matches!(
+        replay::replay(&log, World::default),
+        Err(LogError::Truncated { first_seq: 2 })
+    ));
+
+    let mut resumed = World::default();
+    replay::apply(&log, &mut resumed).unwrap();
+    assert_eq!(resumed.tick, 42);
+}
+
+#[test]
+fn file_recorder_appends_each_entry() {
+    let _guard = exclusive();
+    let world = Mutex::new(World::default());
+    let path = std::env::temp_dir()
+        .join(format!("webtau-replay-{}", std::process::id()))
+        .join("session.jsonl");
+
+    replay::start(Recorder::file(&path)).unwrap();
+    play(&world);
+    let jsonl = std::fs::read_to_string(&path).unwrap();
+    assert!(replay::stop().entries.is_empty());
+
+    let mut lines = jsonl.lines();
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with(r#"{"format":"webtau-command-log","version":1,"#));
+    assert_eq!(lines.count(), 4);
+
+    let log = CommandLog::load(&path).unwrap();
+    assert_eq!(replay::replay(&log, World::default).unwrap().tick, 42);
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn unknown_commands_fail_replay() {
+    let jsonl = format!(
+        "{}\n{}\n",
+        json!({ "format": "webtau-command-log", "version": 1, "webtau": "0.1.0" }),
+        json!({ "seq": 0, "command": "warp", "args": {}, "timestamp": 1 }),
+    );
+    let log = CommandLog::from_jsonl(&jsonl).unwrap();
+    match replay::replay(&log, World::default) {
+        Err(LogError::Dispatch { seq: 0, diagnostic }) => {
+            assert_eq!(diagnostic.code, DiagnosticCode::UnknownCommand)
+        }
+        other => panic!("expected an unknown command, got {:?}", other),
+    }
+}
+
+#[test]
+fn replay_rejects_other_states_commands_by_index() {
+    let _guard = exclusive();
+    let world = Mutex::new(World::default());
+    let lobby = Mutex::new(Lobby::default());
+
+    replay::start(Recorder::ring(16)).unwrap();
+    commands::advance(1, None, tauri::State::new(&world));
+    commands::join("ada".into(), tauri::State::new(&lobby));
+    let log = replay::stop();
+    assert_eq!(lobby.into_inner().unwrap().players, ["ada"]);
+
+    let error = replay::replay(&log, World::default).unwrap_err();
+    match &error {
+        LogError::Unroutable {
+            index: 1, command, ..
+        } => assert_eq!(command, "join"),
+        other => panic!("expected an unroutable command, got {:?}", other),
+    }
+    assert!(error.to_string().starts_with("command log entry 1: `join`"));
+}
+
+#[test]
+fn replay_compares_each_outcome_with_the_recording() {
+    let _guard = exclusive();
+    let world = Mutex::new(World::default());
+
+    replay::start(Recorder::ring(16)).unwrap();
+    play(&world);
+    let log = replay::stop();
+
+    // The rewind failed when recorded; replayed after a bigger advance, it
+    // succeeds.
+    let mut diverged = log.clone();
+    diverged.entries[0].args = json!({ "ticks": 30, "label": null });
+    match replay::replay(&diverged, World::default) {
+        Err(LogError::Mismatch {
+            seq: 1,
+            recorded: Some(recorded),
+            replayed: None,
+        }) => assert_eq!(recorded.code, DiagnosticCode::CommandError),
+        other => panic!("expected a mismatch, got {:?}", other),
+    }
+
+    let mut reworded = log;
+    reworded.entries[1].error.as_mut().unwrap().message = "cannot rewind".into();
+    let error = replay::replay(&reworded, World::default).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "command log seq 1: recorded COMMAND_ERROR: cannot rewind, \
+         replayed COMMAND_ERROR: cannot rewind 10 ticks"
+    );
+}
@@ -0,0 +1,185 @@
+use serde_json::{json, Value};
+use webtau::{DiagnosticCode, Router};
+
+#[derive(Default)]
+struct World {
+    tick: u64,
+    log: Vec<String>,
+}
+
+#[derive(Default)]
+struct Profile {
+    best: u64,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn get_tick(world: &World) -> u64 {
+        world.tick
+    }
+
+    #[webtau::command(name = "advance")]
+    pub fn tick(world: &mut World, ticks: u64, label: Option<String>) -> u64 {
+        world.tick += ticks;
+        world.log.extend(label);
+        world.tick
+    }
+
+    #[webtau::command(rename_all = "camelCase")]
+    pub fn set_tick(world: &mut World, new_tick: u64) {
+        world.tick = new_tick;
+    }
+
+    #[webtau::command]
+    pub fn rewind(world: &mut World, ticks: u64) -> Result<u64, String> {
+        world.tick = world
+            .tick
+            .checked_sub(ticks)
+            .ok_or_else(|| format!("cannot rewind {} ticks", ticks))?;
+        Ok(world.tick)
+    }
+
+    #[webtau::command]
+    pub async fn reset(world: &mut World) {
+        world.tick = 0;
+    }
+
+    #[webtau::command]
+    pub fn version() -> &'static str {
+        "1.0"
+    }
+
+    #[webtau::command]
+    pub fn profile_only(profile: &Profile) {
+        let _ = profile;
+    }
+
+    #[webtau::command]
+    pub fn both(world: &World, profile: &Profile) -> u64 {
+        let _ = profile;
+        world.tick
+    }
+
+    #[webtau::command]
+    pub fn save_best(profile: &mut Profile, world: &World) -> u64 {
+        profile.best = profile.best.max(world.tick);
+        profile.best
+    }
+}
+
+fn call(router: &Router<World>, world: &mut World, command: &str, args: Value) -> Value {
+    router.dispatch(world, command, args).unwrap()
+}
+
+#[test]
+fn dispatches_by_command_name_with_json_args() {
+    let router = Router::<World>::new();
+    let mut world = World::default();
+
+    assert_eq!(
+        call(&router, &mut world, "advance", json!({ "ticks": 3 })),
+        json!(3)
+    );
+    assert_eq!(
+        call(
+            &router,
+            &mut world,
+            "advance",
+            json!({ "ticks": 2, "label": "warp" })
+        ),
+        json!(5)
+    );
+    assert_eq!(world.log, ["warp"]);
+    assert_eq!(call(&router, &mut world, "get_tick", Value::Null), json!(5));
+    assert_eq!(
+        call(&router, &mut world, "set_tick", json!({ "newTick": 9 })),
+        Value::Null
+    );
+    assert_eq!(
+        call(&router, &mut world, "rewind", json!({ "ticks": 4 })),
+        json!(5)
+    );
+    assert_eq!(call(&router, &mut world, "reset", json!({})), Value::Null);
+    assert_eq!(world.tick, 0);
+    assert_eq!(
+        call(&router, &mut world, "version", Value::Null),
+        json!("1.0")
+    );
+}
+
+#[test]
+fn only_commands_for_the_state_type_are_routed() {
+    let router = Router::<World>::new();
+    assert_eq!(
+        router.commands(),
+        ["advance", "get_tick", "reset", "rewind", "set_tick", "version"]
+    );
+    assert!(Router::<Profile>::new().contains("profile_only"));
+    assert!(Router::<Profile>::new().contains("version"));
+    assert!(!Router::<Profile>::new().contains("both"));
+}
+
+#[test]
+fn several_states_are_routed_as_a_tuple_in_parameter_order() {
+    let router = Router::<(Profile, World)>::new();
+    assert_eq!(router.commands(), ["save_best", "version"]);
+    assert_eq!(
+        Router::<(World, Profile)>::new().commands(),
+        ["both", "version"]
+    );
+
+    let mut states = (
+        Profile::default(),
+        World {
+            tick: 7,
+            log: Vec::new(),
+        },
+    );
+    assert_eq!(
+        router
+            .dispatch(&mut states, "save_best", Value::Null)
+            .unwrap(),
+        json!(7)
+    );
+    states.1.tick = 3;
+    assert_eq!(
+        router
+            .dispatch(&mut states, "save_best", json!({}))
+            .unwrap(),
+        json!(7)
+    );
+    assert_eq!(states.0.best, 7);
+}
+
+#[test]
+fn failures_are_diagnostics() {
+    let router = Router::<World>::new();
+    let mut world = World::default();
+
+    let err = router
+        .dispatch(&mut world, "rewind", json!({ "ticks": 1 }))
+        .unwrap_err();
+    assert_eq!(err.code, DiagnosticCode::CommandError);
+    assert_eq!(err.command, "rewind");
+    assert_eq!(err.message, "cannot rewind 1 ticks");
+    assert_eq!(err.details, Some(json!("cannot rewind 1 ticks")));
+
+    let err = router
+        .dispatch(&mut world, "advance", json!({ "ticks": "many" }))
+        .unwrap_err();
+    assert_eq!(err.code, DiagnosticCode::ArgsInvalid);
+
+    let err = router
+        .dispatch(&mut world, "set_tick", json!({ "new_tick": 1 }))
+        .unwrap_err();
+    assert_eq!(err.code, DiagnosticCode::ArgsInvalid);
+    assert!(err.message.contains("newTick"), "{}", err.message);
+
+    let err = router
+        .dispatch(&mut world, "tick", Value::Null)
+        .unwrap_err();
+    assert_eq!(err.code, DiagnosticCode::UnknownCommand);
+    assert_eq!(err.command, "tick");
+}
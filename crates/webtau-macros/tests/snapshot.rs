@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use webtau::snapshot::{self, Encoding};
+use webtau::testing::{json, Harness};
+use webtau::DiagnosticCode;
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct World {
+    tick: u64,
+    names: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    volume: f32,
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn spawn(world: &mut World, name: String) {
+        world.tick += 1;
+        world.names.push(name);
+    }
+
+    webtau::snapshot_commands!(World, version = 2);
+    webtau::snapshot_commands!(settings: Settings);
+}
+
+fn world() -> Harness<World> {
+    let mut app = Harness::new(World::default());
+    app.invoke("spawn", json!({ "name": "ada" }));
+    app.invoke("spawn", json!({ "name": "grace" }));
+    app
+}
+
+#[test]
+fn export_and_import_restore_the_world() {
+    for encoding in ["json", "binary"] {
+        let snapshot = world().invoke("export_state", json!({ "encoding": encoding }));
+        assert!(snapshot.is_string(), "{}", snapshot);
+        let mut fresh = Harness::new(World::default());
+        fresh.invoke("import_state", json!({ "snapshot": snapshot }));
+        assert_eq!(fresh.state(), world().state(), "{}", encoding);
+    }
+}
+
+#[test]
+fn export_defaults_to_json_with_the_schema_version() {
+    let snapshot: String = world().invoke_as("export_state", json!({}));
+    let bytes = snapshot::from_base64(&snapshot).unwrap();
+    assert_eq!(snapshot::encoding_of(&bytes), Some(Encoding::Json));
+    let raw = snapshot::decode(&bytes).unwrap();
+    assert_eq!(raw.header.version, 2);
+    assert_eq!(raw.state, json!({ "tick": 2, "names": ["ada", "grace"] }));
+}
+
+#[test]
+fn import_rejects_other_schema_versions() {
+    let old = snapshot::export_base64(&World::default(), 1, Encoding::Binary).unwrap();
+    let mut app = world();
+    let diagnostic = app.assert_diagnostic(
+        "import_state",
+        json!({ "snapshot": old }),
+        DiagnosticCode::CommandError,
+    );
+    assert_eq!(
+        diagnostic.details,
+        Some(json!({ "kind": "version_mismatch", "found": 1, "expected": 2 }))
+    );
+    assert_eq!(app.state().tick, 2);
+}
+
+#[test]
+fn named_form_snapshots_its_own_state() {
+    let mut app = Harness::new(Settings { volume: 0.5 });
+    let snapshot = app.invoke("export_settings", json!({ "encoding": "binary" }));
+    app.state_mut().volume = 1.0;
+    app.invoke("import_settings", json!({ "snapshot": snapshot }));
+    assert_eq!(app.state().volume, 0.5);
+}
+
+#[test]
+fn import_rejects_strings_that_are_not_base64() {
+    let mut app = world();
+    let diagnostic = app.assert_diagnostic(
+        "import_state",
+        json!({ "snapshot": "{}" }),
+        DiagnosticCode::CommandError,
+    );
+    assert_eq!(diagnostic.details.unwrap()["kind"], "malformed");
+}
@@ -1,13 +1,19 @@
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, punctuated::Punctuated, Expr, ExprLit, Lit, Meta, Token};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, punctuated::Punctuated, Expr, ExprLit, ItemFn, Lit, Meta, Path, Token,
+};
 
-/// Test-only replacement for `#[tauri::command]` that enforces
-/// `rename_all = "snake_case"` for generated wrappers.
+/// Test-only replacement for `#[tauri::command]` that enforces an explicit
+/// `rename_all = "snake_case"` or `"camelCase"` on generated wrappers. Like
+/// Tauri's, it records the name the handler matches (`rename`, or the fn
+/// name) next to the fn.
 #[proc_macro_attribute]
 pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
     let args = parse_macro_input!(attr with Punctuated::<Meta, Token![,]>::parse_terminated);
+    let function = parse_macro_input!(item as ItemFn);
 
-    let has_snake_case_rename = args.iter().any(|meta| {
+    let has_supported_rename = args.iter().any(|meta| {
         let Meta::NameValue(name_value) = meta else {
             return false;
         };
@@ -21,15 +27,72 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
             Expr::Lit(ExprLit {
                 lit: Lit::Str(value),
                 ..
-            }) if value.value() == "snake_case"
+            }) if matches!(value.value().as_str(), "snake_case" | "camelCase")
         )
     });
 
-    if !has_snake_case_rename {
-        return "compile_error!(\"test tauri::command requires `rename_all = \\\"snake_case\\\"`\");"
+    if !has_supported_rename {
+        return "compile_error!(\"test tauri::command requires `rename_all = \\\"snake_case\\\"` or `\\\"camelCase\\\"`\");"
             .parse()
             .expect("valid compile_error tokens");
     }
 
-    item
+    let rename = args.iter().find_map(|meta| match meta {
+        Meta::NameValue(name_value) if name_value.path.is_ident("rename") => {
+            match &name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(value),
+                    ..
+                }) => Some(value.value()),
+                _ => None,
+            }
+        }
+        _ => None,
+    });
+    let ident = &function.sig.ident;
+    let name = rename.unwrap_or_else(|| ident.to_string());
+    let name_const = format_ident!("__tauri_command_name_{}", ident);
+    let vis = &function.vis;
+    let cfgs = function.attrs.iter().filter(|a| a.path().is_ident("cfg"));
+    quote! {
+        #function
+
+        #(#cfgs)*
+        #[doc(hidden)]
+        #[allow(non_upper_case_globals, dead_code)]
+        #vis const #name_const: &str = #name;
+    }
+    .into()
+}
+
+/// Test-only replacement for `tauri::generate_handler!`: matches the command
+/// name the fn was registered under and resolves the invoke with the name of the function it routed to.
+#[proc_macro]
+pub fn generate_handler(input: TokenStream) -> TokenStream {
+    let paths = parse_macro_input!(input with Punctuated::<Path, Token![,]>::parse_terminated);
+    let arms = paths.iter().map(|path| {
+        let name = path
+            .segments
+            .last()
+            .expect("command path")
+            .ident
+            .to_string();
+        let mut name_const = path.clone();
+        let last = name_const.segments.last_mut().expect("command path");
+        last.ident = format_ident!("__tauri_command_name_{}", last.ident);
+        quote! {
+            __tauri_cmd__ if __tauri_cmd__ == #name_const => {
+                let _ = #path;
+                __tauri_invoke__.resolver.resolve(#name);
+                true
+            }
+        }
+    });
+    quote! {
+        move |__tauri_invoke__: ::tauri::ipc::Invoke| match __tauri_invoke__.message.command() {
+            #(#arms)*
+            _ => false,
+        }
+    }
+    .into()
 }
@@ -1,6 +1,9 @@
+use std::future::Future;
 use std::ops::Deref;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
-pub use tauri_macros::command;
+pub use tauri_macros::{command, generate_handler};
 
 /// Minimal test-only stand-in for `tauri::State`.
 pub struct State<'a, T>(&'a T);
@@ -9,6 +12,10 @@ impl<'a, T> State<'a, T> {
     pub fn new(inner: &'a T) -> Self {
         Self(inner)
     }
+
+    pub fn inner(&self) -> &'a T {
+        self.0
+    }
 }
 
 impl<'a, T> Deref for State<'a, T> {
@@ -18,3 +25,264 @@ impl<'a, T> Deref for State<'a, T> {
         self.0
     }
 }
+
+/// Minimal test-only stand-in for `tauri::AppHandle`.
+#[derive(Clone)]
+pub struct AppHandle;
+
+/// Minimal test-only stand-in for `tauri::Manager`.
+pub trait Manager {
+    fn state<T: Send + Sync + 'static>(&self) -> State<'_, T>;
+    fn try_state<T: Send + Sync + 'static>(&self) -> Option<State<'_, T>>;
+}
+
+type Managed =
+    std::collections::HashMap<std::any::TypeId, &'static (dyn std::any::Any + Send + Sync)>;
+
+static MANAGED: std::sync::Mutex<Option<Managed>> = std::sync::Mutex::new(None);
+
+/// Test-only stand-in for `Builder::manage`: every stub `AppHandle` shares
+/// one set of managed values, leaked for the rest of the test binary.
+pub fn manage<T: Send + Sync + 'static>(value: T) {
+    let value: &'static T = Box::leak(Box::new(value));
+    MANAGED
+        .lock()
+        .unwrap()
+        .get_or_insert_with(Default::default)
+        .insert(std::any::TypeId::of::<T>(), value);
+}
+
+impl Manager for AppHandle {
+    fn state<T: Send + Sync + 'static>(&self) -> State<'_, T> {
+        self.try_state()
+            .expect("test-only tauri stub: state not managed; call tauri::manage first")
+    }
+
+    fn try_state<T: Send + Sync + 'static>(&self) -> Option<State<'_, T>> {
+        let managed = MANAGED.lock().unwrap();
+        let value = *managed.as_ref()?.get(&std::any::TypeId::of::<T>())?;
+        value.downcast_ref::<T>().map(State::new)
+    }
+}
+
+/// Minimal test-only stand-in for `tauri::Event`.
+pub struct Event {
+    payload: String,
+}
+
+impl Event {
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+}
+
+type Handler = std::sync::Arc<dyn Fn(Event) + Send + Sync>;
+
+static LISTENERS: std::sync::Mutex<Vec<(String, Handler)>> = std::sync::Mutex::new(Vec::new());
+
+/// Minimal test-only stand-in for `tauri::Listener`.
+pub trait Listener {
+    fn listen_any<F: Fn(Event) + Send + 'static>(
+        &self,
+        event: impl Into<String>,
+        handler: F,
+    ) -> u32;
+}
+
+impl Listener for AppHandle {
+    fn listen_any<F: Fn(Event) + Send + 'static>(
+        &self,
+        event: impl Into<String>,
+        handler: F,
+    ) -> u32 {
+        let handler = std::sync::Mutex::new(handler);
+        let mut listeners = LISTENERS.lock().unwrap();
+        listeners.push((
+            event.into(),
+            std::sync::Arc::new(move |e| (handler.lock().unwrap())(e)),
+        ));
+        listeners.len() as u32
+    }
+}
+
+/// Test-only: delivers a frontend `emit(event, payload)` to every
+/// `listen_any` handler, with the payload as JSON.
+pub fn emit_from_frontend(event: &str, payload: &str) {
+    let handlers: Vec<Handler> = LISTENERS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(name, _)| name == event)
+        .map(|(_, handler)| handler.clone())
+        .collect();
+    for handler in handlers {
+        handler(Event {
+            payload: payload.to_string(),
+        });
+    }
+}
+
+/// Minimal test-only stand-in for `tauri::Emitter`. The stub `AppHandle`
+/// records every event for [`take_emitted`].
+pub trait Emitter {
+    fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) -> Result<(), Error>;
+}
+
+static EMITTED: std::sync::Mutex<Vec<(String, serde_json::Value)>> =
+    std::sync::Mutex::new(Vec::new());
+
+impl Emitter for AppHandle {
+    fn emit<S: serde::Serialize + Clone>(&self, event: &str, payload: S) -> Result<(), Error> {
+        let payload = serde_json::to_value(payload).map_err(|err| Error(err.to_string()))?;
+        EMITTED.lock().unwrap().push((event.to_string(), payload));
+        Ok(())
+    }
+}
+
+/// Events emitted through any stub `AppHandle` since the last call.
+pub fn take_emitted() -> Vec<(String, serde_json::Value)> {
+    std::mem::take(&mut EMITTED.lock().unwrap())
+}
+
+/// Minimal test-only stand-in for `tauri::Error`.
+#[derive(Debug)]
+pub struct Error(pub String);
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+pub mod async_runtime {
+    use super::*;
+
+    /// Test-only join handle; resolves by joining the closure's thread,
+    /// with its output or an error naming its panic like Tokio's
+    /// `JoinError`.
+    pub struct JoinHandle<T>(Option<std::thread::JoinHandle<T>>);
+
+    impl<T> Unpin for JoinHandle<T> {}
+
+    impl<T> Future for JoinHandle<T> {
+        type Output = Result<T, Error>;
+
+        fn poll(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let thread = self.0.take().expect("JoinHandle polled after completion");
+            Poll::Ready(thread.join().map_err(|panic| {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                Error(format!("task panicked with message {:?}", message))
+            }))
+        }
+    }
+
+    /// Test-only `spawn`: drives `task` to completion on a thread of its own.
+    pub fn spawn<F>(task: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        spawn_blocking(move || block_on(task))
+    }
+
+    pub fn spawn_blocking<F, R>(func: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        JoinHandle(Some(std::thread::spawn(func)))
+    }
+
+    /// Test-only executor: polls `task` on the current thread, parking it
+    /// while the future is pending.
+    pub fn block_on<F: Future>(task: F) -> F::Output {
+        struct Unpark(std::thread::Thread);
+
+        impl std::task::Wake for Unpark {
+            fn wake(self: std::sync::Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = std::task::Waker::from(std::sync::Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut task = std::pin::pin!(task);
+        loop {
+            match task.as_mut().poll(&mut cx) {
+                Poll::Ready(out) => return out,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+}
+
+pub mod ipc {
+    use std::sync::mpsc::{channel, Receiver, Sender};
+
+    /// Minimal test-only stand-in for `tauri::ipc::Invoke`.
+    pub struct Invoke {
+        pub message: InvokeMessage,
+        pub resolver: InvokeResolver,
+    }
+
+    impl Invoke {
+        /// An invoke of `command`, plus a receiver for what it resolves with.
+        pub fn new(command: &str) -> (Self, Receiver<String>) {
+            let (tx, rx) = channel();
+            let invoke = Self {
+                message: InvokeMessage {
+                    command: command.to_string(),
+                },
+                resolver: InvokeResolver(tx),
+            };
+            (invoke, rx)
+        }
+    }
+
+    pub struct InvokeMessage {
+        command: String,
+    }
+
+    impl InvokeMessage {
+        pub fn command(&self) -> &str {
+            &self.command
+        }
+    }
+
+    pub struct InvokeResolver(Sender<String>);
+
+    impl InvokeResolver {
+        pub fn resolve(self, value: &str) {
+            let _ = self.0.send(value.to_string());
+        }
+    }
+
+    /// Minimal test-only stand-in for `tauri::ipc::Channel`. Clones share
+    /// the items sent so far, for [`Channel::take`].
+    #[derive(Clone, Default)]
+    pub struct Channel<T> {
+        sent: std::sync::Arc<std::sync::Mutex<Vec<T>>>,
+    }
+
+    impl<T> Channel<T> {
+        pub fn new() -> Self {
+            Self {
+                sent: Default::default(),
+            }
+        }
+
+        pub fn send(&self, data: T) -> Result<(), crate::Error> {
+            self.sent.lock().unwrap().push(data);
+            Ok(())
+        }
+
+        /// Items sent since the last call.
+        pub fn take(&self) -> Vec<T> {
+            std::mem::take(&mut self.sent.lock().unwrap())
+        }
+    }
+}
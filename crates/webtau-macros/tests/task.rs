@@ -0,0 +1,162 @@
+use std::sync::Mutex;
+
+use webtau::task::TaskStatus;
+use webtau::testing::{json, Harness};
+use webtau::{DiagnosticCode, TaskContext};
+
+#[derive(Debug, Default)]
+struct Terrain {
+    rows: Vec<u32>,
+}
+
+#[derive(Debug)]
+struct Seed(u32);
+
+mod commands {
+    use super::*;
+
+    #[webtau::command(task)]
+    pub fn generate(terrain: &mut Terrain, ctx: TaskContext, rows: u32) -> usize {
+        for row in 0..rows {
+            if ctx.is_cancelled() {
+                break;
+            }
+            terrain.rows.push(row);
+            ctx.report_progress(100.0 * f64::from(row + 1) / f64::from(rows), "terrain");
+        }
+        terrain.rows.len()
+    }
+
+    #[webtau::command(task)]
+    pub async fn erode(terrain: &mut Terrain, ctx: TaskContext) -> Result<u32, String> {
+        let Some(top) = terrain.rows.pop() else {
+            return Err("nothing to erode".to_string());
+        };
+        ctx.yield_now().await;
+        Ok(top)
+    }
+
+    #[webtau::command(task, rename_all = "camelCase")]
+    pub fn survey(ctx: TaskContext, max_rows: u32) -> u32 {
+        ctx.report_progress(100.0, "");
+        max_rows
+    }
+
+    #[webtau::command(task)]
+    pub fn reseed(terrain: &mut Terrain, seed: &Seed, ctx: TaskContext) -> usize {
+        let _ = ctx;
+        terrain.rows = vec![seed.0; 2];
+        terrain.rows.len()
+    }
+}
+
+fn poll(app: &mut Harness<Terrain>, command: &str, id: u64) -> serde_json::Value {
+    app.invoke(&format!("poll_{}", command), json!({ "id": id }))
+}
+
+#[test]
+fn harness_runs_tasks_to_completion() {
+    let mut app = Harness::new(Terrain::default());
+    let id = app.invoke("start_generate", json!({ "rows": 3 }));
+    let id = id.as_u64().expect("start_ returns the task id");
+
+    assert_eq!(
+        poll(&mut app, "generate", id),
+        json!({ "state": "completed", "result": 3 })
+    );
+    assert_eq!(app.state().rows, [0, 1, 2]);
+
+    // A reported outcome is not reported twice.
+    let err = app.invoke_err("poll_generate", json!({ "id": id }));
+    assert_eq!(err.code, DiagnosticCode::ArgsInvalid);
+    assert_eq!(err.command, "poll_generate");
+
+    let id = app
+        .invoke("start_survey", json!({ "maxRows": 9 }))
+        .as_u64()
+        .unwrap();
+    assert_eq!(
+        poll(&mut app, "survey", id),
+        json!({ "state": "completed", "result": 9 })
+    );
+}
+
+#[test]
+fn tasks_borrowing_several_states_route_through_a_tuple() {
+    let mut app = Harness::new((Terrain::default(), Seed(9)));
+    let id = app.invoke("start_reseed", json!({}));
+    assert_eq!(
+        app.invoke("poll_reseed", json!({ "id": id })),
+        json!({ "state": "completed", "result": 2 })
+    );
+    assert_eq!(app.state().0.rows, [9, 9]);
+}
+
+#[test]
+fn failed_tasks_report_a_diagnostic() {
+    let mut app = Harness::new(Terrain { rows: vec![4] });
+    let first = app.invoke("start_erode", json!(null)).as_u64().unwrap();
+    let second = app.invoke("start_erode", json!(null)).as_u64().unwrap();
+
+    assert_eq!(
+        poll(&mut app, "erode", first),
+        json!({ "state": "completed", "result": 4 })
+    );
+    let status = poll(&mut app, "erode", second);
+    assert_eq!(status["state"], "failed");
+    assert_eq!(status["error"]["code"], "COMMAND_ERROR");
+    assert_eq!(status["error"]["command"], "start_erode");
+    assert_eq!(status["error"]["message"], "nothing to erode");
+
+    // Polling another task command's id is an error; cancelling is a no-op.
+    let id = app
+        .invoke("start_survey", json!({ "maxRows": 1 }))
+        .as_u64()
+        .unwrap();
+    let err = app.invoke_err("poll_erode", json!({ "id": id }));
+    assert_eq!(err.code, DiagnosticCode::ArgsInvalid);
+    assert_eq!(app.invoke("cancel_erode", json!({ "id": id })), json!(null));
+    assert_eq!(poll(&mut app, "survey", id)["state"], "completed");
+}
+
+#[test]
+fn task_commands_have_typescript_signatures() {
+    let rendered = webtau::ts::render_commands();
+    for signature in [
+        "start_generate: { args: { rows: number }; result: number }",
+        "cancel_generate: { args: { id: number }; result: void }",
+        "start_survey: { args: { maxRows: number }; result: number }",
+    ] {
+        assert!(rendered.contains(signature), "{}\n{}", signature, rendered);
+    }
+    assert!(
+        rendered.contains("poll_erode: { args: { id: number }; result: { state: \"running\""),
+        "{}",
+        rendered
+    );
+    assert!(
+        rendered.contains("{ state: \"completed\"; result: number }"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn tauri_wrappers_run_the_body_on_a_worker() {
+    tauri::manage(Mutex::new(Terrain::default()));
+
+    let id = commands::start_generate(2, tauri::AppHandle);
+    let status = loop {
+        let status = commands::poll_generate(id).unwrap();
+        if status.is_finished() {
+            break status;
+        }
+        std::thread::yield_now();
+    };
+    assert_eq!(status, TaskStatus::Completed { result: json!(2) });
+    commands::cancel_generate(id);
+    assert!(commands::poll_generate(id).is_err());
+
+    let terrain = tauri::Manager::state::<Mutex<Terrain>>(&tauri::AppHandle);
+    assert_eq!(terrain.lock().unwrap().rows, [0, 1]);
+}
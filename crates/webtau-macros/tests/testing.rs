@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use webtau::testing::{json, Harness, Scenario, Transcript, UPDATE_SNAPSHOTS_ENV};
+use webtau::DiagnosticCode;
+
+#[derive(Debug, Default)]
+struct Shop {
+    gold: u32,
+    items: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Receipt {
+    item: String,
+    gold_left: u32,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "kind")]
+enum ShopError {
+    TooPoor { missing: u32 },
+}
+
+impl std::fmt::Display for ShopError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooPoor { missing } => write!(f, "{} gold short", missing),
+        }
+    }
+}
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn deposit(shop: &mut Shop, gold: u32) -> u32 {
+        shop.gold += gold;
+        shop.gold
+    }
+
+    #[webtau::command(rename_all = "camelCase")]
+    pub fn buy(shop: &mut Shop, item_name: String, price: u32) -> Result<Receipt, ShopError> {
+        if price > shop.gold {
+            return Err(ShopError::TooPoor {
+                missing: price - shop.gold,
+            });
+        }
+        shop.gold -= price;
+        shop.items.push(item_name.clone());
+        Ok(Receipt {
+            item: item_name,
+            gold_left: shop.gold,
+        })
+    }
+
+    #[webtau::command]
+    pub async fn inventory(shop: &Shop) -> Vec<String> {
+        shop.items.clone()
+    }
+}
+
+#[test]
+fn invokes_commands_like_the_frontend() {
+    let mut app = Harness::<Shop>::default();
+    assert_eq!(app.commands(), ["buy", "deposit", "inventory"]);
+
+    assert_eq!(app.invoke("deposit", json!({ "gold": 10 })), json!(10));
+    let receipt: Receipt = app.invoke_as("buy", json!({ "itemName": "sword", "price": 7 }));
+    assert_eq!(
+        receipt,
+        Receipt {
+            item: "sword".into(),
+            gold_left: 3
+        }
+    );
+    assert_eq!(app.invoke("inventory", json!({})), json!(["sword"]));
+    assert_eq!(app.state().gold, 3);
+
+    app.state_mut().gold = 0;
+    assert_eq!(app.into_state().items, ["sword"]);
+}
+
+#[test]
+fn failures_surface_as_diagnostics() {
+    let mut app = Harness::new(Shop {
+        gold: 2,
+        items: Vec::new(),
+    });
+
+    let err = app.assert_diagnostic(
+        "buy",
+        json!({ "itemName": "shield", "price": 5 }),
+        DiagnosticCode::CommandError,
+    );
+    assert_eq!(err.message, "3 gold short");
+    assert_eq!(
+        err.details,
+        Some(json!({ "kind": "TooPoor", "missing": 3 }))
+    );
+
+    app.assert_diagnostic(
+        "buy",
+        json!({ "item_name": "shield", "price": 1 }),
+        DiagnosticCode::ArgsInvalid,
+    );
+    app.assert_diagnostic("sell", json!({}), DiagnosticCode::UnknownCommand);
+    assert!(app.state().items.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "`deposit` failed: ARGS_INVALID in `deposit`")]
+fn invoke_panics_with_the_diagnostic() {
+    Harness::<Shop>::default().invoke("deposit", json!({}));
+}
+
+#[test]
+#[should_panic(expected = "`deposit` succeeded with 1, expected an error")]
+fn invoke_err_panics_on_success() {
+    Harness::<Shop>::default().invoke_err("deposit", json!({ "gold": 1 }));
+}
+
+fn shopping_trip() -> Scenario {
+    Scenario::new()
+        .repeat(2, "deposit", json!({ "gold": 4 }))
+        .step("buy", json!({ "itemName": "sword", "price": 5 }))
+        .step("buy", json!({ "itemName": "shield", "price": 5 }))
+        .step("inventory", json!({}))
+}
+
+#[test]
+fn scenarios_record_every_invocation() {
+    let transcript = Harness::<Shop>::default().run(&shopping_trip());
+    let results: Vec<_> = transcript
+        .entries
+        .iter()
+        .map(|e| e.result.clone())
+        .collect();
+    assert_eq!(
+        results,
+        [
+            Some(json!(4)),
+            Some(json!(8)),
+            Some(json!({ "item": "sword", "goldLeft": 3 })),
+            None,
+            Some(json!(["sword"])),
+        ]
+    );
+    assert_eq!(
+        transcript.entries[3].error.as_ref().unwrap().message,
+        "2 gold short"
+    );
+}
+
+#[test]
+fn snapshots_are_recorded_then_compared() {
+    let dir = std::env::temp_dir().join(format!("webtau-snapshots-{}", std::process::id()));
+    let path = dir.join("shopping_trip.json");
+    let transcript = Harness::<Shop>::default().run(&shopping_trip());
+
+    let missing = std::panic::catch_unwind(|| transcript.assert_snapshot(&path)).unwrap_err();
+    let message = missing.downcast_ref::<String>().unwrap();
+    assert!(message.contains("does not exist"), "{}", message);
+    assert!(!path.exists());
+
+    std::env::set_var(UPDATE_SNAPSHOTS_ENV, "1");
+    transcript.assert_snapshot(&path);
+    std::env::remove_var(UPDATE_SNAPSHOTS_ENV);
+    let golden = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(golden, transcript.to_snapshot());
+    assert!(
+        golden.contains("\"message\": \"2 gold short\""),
+        "{}",
+        golden
+    );
+    assert!(
+        !golden.contains("\"hint\"") && !golden.contains("\"runtime\""),
+        "{}",
+        golden
+    );
+    transcript.assert_snapshot(&path);
+    let reread: Transcript = serde_json::from_str(&golden).unwrap();
+    assert_eq!(reread, transcript);
+
+    let richer = Harness::new(Shop {
+        gold: 100,
+        items: Vec::new(),
+    })
+    .run(&shopping_trip());
+    let mismatch = std::panic::catch_unwind(|| richer.assert_snapshot(&path)).unwrap_err();
+    let message = mismatch.downcast_ref::<String>().unwrap();
+    assert!(message.contains("does not match"), "{}", message);
+    assert!(message.contains("\n-     \"result\": 4\n"), "{}", message);
+    assert!(message.contains("\n+     \"result\": 104\n"), "{}", message);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
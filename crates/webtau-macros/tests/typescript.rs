@@ -0,0 +1,214 @@
+// Fixture types only exist to be described, so most are never constructed.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use webtau::ts::{Declarations, TypeScript};
+
+#[derive(Serialize, TypeScript)]
+#[serde(rename_all = "camelCase")]
+struct GameView {
+    player_score: u32,
+    paddles: Vec<Paddle>,
+    winner: Option<Side>,
+    #[serde(skip)]
+    frame_cache: Vec<u8>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    tags: HashMap<String, String>,
+}
+
+#[derive(Serialize, TypeScript)]
+struct Paddle {
+    y: f32,
+    #[serde(rename = "type")]
+    kind: Side,
+    #[ts(type = "`#${string}`")]
+    color: String,
+}
+
+#[derive(Serialize, TypeScript)]
+enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Serialize, TypeScript)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Contact {
+    Radar { bearing: f64, range_km: f64 },
+    Visual(Sighting),
+    Lost,
+}
+
+#[derive(Serialize, TypeScript)]
+struct Sighting {
+    confidence: f32,
+}
+
+#[derive(Serialize, TypeScript)]
+#[serde(tag = "t", content = "c")]
+enum Message {
+    Ping,
+    Chat(String),
+    Move(i32, i32),
+}
+
+#[derive(Serialize, TypeScript)]
+#[serde(untagged)]
+enum Either {
+    Count(u32),
+    Named { name: String },
+}
+
+#[derive(Serialize, TypeScript)]
+#[serde(transparent)]
+struct MissionId(String);
+
+#[derive(Serialize, TypeScript)]
+struct Position(f32, f32);
+
+#[derive(Serialize, TypeScript)]
+struct Tree {
+    children: Vec<Tree>,
+}
+
+#[derive(Serialize, TypeScript)]
+struct Tracked {
+    id: MissionId,
+    #[serde(flatten)]
+    position: Sighting,
+}
+
+fn declaration<T: TypeScript>(name: &str) -> String {
+    let mut decls = Declarations::new();
+    T::ts_declare(&mut decls);
+    decls.get(name).expect("declared").to_string()
+}
+
+#[test]
+fn structs_follow_serde_field_names() {
+    assert_eq!(
+        declaration::<GameView>("GameView"),
+        "export interface GameView {\n  playerScore: number;\n  paddles: Paddle[];\n  \
+         winner?: Side | null;\n  tags?: Record<string, string>;\n}"
+    );
+    assert_eq!(
+        declaration::<GameView>("Paddle"),
+        "export interface Paddle {\n  y: number;\n  type: Side;\n  color: `#${string}`;\n}"
+    );
+    assert_eq!(
+        declaration::<Position>("Position"),
+        "export type Position = [number, number];"
+    );
+    assert_eq!(
+        declaration::<Tracked>("Tracked"),
+        "export type Tracked = { id: string } & Sighting;"
+    );
+    assert_eq!(MissionId::ts_type(), "string");
+}
+
+#[test]
+fn enums_follow_serde_representation() {
+    assert_eq!(
+        declaration::<Side>("Side"),
+        "export type Side =\n  | \"Left\"\n  | \"Right\";"
+    );
+    assert_eq!(
+        declaration::<Contact>("Contact"),
+        "export type Contact =\n  \
+         | { kind: \"radar\"; bearing: number; range_km: number }\n  \
+         | { kind: \"visual\" } & Sighting\n  \
+         | { kind: \"lost\" };"
+    );
+    assert_eq!(
+        declaration::<Message>("Message"),
+        "export type Message =\n  \
+         | { t: \"Ping\" }\n  \
+         | { t: \"Chat\"; c: string }\n  \
+         | { t: \"Move\"; c: [number, number] };"
+    );
+    assert_eq!(
+        declaration::<Either>("Either"),
+        "export type Either =\n  | number\n  | { name: string };"
+    );
+}
+
+#[test]
+fn recursive_types_terminate() {
+    assert_eq!(
+        declaration::<Tree>("Tree"),
+        "export interface Tree {\n  children: Tree[];\n}"
+    );
+}
+
+// ── Commands ──
+
+struct World {
+    score: u32,
+}
+
+#[derive(Serialize)]
+struct Opaque;
+
+mod commands {
+    use super::*;
+
+    #[webtau::command]
+    pub fn get_view(world: &World) -> GameView {
+        GameView {
+            player_score: world.score,
+            paddles: Vec::new(),
+            winner: None,
+            frame_cache: Vec::new(),
+            tags: HashMap::new(),
+        }
+    }
+
+    #[webtau::command(rename_all = "camelCase")]
+    pub fn set_score(world: &mut World, new_score: u32, reason: Option<String>) {
+        let _ = reason;
+        world.score = new_score;
+    }
+
+    #[webtau::command]
+    pub fn scan(range_km: f64) -> Result<Vec<Contact>, String> {
+        let _ = range_km;
+        Ok(Vec::new())
+    }
+
+    #[webtau::command]
+    pub fn opaque() -> Opaque {
+        Opaque
+    }
+}
+
+#[test]
+fn commands_render_an_invoke_map() {
+    let rendered = webtau::ts::render_commands();
+    assert!(rendered.starts_with("// Generated by webtau::ts::export_commands()"));
+    assert!(rendered.contains("export interface GameView {\n"));
+    assert!(rendered.contains("export type Contact =\n"));
+    assert!(rendered.contains(
+        "export interface WebtauCommands {\n  \
+         get_view: { args: Record<string, never>; result: GameView };\n  \
+         opaque: { args: Record<string, never>; result: unknown };\n  \
+         scan: { args: { range_km: number }; result: Contact[] };\n  \
+         set_score: { args: { newScore: number; reason?: string | null }; result: void };\n}"
+    ));
+    assert!(rendered.ends_with(
+        "declare module \"webtau\" {\n  interface CommandMap extends WebtauCommands {}\n}\n"
+    ));
+}
+
+#[test]
+fn export_writes_the_declaration_file() {
+    let dir = std::env::temp_dir().join(format!("webtau-ts-{}", std::process::id()));
+    let path = dir.join("bindings/commands.d.ts");
+    webtau::ts::export_commands(&path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        webtau::ts::render_commands()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,400 @@
+//! Cancellation and timeouts for commands, on both targets.
+//!
+//! A command takes a [`Cancel`] argument to notice when the frontend gives
+//! up on it or its `#[command(timeout_ms = N)]` deadline passes:
+//!
+//! ```rust,ignore
+//! #[webtau::command(timeout_ms = 2000)]
+//! fn bake_lightmap(world: &mut World, cancel: webtau::Cancel) -> usize {
+//!     let mut baked = 0;
+//!     for chunk in world.dirty_chunks() {
+//!         if cancel.is_cancelled() {
+//!             break;
+//!         }
+//!         world.bake(chunk);
+//!         baked += 1;
+//!     }
+//!     baked
+//! }
+//! ```
+//!
+//! ```typescript
+//! const controller = new AbortController();
+//! const baked = invoke("bake_lightmap", { cancel: controller.signal });
+//! controller.abort();
+//! ```
+//!
+//! The argument is an `AbortSignal`, and may be left out when only the
+//! timeout matters. Stopping is cooperative: the body checks
+//! [`Cancel::is_cancelled`] between steps of work, so it always stops at a
+//! point where its state is consistent. If the command was cancelled or
+//! timed out by the time the body returns, its result is dropped and
+//! `invoke()` rejects with a `CANCELLED` or `TIMEOUT` diagnostic instead.
+//!
+//! Natively `invoke()` passes a number in place of the signal and aborting
+//! invokes the generated async `cancel_<name>({ id })` command, which runs
+//! while the body holds its state. On WASM the body runs on the page's only
+//! thread, so an abort is seen once an `async` body hands the loop back with
+//! [`Cancel::yield_now`]; the timeout works in every body.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::ts::TypeScript;
+use crate::{Diagnostic, DiagnosticCode};
+
+/// A command's view of its cancellation request and deadline. See the
+/// [module docs](self).
+///
+/// Cheap to clone, so it can be handed to helpers doing the actual work.
+#[derive(Clone, Default)]
+pub struct Cancel {
+    requested: Arc<AtomicBool>,
+    deadline: Option<Deadline>,
+    #[cfg(target_arch = "wasm32")]
+    signal: Option<web_sys::AbortSignal>,
+}
+
+impl Cancel {
+    /// A token that is only cancelled by [`Cancel::cancel`], e.g. to call a
+    /// command's helpers from a test.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation, as an aborted signal would.
+    pub fn cancel(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the body should stop: cancellation was requested or the
+    /// deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.is_requested() || self.is_timed_out()
+    }
+
+    /// Whether the `#[command(timeout_ms)]` deadline has passed.
+    pub fn is_timed_out(&self) -> bool {
+        self.deadline.as_ref().is_some_and(Deadline::passed)
+    }
+
+    /// Hands the event loop back to the browser until its next turn on
+    /// WASM, so an abort can arrive. Returns immediately natively, where
+    /// `cancel_*` runs on another thread.
+    pub async fn yield_now(&self) {
+        #[cfg(target_arch = "wasm32")]
+        crate::task::next_turn().await;
+    }
+
+    fn is_requested(&self) -> bool {
+        #[cfg(target_arch = "wasm32")]
+        if self.signal.as_ref().is_some_and(|signal| signal.aborted()) {
+            return true;
+        }
+        self.requested.load(Ordering::Relaxed)
+    }
+}
+
+impl std::fmt::Debug for Cancel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cancel")
+            .field("cancelled", &self.is_requested())
+            .field("timed_out", &self.is_timed_out())
+            .finish()
+    }
+}
+
+/// An optional `AbortSignal` in the TypeScript args object.
+impl TypeScript for Cancel {
+    fn ts_type() -> String {
+        "AbortSignal".to_string()
+    }
+
+    fn ts_optional() -> bool {
+        true
+    }
+}
+
+/// When a command times out. `std::time::Instant` panics on WASM, so the
+/// browser clock stands in there.
+#[derive(Clone, Copy)]
+struct Deadline {
+    #[cfg(not(target_arch = "wasm32"))]
+    at: std::time::Instant,
+    #[cfg(target_arch = "wasm32")]
+    at_ms: f64,
+}
+
+impl Deadline {
+    fn after(timeout_ms: u64) -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            at: std::time::Instant::now() + std::time::Duration::from_millis(timeout_ms),
+            #[cfg(target_arch = "wasm32")]
+            at_ms: js_sys::Date::now() + timeout_ms as f64,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn passed(&self) -> bool {
+        std::time::Instant::now() >= self.at
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn passed(&self) -> bool {
+        js_sys::Date::now() >= self.at_ms
+    }
+}
+
+/// A cancellable command's invocation, held by the generated wrapper until
+/// the body returns.
+#[doc(hidden)]
+pub struct CancelScope {
+    command: &'static str,
+    timeout_ms: Option<u64>,
+    token: Cancel,
+    #[cfg(not(target_arch = "wasm32"))]
+    id: Option<u32>,
+}
+
+impl CancelScope {
+    fn new(command: &'static str, timeout_ms: Option<u64>, token: Cancel) -> Self {
+        Self {
+            command,
+            timeout_ms,
+            token: Cancel {
+                deadline: timeout_ms.map(Deadline::after),
+                ..token
+            },
+            #[cfg(not(target_arch = "wasm32"))]
+            id: None,
+        }
+    }
+
+    /// The token the body receives.
+    pub fn token(&self) -> Cancel {
+        self.token.clone()
+    }
+
+    /// The body's outcome, or a `TIMEOUT` or `CANCELLED` diagnostic when it
+    /// returned after the deadline or a cancellation request.
+    pub fn finish<T>(self, outcome: Result<T, Box<Diagnostic>>) -> Result<T, Box<Diagnostic>> {
+        if self.token.is_timed_out() {
+            let timeout_ms = self.timeout_ms.unwrap_or_default();
+            return Err(Box::new(Diagnostic::new(
+                DiagnosticCode::Timeout,
+                self.command,
+                format!("`{}` timed out after {} ms", self.command, timeout_ms),
+            )));
+        }
+        if self.token.is_requested() {
+            return Err(Box::new(Diagnostic::new(
+                DiagnosticCode::Cancelled,
+                self.command,
+                format!("`{}` was cancelled", self.command),
+            )));
+        }
+        outcome
+    }
+}
+
+// ── Native ────────────────────────────────────────────────────────────
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::time::{Duration, Instant};
+
+    use super::{Cancel, CancelScope};
+
+    type Key = (&'static str, u32);
+
+    /// Cancellation flags of running invocations, by command and the id
+    /// `invoke()` passed. Only live scopes have an entry.
+    pub(super) static REQUESTS: Mutex<BTreeMap<Key, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+
+    /// `cancel_*` requests that arrived before their invocation started, and
+    /// when. The invocation picks one up if it starts within
+    /// [`EARLY_REQUEST_TTL`]; requests for calls that already ended or never
+    /// start expire instead of piling up.
+    pub(super) static EARLY: Mutex<BTreeMap<Key, Instant>> = Mutex::new(BTreeMap::new());
+
+    /// How long a `cancel_*` that beat its invocation is remembered.
+    pub(super) const EARLY_REQUEST_TTL: Duration = Duration::from_secs(10);
+
+    fn lock<T>(mutex: &'static Mutex<T>) -> MutexGuard<'static, T> {
+        mutex.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Removes the early request for `key`, reporting whether it was still
+    /// fresh, and drops every expired one.
+    fn take_early(key: Option<Key>) -> bool {
+        let mut early = lock(&EARLY);
+        let now = Instant::now();
+        let fresh = key
+            .and_then(|key| early.remove(&key))
+            .is_some_and(|at| now.duration_since(at) < EARLY_REQUEST_TTL);
+        early.retain(|_, at| now.duration_since(*at) < EARLY_REQUEST_TTL);
+        fresh
+    }
+
+    pub(crate) fn scope(
+        command: &'static str,
+        id: Option<u32>,
+        timeout_ms: Option<u64>,
+    ) -> CancelScope {
+        let requested = match id {
+            Some(id) => {
+                // Held across `take_early`, so a racing `request` sees
+                // either the flag or its early entry being taken.
+                let mut requests = lock(&REQUESTS);
+                let flag = Arc::clone(requests.entry((command, id)).or_default());
+                if take_early(Some((command, id))) {
+                    flag.store(true, Ordering::Relaxed);
+                }
+                flag
+            }
+            None => Arc::default(),
+        };
+        let token = Cancel {
+            requested,
+            deadline: None,
+        };
+        let mut scope = CancelScope::new(command, timeout_ms, token);
+        scope.id = id;
+        scope
+    }
+
+    pub(crate) fn request(command: &'static str, id: u32) {
+        let requests = lock(&REQUESTS);
+        if let Some(flag) = requests.get(&(command, id)) {
+            flag.store(true, Ordering::Relaxed);
+            return;
+        }
+        take_early(None);
+        lock(&EARLY).insert((command, id), Instant::now());
+    }
+
+    impl Drop for CancelScope {
+        fn drop(&mut self) {
+            if let Some(id) = self.id {
+                lock(&REQUESTS).remove(&(self.command, id));
+            }
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use native::{request, scope};
+
+// ── WASM ──────────────────────────────────────────────────────────────
+
+/// The `AbortSignal`, if any, a WASM command's args object carries for its
+/// `Cancel` parameter.
+#[cfg(target_arch = "wasm32")]
+#[doc(hidden)]
+#[derive(Default)]
+pub struct CancelSignal(Option<web_sys::AbortSignal>);
+
+#[cfg(target_arch = "wasm32")]
+impl<'de> serde::Deserialize<'de> for CancelSignal {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use wasm_bindgen::JsCast;
+
+        let value: wasm_bindgen::JsValue = serde_wasm_bindgen::preserve::deserialize(deserializer)?;
+        if value.is_undefined() || value.is_null() {
+            return Ok(Self(None));
+        }
+        value
+            .dyn_into::<web_sys::AbortSignal>()
+            .map(|signal| Self(Some(signal)))
+            .map_err(|_| serde::de::Error::custom("expected an AbortSignal"))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn scope(
+    command: &'static str,
+    signal: CancelSignal,
+    timeout_ms: Option<u64>,
+) -> CancelScope {
+    let token = Cancel {
+        signal: signal.0,
+        ..Cancel::default()
+    };
+    CancelScope::new(command, timeout_ms, token)
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_reach_the_running_invocation() {
+        let running = scope("unit_cancel", Some(7), None);
+        let token = running.token();
+        request("other_command", 7);
+        assert!(!token.is_cancelled());
+        request("unit_cancel", 7);
+        assert!(token.is_cancelled() && !token.is_timed_out());
+
+        let err = running.finish(Ok(1)).unwrap_err();
+        assert_eq!(err.code, DiagnosticCode::Cancelled);
+        assert_eq!(err.command, "unit_cancel");
+        assert!(requests_pending("unit_cancel", 7).is_none());
+    }
+
+    #[test]
+    fn early_requests_wait_for_their_invocation() {
+        request("unit_early", 3);
+        let running = scope("unit_early", Some(3), None);
+        assert!(running.token().is_cancelled());
+        assert_eq!(
+            scope("unit_early", Some(4), None).finish(Ok("kept")),
+            Ok("kept")
+        );
+        drop(running);
+        assert!(!scope("unit_early", Some(3), None).token().is_cancelled());
+    }
+
+    #[test]
+    fn late_requests_leave_no_running_flag_behind() {
+        drop(scope("unit_late", Some(9), None));
+        request("unit_late", 9);
+        assert!(requests_pending("unit_late", 9).is_none());
+
+        *native::EARLY
+            .lock()
+            .unwrap()
+            .get_mut(&("unit_late", 9))
+            .unwrap() -= native::EARLY_REQUEST_TTL;
+        assert!(!scope("unit_late", Some(9), None).token().is_cancelled());
+        assert!(!native::EARLY
+            .lock()
+            .unwrap()
+            .contains_key(&("unit_late", 9)));
+    }
+
+    #[test]
+    fn deadlines_time_out() {
+        let running = scope("unit_timeout", None, Some(0));
+        assert!(running.token().is_timed_out());
+        let err = running.finish(Ok(())).unwrap_err();
+        assert_eq!(err.code, DiagnosticCode::Timeout);
+        assert_eq!(err.message, "`unit_timeout` timed out after 0 ms");
+
+        let relaxed = scope("unit_timeout", None, Some(60_000));
+        assert!(!relaxed.token().is_cancelled());
+        assert!(relaxed.finish(Ok(())).is_ok());
+    }
+
+    fn requests_pending(command: &'static str, id: u32) -> Option<bool> {
+        native::REQUESTS
+            .lock()
+            .unwrap()
+            .get(&(command, id))
+            .map(|flag| flag.load(Ordering::Relaxed))
+    }
+}
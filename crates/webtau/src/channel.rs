@@ -0,0 +1,273 @@
+//! Streaming command output, on both targets.
+//!
+//! A command takes a [`Channel<T>`] argument to send items while it runs,
+//! instead of returning them all at the end:
+//!
+//! ```rust,ignore
+//! #[webtau::command]
+//! fn find_path(world: &World, from: Tile, to: Tile, steps: webtau::Channel<Tile>) -> usize {
+//!     let mut len = 0;
+//!     for tile in world.path(from, to) {
+//!         let _ = steps.send(tile);
+//!         len += 1;
+//!     }
+//!     len
+//! }
+//! ```
+//!
+//! ```typescript
+//! import { Channel, invoke } from "webtau";
+//!
+//! const steps = new Channel<Tile>((tile) => drawStep(tile));
+//! await invoke("find_path", { from, to, steps });
+//! ```
+//!
+//! Unlike an [`Emitter`](crate::Emitter), a channel is part of the args
+//! object and reaches only the caller that passed it. Natively the command
+//! receives a `tauri::ipc::Channel`, which `invoke()` creates from the
+//! `webtau` `Channel`; on WASM it receives the `Channel`'s callback
+//! function and calls it with each item. Under `webtau::Router` items are
+//! kept by an enclosing `Harness` and dropped otherwise.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ts::{Declarations, TypeScript};
+
+/// Why an item could not be sent.
+///
+/// Serialized with a `kind` tag, so a command returning it rejects with a
+/// `COMMAND_ERROR` diagnostic whose `details` say what went wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SendError {
+    /// The item failed to serialize.
+    Payload { message: String },
+    /// Tauri or the callback rejected the item.
+    Dispatch { message: String },
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Payload { message } => write!(f, "channel item: {}", message),
+            Self::Dispatch { message } => write!(f, "channel send: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+/// A `webtau` `Channel<T>` in the TypeScript args object.
+impl<T: TypeScript> TypeScript for Channel<T> {
+    fn ts_type() -> String {
+        format!("import(\"webtau\").Channel<{}>", T::ts_type())
+    }
+
+    fn ts_declare(decls: &mut Declarations) {
+        T::ts_declare(decls);
+    }
+}
+
+// ── Native ────────────────────────────────────────────────────────────
+
+#[cfg(not(target_arch = "wasm32"))]
+type Sink = dyn Fn(serde_json::Value) -> Result<(), String> + Send + Sync;
+
+/// Sends a command's items to the frontend as it runs. See the
+/// [module docs](self).
+///
+/// Cheap to clone; clones send to the same place.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Channel<T> {
+    sink: std::sync::Arc<Sink>,
+    item: PhantomData<fn(T)>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> Channel<T> {
+    /// A channel that hands every serialized item to `sink`, e.g. to stream
+    /// into a `tauri::ipc::Channel` outside a command:
+    ///
+    /// ```rust,ignore
+    /// let steps = webtau::Channel::<Tile>::from_fn(move |item| {
+    ///     channel.send(item).map_err(|e| e.to_string())
+    /// });
+    /// ```
+    pub fn from_fn(
+        sink: impl Fn(serde_json::Value) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            sink: std::sync::Arc::new(sink),
+            item: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T: Serialize> Channel<T> {
+    /// Sends `item` to the frontend callback.
+    pub fn send(&self, item: T) -> Result<(), SendError> {
+        let item = serde_json::to_value(item).map_err(|err| SendError::Payload {
+            message: err.to_string(),
+        })?;
+        (self.sink)(item).map_err(|message| SendError::Dispatch { message })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sink: self.sink.clone(),
+            item: PhantomData,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> fmt::Debug for Channel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+std::thread_local! {
+    /// Items sent through routed channels while a `Harness` dispatches.
+    static CAPTURED: std::cell::RefCell<Option<Vec<(String, serde_json::Value)>>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// The channel a routed command receives for its arg `key`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn route_channel<T>(key: &'static str) -> Channel<T> {
+    Channel::from_fn(move |item| {
+        CAPTURED.with(|captured| {
+            if let Some(items) = captured.borrow_mut().as_mut() {
+                items.push((key.to_string(), item));
+            }
+        });
+        Ok(())
+    })
+}
+
+/// Runs `f`, returning the items routed channels sent meanwhile, by arg key.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn capture<R>(f: impl FnOnce() -> R) -> (R, Vec<(String, serde_json::Value)>) {
+    let outer = CAPTURED.with(|captured| captured.replace(Some(Vec::new())));
+    let out = f();
+    let items = CAPTURED
+        .with(|captured| captured.replace(outer))
+        .unwrap_or_default();
+    (out, items)
+}
+
+// ── WASM ──────────────────────────────────────────────────────────────
+
+/// Sends a command's items to the frontend as it runs. See the
+/// [module docs](self).
+///
+/// Deserialized from the callback function in the args object.
+#[cfg(target_arch = "wasm32")]
+pub struct Channel<T> {
+    callback: js_sys::Function,
+    item: PhantomData<fn(T)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> Channel<T> {
+    /// A channel calling `callback` with every item.
+    pub fn from_function(callback: js_sys::Function) -> Self {
+        Self {
+            callback,
+            item: PhantomData,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<T: Serialize> Channel<T> {
+    /// Sends `item` to the frontend callback.
+    pub fn send(&self, item: T) -> Result<(), SendError> {
+        let item = serde_wasm_bindgen::to_value(&item).map_err(|err| SendError::Payload {
+            message: err.to_string(),
+        })?;
+        self.callback
+            .call1(&wasm_bindgen::JsValue::NULL, &item)
+            .map(drop)
+            .map_err(|err| SendError::Dispatch {
+                message: err.as_string().unwrap_or_else(|| format!("{:?}", err)),
+            })
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> Clone for Channel<T> {
+    fn clone(&self) -> Self {
+        Self::from_function(self.callback.clone())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<T> fmt::Debug for Channel<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Channel").finish_non_exhaustive()
+    }
+}
+
+/// Only `serde_wasm_bindgen` can deserialize a channel: it hands over the
+/// args object's function as is.
+#[cfg(target_arch = "wasm32")]
+impl<'de, T> Deserialize<'de> for Channel<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serde_wasm_bindgen::preserve::deserialize(deserializer).map(Self::from_function)
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sends_serialized_items_to_the_sink() {
+        let (sent, items) = capture(|| {
+            let steps = route_channel::<(u32, u32)>("steps");
+            steps.send((1, 2)).unwrap();
+            steps.clone().send((2, 2)).unwrap();
+            2
+        });
+        assert_eq!(sent, 2);
+        assert_eq!(
+            items,
+            [
+                ("steps".to_string(), json!([1, 2])),
+                ("steps".to_string(), json!([2, 2])),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_sink_and_payload_failures() {
+        let closed = Channel::<u32>::from_fn(|_| Err("channel closed".to_string()));
+        assert_eq!(
+            closed.send(1).unwrap_err().to_string(),
+            "channel send: channel closed"
+        );
+
+        let keyed = Channel::<std::collections::HashMap<(u8, u8), u8>>::from_fn(|_| Ok(()));
+        let err = keyed.send([((0, 0), 1)].into()).unwrap_err();
+        assert!(matches!(err, SendError::Payload { .. }), "{:?}", err);
+    }
+
+    #[test]
+    fn typescript_names_the_webtau_channel() {
+        assert_eq!(
+            Channel::<u32>::ts_type(),
+            "import(\"webtau\").Channel<number>"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Structured command errors matching the JS `DiagnosticEnvelope`.
+//!
+//! `#[webtau::command]` wrappers report failures as a [`Diagnostic`]: WASM
+//! wrappers throw it as a plain `{ code, runtime, command, message, hint }`
+//! object, and native wrappers return it as the Tauri command error. The
+//! `webtau` npm package turns both into a `WebtauError`, so frontends can
+//! branch on `code` the same way on every target.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Machine-readable failure category.
+///
+/// Serialized in `SCREAMING_SNAKE_CASE`, matching the JS `DiagnosticCode`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[non_exhaustive]
+pub enum DiagnosticCode {
+    /// No command is registered under the invoked name.
+    UnknownCommand,
+    /// A command borrowed state that was never initialized.
+    StateUninitialized,
+    /// A command borrowed state that another in-flight command still holds.
+    StateBusy,
+    /// A native event listener's state has no way to be locked, because no
+    /// `#[webtau::command]` borrows its type.
+    StateUnreachable,
+    /// A command borrowed a state type declared under several `wasm_state!`
+    /// names without saying which one.
+    StateAmbiguous,
+    /// The `invoke()` args did not match the command's parameters.
+    ArgsInvalid,
+    /// The command's return value could not be serialized.
+    SerializeFailed,
+    /// The command returned an `Err`.
+    CommandError,
+    /// The command ran past its `#[command(timeout_ms)]` deadline.
+    Timeout,
+    /// The frontend cancelled the command while it ran.
+    Cancelled,
+}
+
+impl DiagnosticCode {
+    /// The wire name, e.g. `"STATE_UNINITIALIZED"`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UnknownCommand => "UNKNOWN_COMMAND",
+            Self::StateUninitialized => "STATE_UNINITIALIZED",
+            Self::StateBusy => "STATE_BUSY",
+            Self::StateUnreachable => "STATE_UNREACHABLE",
+            Self::StateAmbiguous => "STATE_AMBIGUOUS",
+            Self::ArgsInvalid => "ARGS_INVALID",
+            Self::SerializeFailed => "SERIALIZE_FAILED",
+            Self::CommandError => "COMMAND_ERROR",
+            Self::Timeout => "TIMEOUT",
+            Self::Cancelled => "CANCELLED",
+        }
+    }
+
+    fn default_hint(self) -> &'static str {
+        match self {
+            Self::UnknownCommand => {
+                "Check the command name, and that the crate defining it is linked \
+                 into the binary."
+            }
+            Self::StateUninitialized => {
+                "Initialize the state before invoking commands: call the wasm_state! \
+                 setter (e.g. set_state()) on the web, or .manage() it on desktop."
+            }
+            Self::StateBusy => {
+                "Another command is still using this state, usually an async command \
+                 awaiting. Await it before invoking again."
+            }
+            Self::StateUnreachable => {
+                "Native listeners lock their state through the #[webtau::command]s that \
+                 borrow it. Add a command taking the state type as a parameter."
+            }
+            Self::StateAmbiguous => {
+                "Several wasm_state! names hold this type. Name the one the command \
+                 borrows with #[webtau::command(state = \"name\")]."
+            }
+            Self::ArgsInvalid => {
+                "Check the invoke() args object: keys must match the command's \
+                 parameter names in its rename_all casing."
+            }
+            Self::SerializeFailed => "Check the Serialize impl of the command's return type.",
+            Self::CommandError => "The command returned an error; see the message for details.",
+            Self::Timeout => {
+                "The command stopped at its timeout and its result was dropped. Raise \
+                 timeout_ms or split the work into smaller commands."
+            }
+            Self::Cancelled => "The command was cancelled and its result was dropped.",
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A structured command failure, shaped like the JS `DiagnosticEnvelope`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// Machine-readable failure category.
+    pub code: DiagnosticCode,
+    /// Runtime that produced the error: `"wasm"` or `"tauri"`.
+    pub runtime: String,
+    /// Name of the command that failed, as passed to `invoke()`.
+    pub command: String,
+    /// Human-readable description of the failure.
+    pub message: String,
+    /// Remediation hint for the developer.
+    pub hint: String,
+    /// Structured payload, e.g. a command's serialized error value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic for `command` on the current runtime, with the
+    /// default hint for `code`.
+    pub fn new(
+        code: DiagnosticCode,
+        command: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            code,
+            runtime: current_runtime().to_string(),
+            command: command.into(),
+            message: message.into(),
+            hint: code.default_hint().to_string(),
+            details: None,
+        }
+    }
+
+    /// Replace the default hint.
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = hint.into();
+        self
+    }
+
+    /// Attach a structured payload.
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in `{}`: {}", self.code, self.command, self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+#[cfg(target_arch = "wasm32")]
+impl From<Diagnostic> for wasm_bindgen::JsValue {
+    /// Convert to a plain JS object with the envelope fields.
+    fn from(diagnostic: Diagnostic) -> Self {
+        let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+        diagnostic
+            .serialize(&serializer)
+            .unwrap_or_else(|_| wasm_bindgen::JsValue::from_str(&diagnostic.to_string()))
+    }
+}
+
+fn current_runtime() -> &'static str {
+    if cfg!(target_arch = "wasm32") {
+        "wasm"
+    } else {
+        "tauri"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_as_js_envelope() {
+        let diagnostic = Diagnostic::new(
+            DiagnosticCode::ArgsInvalid,
+            "tick_world",
+            "missing field `dt`",
+        );
+        let json = serde_json::to_value(&diagnostic).unwrap();
+        assert_eq!(json["code"], "ARGS_INVALID");
+        assert_eq!(json["runtime"], "tauri");
+        assert_eq!(json["command"], "tick_world");
+        assert_eq!(json["message"], "missing field `dt`");
+        assert!(json["hint"].as_str().unwrap().contains("rename_all"));
+        assert!(json.get("details").is_none());
+    }
+
+    #[test]
+    fn code_wire_names_match_as_str() {
+        for code in [
+            DiagnosticCode::UnknownCommand,
+            DiagnosticCode::StateUninitialized,
+            DiagnosticCode::StateBusy,
+            DiagnosticCode::StateUnreachable,
+            DiagnosticCode::StateAmbiguous,
+            DiagnosticCode::ArgsInvalid,
+            DiagnosticCode::SerializeFailed,
+            DiagnosticCode::CommandError,
+            DiagnosticCode::Timeout,
+            DiagnosticCode::Cancelled,
+        ] {
+            assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+        }
+    }
+
+    #[test]
+    fn with_hint_overrides_default() {
+        let diagnostic = Diagnostic::new(DiagnosticCode::CommandError, "save", "disk full")
+            .with_hint("Free some space.");
+        assert_eq!(diagnostic.hint, "Free some space.");
+        assert_eq!(diagnostic.to_string(), "COMMAND_ERROR in `save`: disk full");
+    }
+}
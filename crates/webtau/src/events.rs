@@ -0,0 +1,637 @@
+//! Events between Rust and the frontend, on both targets.
+//!
+//! A command takes an [`Emitter`] parameter to notify the frontend mid-call,
+//! instead of smuggling notifications through its return value:
+//!
+//! ```rust,ignore
+//! #[webtau::command]
+//! fn tick(world: &mut World, events: webtau::Emitter, dt: f64) -> WorldView {
+//!     for enemy in world.step(dt) {
+//!         let _ = events.emit("enemy-destroyed", &enemy);
+//!     }
+//!     world.view()
+//! }
+//! ```
+//!
+//! The emitter is not part of the `invoke()` args. Natively it is built from
+//! the command's `tauri::AppHandle` and emits through Tauri's event bus; on
+//! WASM it dispatches a `CustomEvent` on `window` with the payload as
+//! `detail`, which is what `webtau/event`'s `listen()` receives in the
+//! browser. Either way the frontend sees the same serialized payload.
+//!
+//! The other way round, [`listen`] runs a handler with mutable access to a
+//! state whenever the frontend emits an event, without a round-trip command:
+//!
+//! ```rust,ignore
+//! webtau::events::listen("settings-changed", |world: &mut World, patch: SettingsPatch| {
+//!     world.apply_settings(patch);
+//! });
+//! ```
+//!
+//! On WASM the handler listens on `window`, where `webtau/event`'s `emit()`
+//! dispatches, and borrows the type's `wasm_state!` cell. Natively it needs
+//! the Tauri bridge, attached once in `setup` with
+//! [`webtau::attach_listeners!`](crate::attach_listeners), and locks the
+//! type's managed `Mutex`, found through the `#[webtau::command]`s that
+//! borrow it, so a state no command borrows cannot be listened on natively.
+//! Handlers must not listen to events that commands holding the
+//! same state emit, since Tauri delivers those while the state is locked.
+//!
+//! An event that cannot reach its handler is reported to the
+//! [`on_delivery_error`] callback as a [`Diagnostic`](crate::Diagnostic).
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::Diagnostic;
+
+/// Why an event could not be emitted.
+///
+/// Serialized with a `kind` tag, so a command returning it rejects with a
+/// `COMMAND_ERROR` diagnostic whose `details` say what went wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EmitError {
+    /// The payload failed to serialize.
+    Payload { message: String },
+    /// Tauri or the browser rejected the event.
+    Dispatch { message: String },
+}
+
+impl fmt::Display for EmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Payload { message } => write!(f, "event payload: {}", message),
+            Self::Dispatch { message } => write!(f, "event dispatch: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for EmitError {}
+
+// ── Native ────────────────────────────────────────────────────────────
+
+#[cfg(not(target_arch = "wasm32"))]
+type Sink = dyn Fn(&str, serde_json::Value) -> Result<(), String> + Send + Sync;
+
+/// Emits events to the frontend. See the [module docs](self).
+///
+/// Cheap to clone; clones emit to the same place.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Clone)]
+pub struct Emitter {
+    sink: std::sync::Arc<Sink>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Emitter {
+    /// An emitter that hands every event to `sink`, e.g. to emit from an
+    /// `AppHandle` outside a command:
+    ///
+    /// ```rust,ignore
+    /// let handle = app.handle().clone();
+    /// let events = webtau::Emitter::from_fn(move |event, payload| {
+    ///     tauri::Emitter::emit(&handle, event, payload).map_err(|e| e.to_string())
+    /// });
+    /// ```
+    pub fn from_fn(
+        sink: impl Fn(&str, serde_json::Value) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            sink: std::sync::Arc::new(sink),
+        }
+    }
+
+    /// Emits `event` with `payload` to every frontend listener.
+    pub fn emit<T: Serialize>(&self, event: &str, payload: T) -> Result<(), EmitError> {
+        let payload = serde_json::to_value(payload).map_err(|err| EmitError::Payload {
+            message: err.to_string(),
+        })?;
+        (self.sink)(event, payload).map_err(|message| EmitError::Dispatch { message })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl fmt::Debug for Emitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Emitter").finish_non_exhaustive()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+type Captured = Vec<(String, serde_json::Value)>;
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    static CAPTURED: std::cell::RefCell<Option<Captured>> = const { std::cell::RefCell::new(None) };
+}
+
+/// The emitter `webtau::Router` passes to commands. Events are collected by
+/// an enclosing [`capture`] (as `webtau::testing::Harness` does) and
+/// dropped otherwise, since there is no frontend to receive them.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn route_emitter() -> Emitter {
+    Emitter::from_fn(|event, payload| {
+        CAPTURED.with(|captured| {
+            if let Some(captured) = captured.borrow_mut().as_mut() {
+                captured.push((event.to_string(), payload));
+            }
+        });
+        Ok(())
+    })
+}
+
+/// Runs `f`, returning the events routed commands emitted on this thread
+/// meanwhile.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn capture<R>(f: impl FnOnce() -> R) -> (R, Captured) {
+    let outer = CAPTURED.with(|captured| captured.replace(Some(Vec::new())));
+    let result = f();
+    let events = CAPTURED
+        .with(|captured| captured.replace(outer))
+        .unwrap_or_default();
+    (result, events)
+}
+
+// ── WASM ──────────────────────────────────────────────────────────────
+
+/// Emits events to the frontend. See the [module docs](self).
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone, Default)]
+pub struct Emitter {
+    _private: (),
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Emitter {
+    /// An emitter dispatching on `window`, usable outside commands too.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Emits `event` with `payload` to every frontend listener.
+    pub fn emit<T: Serialize>(&self, event: &str, payload: T) -> Result<(), EmitError> {
+        let dispatch = |err: wasm_bindgen::JsValue| EmitError::Dispatch {
+            message: format!("{:?}", err),
+        };
+        let detail = serde_wasm_bindgen::to_value(&payload).map_err(|err| EmitError::Payload {
+            message: err.to_string(),
+        })?;
+        let window = web_sys::window().ok_or_else(|| EmitError::Dispatch {
+            message: "no `window` to dispatch on".to_string(),
+        })?;
+        let init = web_sys::CustomEventInit::new();
+        init.set_detail(&detail);
+        let event =
+            web_sys::CustomEvent::new_with_event_init_dict(event, &init).map_err(dispatch)?;
+        window.dispatch_event(&event).map_err(dispatch)?;
+        Ok(())
+    }
+}
+
+// ── Listening ─────────────────────────────────────────────────────────
+
+/// Identifies a handler registered with [`listen`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(u64);
+
+fn next_listener_id() -> ListenerId {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    ListenerId(NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Runs `handler` with the state `S` and the deserialized payload every
+/// time the frontend emits `event`, until [`unlisten`]. See the
+/// [module docs](self).
+///
+/// Events whose payload does not deserialize as `T`, or that arrive while
+/// `S` is missing, are not delivered; the failure goes to the
+/// [`on_delivery_error`] callback, if any.
+///
+/// The managed `Mutex<S>` is locked through code that `#[webtau::command]`
+/// generates for its state parameters, since this crate does not depend on
+/// Tauri. At least one command must borrow `S`; otherwise every event fails
+/// with a `STATE_UNREACHABLE` diagnostic.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn listen<S, T, F>(event: &str, mut handler: F) -> ListenerId
+where
+    S: 'static,
+    T: DeserializeOwned,
+    F: FnMut(&mut S, T) + Send + 'static,
+{
+    let id = next_listener_id();
+    let run = move |state: &mut dyn std::any::Any, payload: serde_json::Value| {
+        let payload = serde_json::from_value(payload).map_err(|err| err.to_string())?;
+        let state = state
+            .downcast_mut::<S>()
+            .expect("webtau: listener state has the wrong type");
+        handler(state, payload);
+        Ok(())
+    };
+    let subscribe = {
+        let mut registry = native::REGISTRY.lock().unwrap_or_else(|p| p.into_inner());
+        registry.listeners.push(native::Listener {
+            id,
+            event: event.to_string(),
+            state: std::any::TypeId::of::<S>(),
+            state_name: std::any::type_name::<S>(),
+            run: std::sync::Arc::new(std::sync::Mutex::new(run)),
+        });
+        registry.subscription(event)
+    };
+    if let Some(subscribe) = subscribe {
+        subscribe(event);
+    }
+    id
+}
+
+/// Runs `handler` with the state `S` and the deserialized payload every
+/// time the frontend emits `event`, until [`unlisten`]. See the
+/// [module docs](self).
+///
+/// Events whose payload does not deserialize as `T`, or that arrive while
+/// `S` is uninitialized or borrowed, are not delivered; the failure goes to
+/// the [`on_delivery_error`] callback. Without one the listener throws the
+/// `Diagnostic`, which the browser reports as an uncaught error. `S` must be
+/// declared under one `wasm_state!` name; with several, events fail with
+/// `STATE_AMBIGUOUS`.
+#[cfg(target_arch = "wasm32")]
+pub fn listen<S, T, F>(event: &str, mut handler: F) -> ListenerId
+where
+    S: 'static,
+    T: DeserializeOwned,
+    F: FnMut(&mut S, T) + 'static,
+{
+    use wasm_bindgen::{JsCast, JsValue};
+
+    use crate::__private::{diagnostic, state_cell_of};
+    use crate::{Diagnostic, DiagnosticCode};
+
+    let id = next_listener_id();
+    let name = event.to_string();
+    let mut deliver = move |event: web_sys::Event| -> Result<(), Box<Diagnostic>> {
+        let fail = |code, message: String| Box::new(diagnostic(code, &name, message));
+        let detail = event
+            .dyn_ref::<web_sys::CustomEvent>()
+            .map_or(JsValue::UNDEFINED, |event| event.detail());
+        let payload: T = serde_wasm_bindgen::from_value(detail)
+            .map_err(|err| fail(DiagnosticCode::ArgsInvalid, err.to_string()))?;
+        let cell = state_cell_of::<S>()
+            .map_err(|message| fail(DiagnosticCode::StateAmbiguous, message))?;
+        let mut borrow = cell.try_borrow_mut().map_err(|_| {
+            fail(
+                DiagnosticCode::StateBusy,
+                format!(
+                    "state `{}` is busy — another command is still using it",
+                    std::any::type_name::<S>()
+                ),
+            )
+        })?;
+        let state = borrow.as_mut().ok_or_else(|| {
+            fail(
+                DiagnosticCode::StateUninitialized,
+                format!(
+                    "state `{}` not initialized — call its wasm_state! setter (e.g. set_state()) first",
+                    std::any::type_name::<S>()
+                ),
+            )
+        })?;
+        handler(state, payload);
+        Ok(())
+    };
+    let closure =
+        wasm_bindgen::closure::Closure::<dyn FnMut(web_sys::Event) -> Result<(), JsValue>>::new(
+            move |event: web_sys::Event| match deliver(event) {
+                Ok(()) => Ok(()),
+                Err(diagnostic) => match web::ON_ERROR.with(|on_error| on_error.borrow().clone()) {
+                    Some(on_error) => {
+                        on_error(&diagnostic);
+                        Ok(())
+                    }
+                    None => Err(JsValue::from(*diagnostic)),
+                },
+            },
+        );
+    if let Some(window) = web_sys::window() {
+        let _ = window.add_event_listener_with_callback(event, closure.as_ref().unchecked_ref());
+    }
+    web::LISTENERS.with(|listeners| {
+        listeners
+            .borrow_mut()
+            .insert(id, (event.to_string(), closure));
+    });
+    id
+}
+
+/// Runs `handler` with the `Diagnostic` of every frontend event that could
+/// not be delivered to a [`listen`] handler, e.g. because its payload did
+/// not deserialize. Replaces the previous callback.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn on_delivery_error(handler: impl Fn(&Diagnostic) + Send + Sync + 'static) {
+    *native::ON_ERROR.lock().unwrap_or_else(|p| p.into_inner()) =
+        Some(std::sync::Arc::new(handler));
+}
+
+/// Runs `handler` with the `Diagnostic` of every frontend event that could
+/// not be delivered to a [`listen`] handler, e.g. because its state was
+/// busy, instead of throwing it. Replaces the previous callback.
+#[cfg(target_arch = "wasm32")]
+pub fn on_delivery_error(handler: impl Fn(&crate::Diagnostic) + 'static) {
+    web::ON_ERROR.with(|on_error| *on_error.borrow_mut() = Some(std::rc::Rc::new(handler)));
+}
+
+/// Removes a handler registered with [`listen`]. Unknown ids are ignored.
+pub fn unlisten(id: ListenerId) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut registry = native::REGISTRY.lock().unwrap_or_else(|p| p.into_inner());
+        registry.listeners.retain(|listener| listener.id != id);
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        use wasm_bindgen::JsCast;
+
+        let removed = web::LISTENERS.with(|listeners| listeners.borrow_mut().remove(&id));
+        if let (Some((event, closure)), Some(window)) = (removed, web_sys::window()) {
+            let _ = window
+                .remove_event_listener_with_callback(&event, closure.as_ref().unchecked_ref());
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsValue;
+
+    use super::ListenerId;
+
+    type Handler = Closure<dyn FnMut(web_sys::Event) -> Result<(), JsValue>>;
+
+    type OnError = std::rc::Rc<dyn Fn(&crate::Diagnostic)>;
+
+    thread_local! {
+        pub(super) static LISTENERS: RefCell<HashMap<ListenerId, (String, Handler)>> =
+            RefCell::new(HashMap::new());
+        pub(super) static ON_ERROR: RefCell<Option<OnError>> = const { RefCell::new(None) };
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod native {
+    use std::any::{Any, TypeId};
+    use std::collections::{BTreeSet, HashSet};
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::Value;
+
+    use super::ListenerId;
+    use crate::__private::StateRun;
+    use crate::{Diagnostic, DiagnosticCode};
+
+    type Run = dyn FnMut(&mut dyn Any, Value) -> Result<(), String> + Send;
+
+    /// Whether a listener's state could be handed to it.
+    pub(crate) enum Reach {
+        /// The state was locked and the handler ran.
+        Ran,
+        /// The app does not manage the state.
+        Unmanaged,
+        /// No `#[webtau::command]` borrows the state type, so nothing
+        /// registered a way to lock it.
+        NoAccessor,
+    }
+
+    /// Subscribes the Tauri bridge to an event name.
+    pub(crate) type Subscribe = Arc<dyn Fn(&str) + Send + Sync>;
+
+    pub(super) struct Listener {
+        pub(super) id: ListenerId,
+        pub(super) event: String,
+        pub(super) state: TypeId,
+        pub(super) state_name: &'static str,
+        pub(super) run: Arc<Mutex<Run>>,
+    }
+
+    pub(crate) struct Registry {
+        pub(super) listeners: Vec<Listener>,
+        bridge: Option<Subscribe>,
+        subscribed: BTreeSet<String>,
+    }
+
+    type OnError = Arc<dyn Fn(&Diagnostic) + Send + Sync>;
+
+    /// The [`on_delivery_error`](super::on_delivery_error) callback.
+    pub(super) static ON_ERROR: Mutex<Option<OnError>> = Mutex::new(None);
+
+    pub(crate) static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+        listeners: Vec::new(),
+        bridge: None,
+        subscribed: BTreeSet::new(),
+    });
+
+    impl Registry {
+        /// The bridge to subscribe `event` through, if it is attached and
+        /// has not subscribed yet. Called with the lock held; the caller
+        /// subscribes after releasing it.
+        pub(super) fn subscription(&mut self, event: &str) -> Option<Subscribe> {
+            let bridge = self.bridge.clone()?;
+            self.subscribed.insert(event.to_string()).then_some(bridge)
+        }
+    }
+
+    /// Installs the Tauri bridge and subscribes it to every event listened
+    /// for so far.
+    pub(crate) fn attach(bridge: Subscribe) {
+        let events: Vec<String> = {
+            let mut registry = REGISTRY.lock().unwrap_or_else(|p| p.into_inner());
+            registry.bridge = Some(bridge.clone());
+            let events: HashSet<String> =
+                registry.listeners.iter().map(|l| l.event.clone()).collect();
+            events
+                .into_iter()
+                .filter(|event| registry.subscribed.insert(event.clone()))
+                .collect()
+        };
+        for event in events {
+            bridge(&event);
+        }
+    }
+
+    /// Runs the handlers for `event`, each with its state from
+    /// `with_state`. With `only`, other state types are skipped. Returns the
+    /// first failure.
+    pub(crate) fn deliver(
+        event: &str,
+        payload: &Value,
+        only: Option<TypeId>,
+        with_state: &mut dyn FnMut(TypeId, &mut StateRun<'_>) -> Reach,
+    ) -> Result<(), Box<Diagnostic>> {
+        // Collect first, so handlers can call `listen` and `unlisten`.
+        let targets: Vec<_> = {
+            let registry = REGISTRY.lock().unwrap_or_else(|p| p.into_inner());
+            registry
+                .listeners
+                .iter()
+                .filter(|l| l.event == event && only.map_or(true, |ty| ty == l.state))
+                .map(|l| (l.state, l.state_name, l.run.clone()))
+                .collect()
+        };
+        let mut first = Ok(());
+        for (state, state_name, run) in targets {
+            let mut outcome = None;
+            let reach = with_state(state, &mut |state| {
+                let mut run = run.lock().unwrap_or_else(|p| p.into_inner());
+                outcome = Some((*run)(state, payload.clone()));
+            });
+            let result = match (outcome, reach) {
+                (Some(Ok(())), _) => Ok(()),
+                (Some(Err(message)), _) => {
+                    Err(Diagnostic::new(DiagnosticCode::ArgsInvalid, event, message))
+                }
+                (None, Reach::Unmanaged) => Err(Diagnostic::new(
+                    DiagnosticCode::StateUninitialized,
+                    event,
+                    format!("state `{}` is not managed", state_name),
+                )),
+                (None, Reach::NoAccessor) => Err(Diagnostic::new(
+                    DiagnosticCode::StateUnreachable,
+                    event,
+                    format!(
+                        "no #[webtau::command] borrows state `{}`, so the listener cannot lock it",
+                        state_name
+                    ),
+                )),
+                (None, Reach::Ran) => Ok(()),
+            };
+            if first.is_ok() {
+                first = result.map_err(Box::new);
+            }
+        }
+        first
+    }
+}
+
+/// Delivers an event from the Tauri bridge, locking each handler's state
+/// through the accessors `#[webtau::command]` registers for the state types
+/// its commands borrow.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn deliver_tauri(event: &str, payload: &str, app: &dyn std::any::Any) {
+    let payload = match payload {
+        "" => Ok(serde_json::Value::Null),
+        payload => serde_json::from_str(payload),
+    };
+    let result = match payload {
+        Ok(payload) => native::deliver(event, &payload, None, &mut |state, run| {
+            crate::__private::with_managed_state(app, state, run)
+        }),
+        Err(err) => Err(Box::new(Diagnostic::new(
+            crate::DiagnosticCode::ArgsInvalid,
+            event,
+            err.to_string(),
+        ))),
+    };
+    if let Err(diagnostic) = result {
+        let on_error = native::ON_ERROR
+            .lock()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone();
+        if let Some(on_error) = on_error {
+            on_error(&diagnostic);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn emit_serializes_payloads_for_the_sink() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let events = Emitter::from_fn(move |event, payload| {
+            sink.lock().unwrap().push((event.to_string(), payload));
+            Ok(())
+        });
+        events.emit("score", json!({ "left": 1 })).unwrap();
+        events.clone().emit("reset", ()).unwrap();
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                ("score".to_string(), json!({ "left": 1 })),
+                ("reset".to_string(), json!(null)),
+            ]
+        );
+    }
+
+    #[test]
+    fn sink_failures_are_dispatch_errors() {
+        let events = Emitter::from_fn(|_, _| Err("window closed".to_string()));
+        assert_eq!(
+            events.emit("x", 1),
+            Err(EmitError::Dispatch {
+                message: "window closed".to_string()
+            })
+        );
+        let mut bad = std::collections::HashMap::new();
+        bad.insert((1, 2), 3);
+        assert!(matches!(
+            events.emit("x", bad),
+            Err(EmitError::Payload { .. })
+        ));
+    }
+
+    #[test]
+    fn undeliverable_events_go_to_the_error_callback() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        on_delivery_error(move |diagnostic| sink.lock().unwrap().push(diagnostic.clone()));
+        deliver_tauri("undeliverable", "{ not json", &());
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].code, crate::DiagnosticCode::ArgsInvalid);
+        assert_eq!(seen[0].command, "undeliverable");
+    }
+
+    #[test]
+    fn states_no_command_borrows_are_unreachable_not_uninitialized() {
+        struct Unborrowed;
+        let id = listen::<Unborrowed, (), _>("unreachable", |_, ()| {});
+        let code = |reach: fn() -> native::Reach| {
+            native::deliver("unreachable", &json!(null), None, &mut |_, _| reach())
+                .unwrap_err()
+                .code
+        };
+        assert_eq!(
+            code(|| native::Reach::NoAccessor),
+            crate::DiagnosticCode::StateUnreachable
+        );
+        assert_eq!(
+            code(|| native::Reach::Unmanaged),
+            crate::DiagnosticCode::StateUninitialized
+        );
+        unlisten(id);
+    }
+
+    #[test]
+    fn capture_collects_route_events_and_nests() {
+        route_emitter().emit("dropped", 0).unwrap();
+        let ((), outer) = capture(|| {
+            route_emitter().emit("outer", 1).unwrap();
+            let ((), inner) = capture(|| route_emitter().emit("inner", 2).unwrap());
+            assert_eq!(inner, vec![("inner".to_string(), json!(2))]);
+        });
+        assert_eq!(outer, vec![("outer".to_string(), json!(1))]);
+    }
+}
@@ -1,12 +1,52 @@
 /// Re-export the `#[command]` proc macro so users write `#[webtau::command]`.
 pub use webtau_macros::command;
 
+mod diagnostic;
+pub use diagnostic::{Diagnostic, DiagnosticCode};
+
+pub mod ts;
+pub use ts::TypeScript;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod router;
+#[cfg(not(target_arch = "wasm32"))]
+pub use router::Router;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
+
+pub mod replay;
+
+pub mod snapshot;
+
+pub mod save;
+
+pub mod storage;
+
+pub mod path;
+
+pub mod events;
+pub use events::Emitter;
+
+pub mod task;
+pub use task::TaskContext;
+
+pub mod channel;
+pub use channel::Channel;
+
+pub mod cancel;
+pub use cancel::Cancel;
+
+#[doc(hidden)]
+#[path = "private.rs"]
+pub mod __private;
+
 /// Generates thread-local state management boilerplate for WASM targets.
 ///
 /// When building a Tauri game for the web, you need to replace
 /// `State<Mutex<T>>` (which requires threads) with thread-local storage
-/// (WASM is single-threaded). This macro generates the required
-/// `thread_local!` + `RefCell` pattern and accessor functions.
+/// (WASM is single-threaded). This macro generates accessor functions over a
+/// thread-local `RefCell` holding the state.
 ///
 /// # Usage
 ///
@@ -17,7 +57,12 @@ pub use webtau_macros::command;
 ///     score: i32,
 /// }
 ///
+/// struct Settings {
+///     volume: f32,
+/// }
+///
 /// wasm_state!(GameWorld);
+/// wasm_state!(settings: Settings);
 /// ```
 ///
 /// # Generated API
@@ -25,78 +70,171 @@ pub use webtau_macros::command;
 /// - `set_state(val: T)` — Initialize or replace the state.
 /// - `with_state(|state| ...)` — Read-only access to the state.
 /// - `with_state_mut(|state| ...)` — Mutable access to the state.
+/// - `try_with_state(|state| ...)` / `try_with_state_mut(|state| ...)` —
+///   Like the above, but return `None` if the state is not initialized.
+///
+/// `with_state` and `with_state_mut` panic if called before `set_state()`.
+///
+/// The named form `wasm_state!(name: T)` generates the same API with `state`
+/// replaced by `name` (`set_settings`, `with_settings_mut`,
+/// `try_with_settings`, ...), so several states can be declared in one module.
 ///
-/// All three functions panic if called before `set_state()`.
+/// The state lives in a thread-local cell keyed by type and name, so naming
+/// the same type twice declares two separate states. `#[webtau::command]`
+/// wrappers find state by the type of their state parameters, like Tauri's
+/// managed state, so commands work unchanged whichever name (if any)
+/// declared it, one command can borrow several states, and `async` commands
+/// can keep a state borrowed across `.await` points. When a type is declared
+/// under several names, a command picks one with
+/// `#[webtau::command(state = "name")]`; without it, the command fails with
+/// `STATE_AMBIGUOUS`.
 #[macro_export]
 macro_rules! wasm_state {
+    ($name:ident : $T:ty) => {
+        $crate::__private::wasm_state_accessors!($crate, $name, $T);
+    };
     ($T:ty) => {
-        ::std::thread_local! {
-            static __WEBTAU_STATE: ::std::cell::RefCell<Option<$T>> =
-                ::std::cell::RefCell::new(None);
-        }
-
-        /// Initialize or replace the global game state.
-        fn set_state(val: $T) {
-            __WEBTAU_STATE.with(|cell| {
-                *cell.borrow_mut() = Some(val);
-            });
-        }
+        $crate::__private::wasm_state_accessors!($crate, state, $T);
+    };
+}
 
-        /// Read-only access to the game state.
-        ///
-        /// # Panics
-        /// Panics if `set_state()` has not been called.
-        fn with_state<__F, __R>(f: __F) -> __R
-        where
-            __F: FnOnce(&$T) -> __R,
-        {
-            __WEBTAU_STATE.with(|cell| {
-                let borrow = cell.borrow();
-                let state = borrow
-                    .as_ref()
-                    .expect("webtau: state not initialized — call set_state() first");
-                f(state)
-            })
-        }
+/// Generates `export_state` / `import_state` commands that snapshot a state
+/// type on both targets.
+///
+/// # Usage
+///
+/// ```rust,ignore
+/// #[derive(Serialize, Deserialize)]
+/// struct GameWorld {
+///     score: i32,
+/// }
+///
+/// webtau::snapshot_commands!(GameWorld, version = 3);
+/// webtau::snapshot_commands!(settings: Settings);
+/// ```
+///
+/// Invoke it outside any `#[cfg(target_arch = "wasm32")]`, next to the other
+/// commands: it expands to two ordinary `#[webtau::command]`s, which borrow
+/// the managed `Mutex<T>` natively and the `wasm_state!` cell on the web.
+/// That is why it is not an option of [`wasm_state!`]: that macro declares
+/// web-only storage and usually sits behind a `wasm32` cfg, while these
+/// commands must exist on both targets, and only for the states a game
+/// chooses to expose.
+///
+/// # Generated commands
+///
+/// - `export_state(encoding?: "json" | "binary") -> string` — the state as
+///   a base64 [`snapshot`] carrying `version` (default 1).
+/// - `import_state(snapshot: string)` — replaces the state. Fails with a
+///   `COMMAND_ERROR` whose `details` are a [`snapshot::SnapshotError`] when
+///   the string is not a snapshot, was taken with another schema version,
+///   or do not match `T`.
+///
+/// The named form `snapshot_commands!(name: T)` generates `export_name` and
+/// `import_name` instead, like `wasm_state!`. `T` must implement `Serialize`
+/// and `DeserializeOwned`.
+#[macro_export]
+macro_rules! snapshot_commands {
+    ($name:ident : $T:ty $(, version = $version:expr)? $(,)?) => {
+        $crate::__private::snapshot_commands!(
+            $crate, $name, $T, $crate::snapshot_commands!(@version $($version)?)
+        );
+    };
+    ($T:ty $(, version = $version:expr)? $(,)?) => {
+        $crate::__private::snapshot_commands!(
+            $crate, state, $T, $crate::snapshot_commands!(@version $($version)?)
+        );
+    };
+    (@version) => { 1 };
+    (@version $version:expr) => { $version };
+}
 
-        /// Mutable access to the game state.
-        ///
-        /// # Panics
-        /// Panics if `set_state()` has not been called.
-        fn with_state_mut<__F, __R>(f: __F) -> __R
-        where
-            __F: FnOnce(&mut $T) -> __R,
-        {
-            __WEBTAU_STATE.with(|cell| {
-                let mut borrow = cell.borrow_mut();
-                let state = borrow
-                    .as_mut()
-                    .expect("webtau: state not initialized — call set_state() first");
-                f(state)
-            })
+/// Builds a Tauri invoke handler for every `#[webtau::command]` linked into
+/// the app, replacing a hand-maintained `tauri::generate_handler![...]` list.
+///
+/// ```rust,ignore
+/// tauri::Builder::default()
+///     .manage(Mutex::new(GameWorld::new()))
+///     .invoke_handler(webtau::generate_handler!(my_game_commands))
+///     .run(tauri::generate_context!())
+/// ```
+///
+/// Commands register themselves at link time. The optional crate names are
+/// linked explicitly, for commands crates the app does not otherwise use.
+/// Unknown command names return `false`, so Tauri reports them as missing.
+/// Only native commands of the default `tauri::Wry` runtime are registered.
+#[macro_export]
+macro_rules! generate_handler {
+    ($($krate:ident),* $(,)?) => {{
+        $(use $krate as _;)*
+        |__webtau_invoke: ::tauri::ipc::Invoke| -> bool {
+            match $crate::__private::native_command(__webtau_invoke.message.command())
+                .and_then(|__webtau_handler| {
+                    __webtau_handler.downcast_ref::<fn(::tauri::ipc::Invoke) -> bool>()
+                }) {
+                ::std::option::Option::Some(__webtau_handler) => __webtau_handler(__webtau_invoke),
+                ::std::option::Option::None => false,
+            }
         }
+    }};
+}
 
-        /// Read-only access to the game state. Returns `None` if not initialized.
-        fn try_with_state<__F, __R>(f: __F) -> Option<__R>
-        where
-            __F: FnOnce(&$T) -> __R,
-        {
-            __WEBTAU_STATE.with(|cell| {
-                let borrow = cell.borrow();
-                borrow.as_ref().map(f)
-            })
-        }
+/// Connects `webtau::events::listen` handlers to Tauri's event bus. Call it
+/// once in `setup`; handlers registered before or after it both receive
+/// events.
+///
+/// ```rust,ignore
+/// tauri::Builder::default()
+///     .manage(Mutex::new(GameWorld::new()))
+///     .setup(|app| {
+///         webtau::attach_listeners!(app.handle());
+///         Ok(())
+///     })
+/// ```
+///
+/// Each event name gets one `tauri::Listener::listen_any` subscription,
+/// which hands payloads to the handlers with their state locked. States are
+/// found through the `#[webtau::command]`s that borrow them, so only the
+/// default `tauri::Wry` runtime is supported.
+#[macro_export]
+macro_rules! attach_listeners {
+    ($app:expr) => {{
+        let __webtau_app: ::tauri::AppHandle = ::std::clone::Clone::clone($app);
+        $crate::__private::attach_listeners(move |__webtau_event: &str| {
+            let __webtau_handle = ::std::clone::Clone::clone(&__webtau_app);
+            let __webtau_name = ::std::string::ToString::to_string(__webtau_event);
+            ::tauri::Listener::listen_any(
+                &__webtau_app,
+                __webtau_event,
+                move |__webtau_tauri_event| {
+                    $crate::__private::deliver_event(
+                        &__webtau_name,
+                        __webtau_tauri_event.payload(),
+                        &__webtau_handle,
+                    );
+                },
+            );
+        });
+    }};
+}
 
-        /// Mutable access to the game state. Returns `None` if not initialized.
-        fn try_with_state_mut<__F, __R>(f: __F) -> Option<__R>
-        where
-            __F: FnOnce(&mut $T) -> __R,
-        {
-            __WEBTAU_STATE.with(|cell| {
-                let mut borrow = cell.borrow_mut();
-                borrow.as_mut().map(f)
-            })
-        }
+/// Re-exports every public item of the given command modules from the
+/// commands crate root, replacing the `#[cfg]`-split `pub use` lists.
+///
+/// ```rust,ignore
+/// // commands/src/lib.rs
+/// mod commands;
+///
+/// webtau::export_commands!(commands);
+/// ```
+///
+/// Native wrappers and WASM exports (including `init`-style functions that
+/// only exist on one target) are picked up alike, so the crate root never
+/// lists command names.
+#[macro_export]
+macro_rules! export_commands {
+    ($($($segment:ident)::+),+ $(,)?) => {
+        $(pub use $($segment)::+::*;)+
     };
 }
 
@@ -153,6 +291,109 @@ mod tests {
         assert_eq!(result, Some(7));
     }
 
+    #[test]
+    fn state_cell_shares_storage_with_accessors() {
+        struct Shared {
+            n: i32,
+        }
+        wasm_state!(Shared);
+        set_state(Shared { n: 1 });
+        let cell = crate::__private::state_cell::<Shared>("state");
+        cell.borrow_mut().as_mut().unwrap().n = 5;
+        assert_eq!(with_state(|s| s.n), 5);
+
+        // An outstanding borrow (e.g. an async command awaiting) is visible
+        // to the generated wrappers as a failed `try_borrow_mut`.
+        let held = cell.borrow();
+        assert!(crate::__private::state_cell::<Shared>("state")
+            .try_borrow_mut()
+            .is_err());
+        drop(held);
+    }
+
+    struct Score {
+        n: i32,
+    }
+
+    mod score_writer {
+        wasm_state!(super::Score);
+
+        pub fn write(n: i32) {
+            set_state(super::Score { n });
+        }
+    }
+
+    #[test]
+    fn separate_invocations_share_state_by_type() {
+        mod score_reader {
+            wasm_state!(crate::tests::Score);
+
+            pub fn read() -> i32 {
+                with_state(|s| s.n)
+            }
+        }
+        score_writer::write(9);
+        assert_eq!(score_reader::read(), 9);
+    }
+
+    mod named {
+        pub struct World {
+            pub tick: u64,
+        }
+
+        pub struct Profile {
+            pub gold: u32,
+        }
+
+        wasm_state!(world: World);
+        wasm_state!(profile: Profile);
+
+        #[test]
+        fn named_instances_coexist_in_one_module() {
+            set_world(World { tick: 1 });
+            set_profile(Profile { gold: 10 });
+            with_world_mut(|w| w.tick += 1);
+            assert_eq!(try_with_world(|w| w.tick), Some(2));
+            assert_eq!(try_with_profile_mut(|p| p.gold), Some(10));
+        }
+
+        #[test]
+        fn named_instance_is_what_commands_borrow() {
+            set_world(World { tick: 5 });
+            let cell = crate::__private::state_cell_of::<World>().unwrap();
+            assert_eq!(cell.borrow().as_ref().map(|w| w.tick), Some(5));
+        }
+
+        mod rival {
+            wasm_state!(rival: super::Profile);
+
+            pub fn set(gold: u32) {
+                set_rival(super::Profile { gold });
+            }
+
+            pub fn gold() -> u32 {
+                with_rival(|p| p.gold)
+            }
+        }
+
+        #[test]
+        fn a_second_name_for_a_type_is_a_separate_state() {
+            set_profile(Profile { gold: 10 });
+            rival::set(3);
+            assert_eq!(with_profile(|p| p.gold), 10);
+            assert_eq!(rival::gold(), 3);
+            assert!(crate::__private::state_cell_of::<Profile>().is_err());
+            let rival = crate::__private::state_cell::<Profile>("rival");
+            assert_eq!(rival.borrow().as_ref().map(|p| p.gold), Some(3));
+        }
+
+        #[test]
+        #[should_panic(expected = "call set_profile() first")]
+        fn named_panic_mentions_setter() {
+            with_profile(|_| {});
+        }
+    }
+
     #[test]
     fn try_with_state_mut_returns_none_before_init() {
         struct Tracker;
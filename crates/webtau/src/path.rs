@@ -0,0 +1,748 @@
+//! App directories as typed virtual paths, shared with the `webtau/path`
+//! and `webtau/fs` shims.
+//!
+//! Every path is a [`BaseDir`] plus a relative part, written the way the JS
+//! shim writes it (`/app/data/saves/slot-1.json`). On the web that string is
+//! the file's key in the `webtau/fs` IndexedDB store; natively it resolves
+//! against the directories Tauri derives from the app identifier:
+//!
+//! ```rust,ignore
+//! // Desktop only, once at startup (e.g. in the Tauri `setup` hook):
+//! webtau::path::set_identifier(&app.config().identifier);
+//!
+//! let save = webtau::path::app_data_dir().join("saves/slot-1.json");
+//! save.write(&bytes).await?; // readable from `webtau/fs` on the web
+//! let level = webtau::path::resolve_resource("levels/1.json").read().await?;
+//! ```
+//!
+//! `VirtualPath` serializes as its virtual string, so commands can take and
+//! return paths the frontend got from `appDataDir()` and `join()`.
+//!
+//! Natively the file methods do their blocking I/O on a thread of their own,
+//! so they are safe to await from async commands.
+
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ts::TypeScript;
+
+/// The app directories shared by both targets.
+///
+/// The system directories (`Config`, `Cache`, `LocalData`) are the per-user
+/// directories natively, not the app's own, so they get virtual roots of
+/// their own (`/config`, ...) and a path under them parses back to the same
+/// directory. The JS shim's `configDir()` and friends return the app roots
+/// instead, so strings built from them parse as the app-scoped variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BaseDir {
+    /// `appDataDir()`: `/app/data`.
+    AppData,
+    /// `appLocalDataDir()`: `/app/local-data`.
+    AppLocalData,
+    /// `appConfigDir()`: `/app/config`.
+    AppConfig,
+    /// `appCacheDir()`: `/app/cache`.
+    AppCache,
+    /// `appLogDir()`: `/app/log`.
+    AppLog,
+    /// `resourceDir()`: `/app/resources`, the app's bundled resources.
+    Resource,
+    /// `tempDir()`: `/app/temp`.
+    Temp,
+    /// The user's config directory: `/config`.
+    Config,
+    /// The user's cache directory: `/cache`.
+    Cache,
+    /// The user's local data directory: `/local-data`.
+    LocalData,
+}
+
+impl BaseDir {
+    /// Every directory, in the order `parse` tries their roots.
+    const ALL: [Self; 10] = [
+        Self::AppData,
+        Self::AppLocalData,
+        Self::AppConfig,
+        Self::AppCache,
+        Self::AppLog,
+        Self::Resource,
+        Self::Temp,
+        Self::Config,
+        Self::Cache,
+        Self::LocalData,
+    ];
+
+    /// The virtual root of this directory, as the JS shim returns it for the
+    /// app-scoped ones.
+    pub fn virtual_root(self) -> &'static str {
+        match self {
+            Self::AppData => "/app/data",
+            Self::AppLocalData => "/app/local-data",
+            Self::AppConfig => "/app/config",
+            Self::AppCache => "/app/cache",
+            Self::AppLog => "/app/log",
+            Self::Resource => "/app/resources",
+            Self::Temp => "/app/temp",
+            Self::Config => "/config",
+            Self::Cache => "/cache",
+            Self::LocalData => "/local-data",
+        }
+    }
+}
+
+/// A file or directory under one of the app's [`BaseDir`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VirtualPath {
+    base: BaseDir,
+    /// Normalized, `/`-separated, without leading or trailing `/`.
+    relative: String,
+}
+
+/// `appDataDir()`.
+pub fn app_data_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::AppData)
+}
+
+/// `appLocalDataDir()`.
+pub fn app_local_data_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::AppLocalData)
+}
+
+/// `appConfigDir()`.
+pub fn app_config_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::AppConfig)
+}
+
+/// `appCacheDir()`.
+pub fn app_cache_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::AppCache)
+}
+
+/// `appLogDir()`.
+pub fn app_log_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::AppLog)
+}
+
+/// `resourceDir()`.
+pub fn resource_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::Resource)
+}
+
+/// `tempDir()`.
+pub fn temp_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::Temp)
+}
+
+/// `configDir()`.
+pub fn config_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::Config)
+}
+
+/// `cacheDir()`.
+pub fn cache_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::Cache)
+}
+
+/// `localDataDir()`.
+pub fn local_data_dir() -> VirtualPath {
+    VirtualPath::root(BaseDir::LocalData)
+}
+
+/// `resolveResource(path)`: a bundled resource by its path relative to the
+/// resource directory.
+pub fn resolve_resource(path: &str) -> VirtualPath {
+    resource_dir().join(path)
+}
+
+impl VirtualPath {
+    /// The base directory itself.
+    pub fn root(base: BaseDir) -> Self {
+        Self {
+            base,
+            relative: String::new(),
+        }
+    }
+
+    pub fn base(&self) -> BaseDir {
+        self.base
+    }
+
+    /// The part below the base directory, e.g. `saves/slot-1.json`.
+    pub fn relative(&self) -> &str {
+        &self.relative
+    }
+
+    /// Appends `path`, resolving `.` and `..` like the JS `join()`. A `..`
+    /// never climbs above the base directory.
+    pub fn join(&self, path: &str) -> Self {
+        let mut segments: Vec<&str> = self.relative.split('/').filter(|s| !s.is_empty()).collect();
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                segment => segments.push(segment),
+            }
+        }
+        Self {
+            base: self.base,
+            relative: segments.join("/"),
+        }
+    }
+
+    /// The containing directory, or `None` for a base directory.
+    pub fn parent(&self) -> Option<Self> {
+        if self.relative.is_empty() {
+            return None;
+        }
+        let relative = self
+            .relative
+            .rsplit_once('/')
+            .map_or("", |(parent, _)| parent);
+        Some(Self {
+            base: self.base,
+            relative: relative.to_string(),
+        })
+    }
+
+    /// The last segment, or `None` for a base directory.
+    pub fn file_name(&self) -> Option<&str> {
+        self.relative
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+    }
+
+    /// The file name's extension without the dot, like the JS `extname()`
+    /// minus its leading `.`.
+    pub fn extension(&self) -> Option<&str> {
+        let name = self.file_name()?;
+        match name.rfind('.') {
+            Some(0) | None => None,
+            Some(dot) => Some(&name[dot + 1..]),
+        }
+    }
+
+    /// Parses a virtual path such as `/app/data/saves/slot-1.json`, as the
+    /// JS shim returns them. `None` if it lies outside every [`BaseDir`].
+    pub fn parse(path: &str) -> Option<Self> {
+        let path = VirtualPath::root(BaseDir::AppData).join_absolute(path);
+        BaseDir::ALL.into_iter().find_map(|base| {
+            let root = base.virtual_root();
+            let rest = path.strip_prefix(root)?;
+            if !rest.is_empty() && !rest.starts_with('/') {
+                return None;
+            }
+            Some(VirtualPath::root(base).join(rest))
+        })
+    }
+
+    /// `path` normalized as an absolute `/`-separated path.
+    fn join_absolute(&self, path: &str) -> String {
+        let mut segments = Vec::new();
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    segments.pop();
+                }
+                segment => segments.push(segment),
+            }
+        }
+        format!("/{}", segments.join("/"))
+    }
+}
+
+impl fmt::Display for VirtualPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.base.virtual_root())?;
+        if !self.relative.is_empty() {
+            write!(f, "/{}", self.relative)?;
+        }
+        Ok(())
+    }
+}
+
+/// The error from parsing a string that is not under a [`BaseDir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotVirtual(pub String);
+
+impl fmt::Display for NotVirtual {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not under an app directory such as /app/data",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for NotVirtual {}
+
+impl FromStr for VirtualPath {
+    type Err = NotVirtual;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        Self::parse(path).ok_or_else(|| NotVirtual(path.to_string()))
+    }
+}
+
+impl Serialize for VirtualPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for VirtualPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        path.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl TypeScript for VirtualPath {
+    fn ts_type() -> String {
+        "string".to_string()
+    }
+}
+
+// ── Native resolution ─────────────────────────────────────────────────
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::io;
+    use std::path::PathBuf;
+    use std::sync::RwLock;
+
+    use super::BaseDir;
+
+    pub(super) static IDENTIFIER: RwLock<Option<String>> = RwLock::new(None);
+    pub(super) static RESOURCE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+    fn env(name: &str) -> Option<PathBuf> {
+        std::env::var_os(name)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+    }
+
+    fn home() -> Option<PathBuf> {
+        env(if cfg!(windows) { "USERPROFILE" } else { "HOME" })
+    }
+
+    fn xdg(var: &str, fallback: &[&str]) -> Option<PathBuf> {
+        env(var)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home().map(|home| fallback.iter().fold(home, |dir, part| dir.join(part))))
+    }
+
+    /// The per-user directories of the `dirs` crate, which Tauri builds on.
+    fn data_dir() -> Option<PathBuf> {
+        if cfg!(windows) {
+            env("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            home().map(|home| home.join("Library").join("Application Support"))
+        } else {
+            xdg("XDG_DATA_HOME", &[".local", "share"])
+        }
+    }
+
+    fn local_data_dir() -> Option<PathBuf> {
+        if cfg!(windows) {
+            env("LOCALAPPDATA")
+        } else {
+            data_dir()
+        }
+    }
+
+    fn config_dir() -> Option<PathBuf> {
+        if cfg!(windows) {
+            env("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            data_dir()
+        } else {
+            xdg("XDG_CONFIG_HOME", &[".config"])
+        }
+    }
+
+    fn cache_dir() -> Option<PathBuf> {
+        if cfg!(windows) {
+            env("LOCALAPPDATA")
+        } else if cfg!(target_os = "macos") {
+            home().map(|home| home.join("Library").join("Caches"))
+        } else {
+            xdg("XDG_CACHE_HOME", &[".cache"])
+        }
+    }
+
+    /// Next to the executable, or `Contents/Resources` in a macOS bundle.
+    fn exe_resource_dir() -> io::Result<PathBuf> {
+        let exe = std::env::current_exe()?;
+        let dir = exe.parent().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "executable has no directory")
+        })?;
+        if cfg!(target_os = "macos") && dir.ends_with("Contents/MacOS") {
+            if let Some(contents) = dir.parent() {
+                return Ok(contents.join("Resources"));
+            }
+        }
+        Ok(dir.to_path_buf())
+    }
+
+    pub(super) fn base_dir(base: BaseDir, identifier: &str) -> io::Result<PathBuf> {
+        let missing = || {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no home directory to resolve {:?} in", base),
+            )
+        };
+        let dir = match base {
+            BaseDir::AppData => data_dir().map(|dir| dir.join(identifier)),
+            BaseDir::AppLocalData => local_data_dir().map(|dir| dir.join(identifier)),
+            BaseDir::AppConfig => config_dir().map(|dir| dir.join(identifier)),
+            BaseDir::AppCache => cache_dir().map(|dir| dir.join(identifier)),
+            BaseDir::AppLog if cfg!(target_os = "macos") => {
+                home().map(|home| home.join("Library").join("Logs").join(identifier))
+            }
+            BaseDir::AppLog => local_data_dir().map(|dir| dir.join(identifier).join("logs")),
+            BaseDir::Resource => {
+                let configured = RESOURCE_DIR
+                    .read()
+                    .unwrap_or_else(|p| p.into_inner())
+                    .clone();
+                return configured.map_or_else(exe_resource_dir, Ok);
+            }
+            BaseDir::Temp => Some(std::env::temp_dir()),
+            BaseDir::Config => config_dir(),
+            BaseDir::Cache => cache_dir(),
+            BaseDir::LocalData => local_data_dir(),
+        };
+        dir.ok_or_else(missing)
+    }
+
+    /// Runs blocking file I/O on a thread of its own, so awaiting it never
+    /// stalls the async runtime the caller runs on.
+    pub(super) async fn unblock<R: Send + 'static>(
+        work: impl FnOnce() -> io::Result<R> + Send + 'static,
+    ) -> io::Result<R> {
+        use std::sync::{Arc, Mutex};
+        use std::task::{Poll, Waker};
+
+        let slot = Arc::new(Mutex::new((None::<io::Result<R>>, None::<Waker>)));
+        let worker = Arc::clone(&slot);
+        std::thread::Builder::new()
+            .name("webtau-path".to_string())
+            .spawn(move || {
+                let out = std::panic::catch_unwind(std::panic::AssertUnwindSafe(work))
+                    .unwrap_or_else(|_| Err(io::Error::other("file I/O panicked")));
+                let mut slot = worker.lock().unwrap_or_else(|p| p.into_inner());
+                slot.0 = Some(out);
+                if let Some(waker) = slot.1.take() {
+                    waker.wake();
+                }
+            })?;
+        std::future::poll_fn(|cx| {
+            let mut slot = slot.lock().unwrap_or_else(|p| p.into_inner());
+            match slot.0.take() {
+                Some(out) => Poll::Ready(out),
+                None => {
+                    slot.1 = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+}
+
+/// Sets the app identifier (Tauri's `identifier`, e.g.
+/// `com.example.my-game`) that native paths resolve against. Call it once at
+/// startup; later calls replace it. Has no effect on the web.
+pub fn set_identifier(identifier: &str) {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        *native::IDENTIFIER
+            .write()
+            .unwrap_or_else(|p| p.into_inner()) = Some(identifier.to_string());
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = identifier;
+}
+
+/// Overrides the native resource directory, e.g. with Tauri's
+/// `app.path().resource_dir()` for Linux packages, which keep resources
+/// under `/usr/lib/<app>`. Defaults to the executable's directory
+/// (`Contents/Resources` in a macOS bundle).
+#[cfg(not(target_arch = "wasm32"))]
+pub fn set_resource_dir(dir: impl Into<std::path::PathBuf>) {
+    *native::RESOURCE_DIR
+        .write()
+        .unwrap_or_else(|p| p.into_inner()) = Some(dir.into());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl VirtualPath {
+    /// The real path, using the identifier from [`set_identifier`].
+    pub fn to_path(&self) -> io::Result<std::path::PathBuf> {
+        let identifier = native::IDENTIFIER
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone();
+        match identifier {
+            Some(identifier) => self.to_path_in(&identifier),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "webtau::path::set_identifier() has not been called",
+            )),
+        }
+    }
+
+    /// The real path for the app `identifier`.
+    pub fn to_path_in(&self, identifier: &str) -> io::Result<std::path::PathBuf> {
+        let base = native::base_dir(self.base, identifier)?;
+        Ok(self
+            .relative
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .fold(base, |path, segment| path.join(segment)))
+    }
+
+    /// Reads the whole file.
+    pub async fn read(&self) -> io::Result<Vec<u8>> {
+        let path = self.to_path()?;
+        native::unblock(move || std::fs::read(path)).await
+    }
+
+    /// Writes the whole file, creating missing parent directories.
+    pub async fn write(&self, contents: &[u8]) -> io::Result<()> {
+        let path = self.to_path()?;
+        let contents = contents.to_vec();
+        native::unblock(move || {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, contents)
+        })
+        .await
+    }
+
+    pub async fn exists(&self) -> io::Result<bool> {
+        let path = self.to_path()?;
+        native::unblock(move || path.try_exists()).await
+    }
+
+    /// Removes the file. Removing a missing file is not an error.
+    pub async fn remove(&self) -> io::Result<()> {
+        let path = self.to_path()?;
+        native::unblock(move || match std::fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        })
+        .await
+    }
+}
+
+// ── Web: through the `webtau/fs` shim ─────────────────────────────────
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) mod web {
+    use wasm_bindgen::prelude::*;
+
+    // Resolved by the app's bundler from the `webtau` npm package, so Rust
+    // and JS share one store (and any `setFsAdapter()` override).
+    #[wasm_bindgen(module = "webtau/fs")]
+    extern "C" {
+        #[wasm_bindgen(js_name = readFile, catch)]
+        pub fn read_file(path: &str) -> Result<js_sys::Promise, JsValue>;
+        #[wasm_bindgen(js_name = writeFile, catch)]
+        pub fn write_file(
+            path: &str,
+            contents: js_sys::Uint8Array,
+        ) -> Result<js_sys::Promise, JsValue>;
+        #[wasm_bindgen(catch)]
+        pub fn exists(path: &str) -> Result<js_sys::Promise, JsValue>;
+        #[wasm_bindgen(catch)]
+        pub fn remove(path: &str) -> Result<js_sys::Promise, JsValue>;
+        #[wasm_bindgen(js_name = readDir, catch)]
+        pub fn read_dir(path: &str, options: &JsValue) -> Result<js_sys::Promise, JsValue>;
+    }
+
+    pub async fn call(promise: Result<js_sys::Promise, JsValue>) -> std::io::Result<JsValue> {
+        let promise = promise.map_err(js_error)?;
+        wasm_bindgen_futures::JsFuture::from(promise)
+            .await
+            .map_err(js_error)
+    }
+
+    fn js_error(err: JsValue) -> std::io::Error {
+        let message = js_sys::Error::from(err.clone())
+            .message()
+            .as_string()
+            .unwrap_or_else(|| format!("{:?}", err));
+        let kind = if message.contains("not found") {
+            std::io::ErrorKind::NotFound
+        } else {
+            std::io::ErrorKind::Other
+        };
+        std::io::Error::new(kind, message)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl VirtualPath {
+    /// Reads the whole file from the `webtau/fs` store.
+    pub async fn read(&self) -> io::Result<Vec<u8>> {
+        let path = self.to_string();
+        let bytes = web::call(web::read_file(&path)).await?;
+        Ok(js_sys::Uint8Array::new(&bytes).to_vec())
+    }
+
+    /// Writes the whole file to the `webtau/fs` store, creating missing
+    /// parent directories.
+    pub async fn write(&self, contents: &[u8]) -> io::Result<()> {
+        let path = self.to_string();
+        web::call(web::write_file(&path, js_sys::Uint8Array::from(contents))).await?;
+        Ok(())
+    }
+
+    pub async fn exists(&self) -> io::Result<bool> {
+        let path = self.to_string();
+        Ok(web::call(web::exists(&path)).await?.is_truthy())
+    }
+
+    /// Removes the file. Removing a missing file is not an error.
+    pub async fn remove(&self) -> io::Result<()> {
+        let path = self.to_string();
+        web::call(web::remove(&path)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn virtual_strings_match_the_js_shim() {
+        assert_eq!(app_data_dir().to_string(), "/app/data");
+        assert_eq!(app_local_data_dir().to_string(), "/app/local-data");
+        assert_eq!(app_config_dir().to_string(), "/app/config");
+        assert_eq!(app_cache_dir().to_string(), "/app/cache");
+        assert_eq!(app_log_dir().to_string(), "/app/log");
+        assert_eq!(resource_dir().to_string(), "/app/resources");
+        assert_eq!(temp_dir().to_string(), "/app/temp");
+        assert_eq!(
+            resolve_resource("./levels//1.json").to_string(),
+            "/app/resources/levels/1.json"
+        );
+    }
+
+    #[test]
+    fn system_dirs_round_trip_through_their_own_roots() {
+        assert_eq!(config_dir().to_string(), "/config");
+        assert_eq!(cache_dir().to_string(), "/cache");
+        assert_eq!(local_data_dir().to_string(), "/local-data");
+        for dir in [config_dir(), cache_dir(), local_data_dir()] {
+            let path = dir.join("x/y.json");
+            assert_eq!(path.to_string().parse(), Ok(path.clone()));
+            let json = serde_json::to_string(&path).unwrap();
+            assert_eq!(serde_json::from_str::<VirtualPath>(&json).unwrap(), path);
+        }
+        assert_eq!("/app/config/a".parse(), Ok(app_config_dir().join("a")));
+    }
+
+    #[test]
+    fn join_stays_inside_the_base() {
+        let saves = app_data_dir().join("saves");
+        assert_eq!(
+            saves.join("../config/./a.json").to_string(),
+            "/app/data/config/a.json"
+        );
+        assert_eq!(
+            saves.join("../../../../etc/passwd").to_string(),
+            "/app/data/etc/passwd"
+        );
+        let slot = saves.join("slot-1.json");
+        assert_eq!(slot.relative(), "saves/slot-1.json");
+        assert_eq!(slot.file_name(), Some("slot-1.json"));
+        assert_eq!(slot.extension(), Some("json"));
+        assert_eq!(slot.parent(), Some(saves.clone()));
+        assert_eq!(saves.parent(), Some(app_data_dir()));
+        assert_eq!(app_data_dir().parent(), None);
+        assert_eq!(app_data_dir().join(".hidden").extension(), None);
+    }
+
+    #[test]
+    fn parses_and_serializes_virtual_strings() {
+        let path: VirtualPath = "/app/local-data/a/../b.bin".parse().unwrap();
+        assert_eq!(path, app_local_data_dir().join("b.bin"));
+        assert_eq!(VirtualPath::parse("/app/data"), Some(app_data_dir()));
+        assert_eq!(VirtualPath::parse("/app/database/x"), None);
+        assert_eq!(VirtualPath::parse("/etc/passwd"), None);
+        assert!("app/data".parse::<VirtualPath>().is_ok());
+
+        let json = serde_json::to_string(&app_cache_dir().join("tiles/0.png")).unwrap();
+        assert_eq!(json, r#""/app/cache/tiles/0.png""#);
+        let back: VirtualPath = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, app_cache_dir().join("tiles/0.png"));
+        assert!(serde_json::from_str::<VirtualPath>(r#""/tmp/x""#).is_err());
+    }
+
+    #[test]
+    fn native_paths_follow_the_identifier() {
+        let path = app_data_dir().join("saves/slot-1.json");
+        let resolved = path.to_path_in("com.example.game").unwrap();
+        assert!(
+            resolved.ends_with("com.example.game/saves/slot-1.json"),
+            "{:?}",
+            resolved
+        );
+        let log = app_log_dir().to_path_in("com.example.game").unwrap();
+        assert!(
+            log.to_string_lossy().contains("com.example.game"),
+            "{:?}",
+            log
+        );
+        assert_eq!(
+            temp_dir().join("x").to_path_in("id").unwrap(),
+            std::env::temp_dir().join("x")
+        );
+        for (system, app) in [
+            (config_dir(), app_config_dir()),
+            (cache_dir(), app_cache_dir()),
+            (local_data_dir(), app_local_data_dir()),
+        ] {
+            let system = system.to_path_in("com.example.game").unwrap();
+            assert_eq!(
+                app.to_path_in("com.example.game").unwrap(),
+                system.join("com.example.game")
+            );
+        }
+    }
+
+    #[test]
+    fn native_io_round_trips() {
+        let root = std::env::temp_dir().join(format!("webtau-path-{}", std::process::id()));
+        set_resource_dir(&root);
+        let file = resolve_resource("levels/1.json");
+        let path = file.to_path_in("unused").unwrap();
+        assert_eq!(path, root.join("levels").join("1.json"));
+
+        set_identifier("unused");
+        crate::__private::block_on(async {
+            assert!(!file.exists().await.unwrap());
+            file.write(b"{}").await.unwrap();
+            assert_eq!(file.read().await.unwrap(), b"{}");
+            file.remove().await.unwrap();
+            file.remove().await.unwrap();
+            assert_eq!(
+                file.read().await.unwrap_err().kind(),
+                io::ErrorKind::NotFound
+            );
+        });
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
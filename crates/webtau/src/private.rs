@@ -0,0 +1,1094 @@
+//! Runtime support for code generated by `#[webtau::command]` and
+//! `wasm_state!`. Not a public API; may change in any release.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use serde::Serialize;
+
+use crate::ts::{Declarations, TypeScript};
+use crate::{Diagnostic, DiagnosticCode};
+
+pub use webtau_macros::__snapshot_commands as snapshot_commands;
+pub use webtau_macros::__wasm_state_accessors as wasm_state_accessors;
+
+/// Every `wasm_state!` cell, by type and then by instance name.
+type StateCells = HashMap<TypeId, Vec<(&'static str, Rc<dyn Any>)>>;
+
+std::thread_local! {
+    static STATE_CELLS: RefCell<StateCells> = RefCell::new(HashMap::new());
+}
+
+/// Builds the diagnostic a generated wrapper reports for `command`.
+pub fn diagnostic(code: DiagnosticCode, command: &str, message: impl Display) -> Diagnostic {
+    Diagnostic::new(code, command, message.to_string())
+}
+
+/// The diagnostic for a native body whose thread or task did not finish:
+/// `err` is the runtime's join error, which carries the panic message.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn join_error(command: &str, err: impl Display) -> Diagnostic {
+    Diagnostic::new(DiagnosticCode::CommandError, command, err.to_string())
+        .with_hint("The command panicked. Check the app's stderr for the panic location.")
+}
+
+/// Names the success type of a `Result` alias the macro cannot read
+/// (`#[command(fallible)]` on `type Saved = Result<Save, E>`).
+pub trait Fallible {
+    type Ok;
+}
+
+impl<T, E> Fallible for Result<T, E> {
+    type Ok = T;
+}
+
+/// Borrowed command error, dispatched by autoref specialization: the
+/// generated wrapper calls `(&&&CommandError(&err)).to_diagnostic(name)`
+/// with all three traits in scope, and method resolution picks the first
+/// impl whose bounds `E` satisfies.
+pub struct CommandError<'a, E>(pub &'a E);
+
+/// `E: Serialize + Display` — message from `Display`, details from serde.
+///
+/// Messages use the alternate form (`{:#}`), which `anyhow` and `eyre` render
+/// with the full cause chain; most other types ignore the flag.
+pub trait SerializeDisplayError {
+    fn to_diagnostic(&self, command: &str) -> Diagnostic;
+}
+
+/// `E: Serialize` — message taken from the serialized value.
+pub trait SerializeError {
+    fn to_diagnostic(&self, command: &str) -> Diagnostic;
+}
+
+/// `E: Display` — message only.
+pub trait DisplayError {
+    fn to_diagnostic(&self, command: &str) -> Diagnostic;
+}
+
+impl<E: Serialize + Display> SerializeDisplayError for &&CommandError<'_, E> {
+    fn to_diagnostic(&self, command: &str) -> Diagnostic {
+        let diagnostic = diagnostic(
+            DiagnosticCode::CommandError,
+            command,
+            format_args!("{:#}", self.0),
+        );
+        match serde_json::to_value(self.0) {
+            Ok(details) => diagnostic.with_details(details),
+            Err(_) => diagnostic,
+        }
+    }
+}
+
+impl<E: Serialize> SerializeError for &CommandError<'_, E> {
+    fn to_diagnostic(&self, command: &str) -> Diagnostic {
+        match serde_json::to_value(self.0) {
+            Ok(serde_json::Value::String(message)) => {
+                let details = serde_json::Value::String(message.clone());
+                diagnostic(DiagnosticCode::CommandError, command, message).with_details(details)
+            }
+            Ok(details) => {
+                diagnostic(DiagnosticCode::CommandError, command, &details).with_details(details)
+            }
+            Err(err) => diagnostic(
+                DiagnosticCode::SerializeFailed,
+                command,
+                format_args!("command error could not be serialized: {}", err),
+            ),
+        }
+    }
+}
+
+impl<E: Display> DisplayError for CommandError<'_, E> {
+    fn to_diagnostic(&self, command: &str) -> Diagnostic {
+        diagnostic(
+            DiagnosticCode::CommandError,
+            command,
+            format_args!("{:#}", self.0),
+        )
+    }
+}
+
+/// Returns the thread-local cell of the `wasm_state!` instance `name` of
+/// `T`, creating an empty one on first use.
+///
+/// Cells are keyed by type and name, so `wasm_state!(a: T)` and
+/// `wasm_state!(b: T)` are two states. The unnamed form is named `state`, so
+/// every `wasm_state!(T)` shares one cell whichever module invoked it.
+pub fn state_cell<T: 'static>(name: &'static str) -> Rc<RefCell<Option<T>>> {
+    STATE_CELLS.with(|cells| {
+        let mut cells = cells.borrow_mut();
+        let named = cells.entry(TypeId::of::<T>()).or_default();
+        let cell = match named.iter().find(|(n, _)| *n == name) {
+            Some((_, cell)) => cell.clone(),
+            None => {
+                let cell: Rc<dyn Any> = Rc::new(RefCell::new(None::<T>));
+                named.push((name, cell.clone()));
+                cell
+            }
+        };
+        downcast_cell(cell)
+    })
+}
+
+/// Returns the cell a command borrows for `T`. Commands find state by type,
+/// mirroring how Tauri's `manage()` keys managed state, so this is the one
+/// `wasm_state!` instance of `T`, or an empty cell if none was used yet.
+///
+/// Fails with a message naming the instances when `T` has several, since a
+/// command without `#[command(state = "...")]` cannot tell which of them it
+/// borrows.
+pub fn state_cell_of<T: 'static>() -> Result<Rc<RefCell<Option<T>>>, String> {
+    STATE_CELLS.with(
+        |cells| match cells.borrow().get(&TypeId::of::<T>()).map(Vec::as_slice) {
+            None | Some([]) => Ok(Rc::new(RefCell::new(None))),
+            Some([(_, cell)]) => Ok(downcast_cell(cell.clone())),
+            Some(named) => Err(format!(
+                "state `{}` has several wasm_state! names ({}) — pick one with \
+                 #[webtau::command(state = \"...\")]",
+                std::any::type_name::<T>(),
+                named
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        },
+    )
+}
+
+fn downcast_cell<T: 'static>(cell: Rc<dyn Any>) -> Rc<RefCell<Option<T>>> {
+    cell.downcast::<RefCell<Option<T>>>()
+        .unwrap_or_else(|_| unreachable!("webtau: state cell registered under the wrong type"))
+}
+
+/// Returns the order in which a command must lock its state parameters.
+///
+/// States are sorted by `TypeId`, so every command locks shared states in the
+/// same global order and two commands can never deadlock on each other.
+/// Returns the type name of the first state that appears twice, since
+/// borrowing it again would alias (`RefCell`) or self-deadlock (`Mutex`).
+pub fn lock_order<const N: usize>(
+    states: [(TypeId, &'static str); N],
+) -> Result<[usize; N], &'static str> {
+    let mut order: [usize; N] = std::array::from_fn(|i| i);
+    order.sort_by_key(|&i| states[i].0);
+    for pair in order.windows(2) {
+        if states[pair[0]].0 == states[pair[1]].0 {
+            return Err(states[pair[1]].1);
+        }
+    }
+    Ok(order)
+}
+
+// ── Command registry ──────────────────────────────────────────────────
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use inventory;
+
+/// A native `#[webtau::command]` registered for `webtau::generate_handler!`.
+///
+/// `handler` is the command's `fn(tauri::ipc::Invoke) -> bool`, erased to
+/// `Any` because this crate does not depend on `tauri`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct NativeCommand {
+    pub name: &'static str,
+    pub handler: &'static (dyn Any + Send + Sync),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+inventory::collect!(NativeCommand);
+
+/// Looks up the registered handler for `name`.
+///
+/// # Panics
+///
+/// On first use, if two commands linked into the binary share a name.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn native_command(name: &str) -> Option<&'static (dyn Any + Send + Sync)> {
+    static COMMANDS: std::sync::OnceLock<HashMap<&'static str, &'static (dyn Any + Send + Sync)>> =
+        std::sync::OnceLock::new();
+    let commands = COMMANDS.get_or_init(|| {
+        let mut commands = HashMap::new();
+        for command in inventory::iter::<NativeCommand>() {
+            if commands.insert(command.name, command.handler).is_some() {
+                panic!(
+                    "webtau: command `{}` is registered more than once; \
+                     give one of them a different `#[command(name = ...)]`",
+                    command.name
+                );
+            }
+        }
+        commands
+    });
+    commands.get(name).copied()
+}
+
+// ── Router entries ────────────────────────────────────────────────────
+
+pub use serde;
+pub use serde_json;
+
+/// A command's JSON entry point for a state type `S`.
+pub type StatefulRoute<S> =
+    fn(&mut S, serde_json::Value) -> Result<serde_json::Value, Box<Diagnostic>>;
+
+/// A stateless command's JSON entry point.
+pub type StatelessRoute = fn(serde_json::Value) -> Result<serde_json::Value, Box<Diagnostic>>;
+
+/// A native `#[webtau::command]` registered for `webtau::Router`.
+///
+/// `route` is a [`StatefulRoute`] for the command's state type or a
+/// [`StatelessRoute`], erased to `Any` so one registry holds every state type.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct RouteCommand {
+    pub name: &'static str,
+    pub route: &'static (dyn Any + Send + Sync),
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+inventory::collect!(RouteCommand);
+
+/// The `webtau::Emitter` a routed command receives: events are kept by an
+/// enclosing `Harness` and dropped otherwise.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn route_emitter() -> crate::Emitter {
+    crate::events::route_emitter()
+}
+
+/// The `webtau::Channel` a routed command receives for its arg `key`: items
+/// are kept by an enclosing `Harness` and dropped otherwise.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn route_channel<T>(key: &'static str) -> crate::Channel<T> {
+    crate::channel::route_channel(key)
+}
+
+// ── Event listeners ───────────────────────────────────────────────────
+
+/// Locks a command's managed state for `webtau::events::listen` handlers.
+///
+/// `access` downcasts the `&dyn Any` to a `tauri::AppHandle`, locks its
+/// `Mutex<T>` and passes the state on, returning `false` if the app does
+/// not manage one. Registered by every native `#[webtau::command]` for each
+/// state type it borrows.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct StateAccessor {
+    pub state: fn() -> TypeId,
+    pub access: fn(&dyn Any, &mut StateRun<'_>) -> bool,
+}
+
+/// A callback handed a locked state, erased to `Any`.
+pub type StateRun<'a> = dyn FnMut(&mut dyn Any) + 'a;
+
+#[cfg(not(target_arch = "wasm32"))]
+inventory::collect!(StateAccessor);
+
+/// Runs `run` with `app`'s managed state of type `state`, through the
+/// first registered [`StateAccessor`] for it.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn with_managed_state(
+    app: &dyn Any,
+    state: TypeId,
+    run: &mut StateRun<'_>,
+) -> crate::events::native::Reach {
+    use crate::events::native::Reach;
+
+    match inventory::iter::<StateAccessor>().find(|accessor| (accessor.state)() == state) {
+        None => Reach::NoAccessor,
+        Some(accessor) if (accessor.access)(app, run) => Reach::Ran,
+        Some(_) => Reach::Unmanaged,
+    }
+}
+
+/// Installs the Tauri bridge of `webtau::attach_listeners!`: `subscribe`
+/// starts forwarding an event name to [`deliver_event`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn attach_listeners(subscribe: impl Fn(&str) + Send + Sync + 'static) {
+    crate::events::native::attach(std::sync::Arc::new(subscribe));
+}
+
+/// Delivers a frontend event's JSON payload to its listeners. `app` is the
+/// `tauri::AppHandle` to find state in.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn deliver_event(event: &str, payload: &str, app: &dyn Any) {
+    crate::events::deliver_tauri(event, payload, app);
+}
+
+// ── Tasks ─────────────────────────────────────────────────────────────
+
+pub use crate::task::TaskRun;
+
+/// Registers a running task for the `start_*` wrapper of the task command
+/// `command`.
+pub fn task_start(command: &'static str) -> TaskRun {
+    crate::task::start(command)
+}
+
+/// A task body's return value as the JSON `poll_*` reports.
+pub fn task_value<T: Serialize + ?Sized>(
+    command: &str,
+    value: &T,
+) -> Result<serde_json::Value, Box<Diagnostic>> {
+    serde_json::to_value(value)
+        .map_err(|err| Box::new(diagnostic(DiagnosticCode::SerializeFailed, command, err)))
+}
+
+/// The args object of `poll_*` and `cancel_*`.
+#[derive(serde::Deserialize)]
+struct TaskArgs {
+    id: u32,
+}
+
+/// The body of the native `poll_*` wrapper.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn task_poll(
+    command: &'static str,
+    id: u32,
+) -> Result<crate::task::TaskStatus, Box<Diagnostic>> {
+    crate::task::poll(command, id)
+}
+
+/// The body of the native `cancel_*` wrapper.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn task_cancel(command: &'static str, id: u32) {
+    crate::task::cancel(command, id)
+}
+
+/// The `webtau::Router` entry point of `poll_*`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn route_task_poll(
+    command: &'static str,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, Box<Diagnostic>> {
+    let name = format!("poll_{}", command);
+    let args: TaskArgs = route_args(&name, args)?;
+    let status = crate::task::poll(command, args.id)?;
+    task_value(&name, &status)
+}
+
+/// The `webtau::Router` entry point of `cancel_*`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn route_task_cancel(
+    command: &'static str,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, Box<Diagnostic>> {
+    let args: TaskArgs = route_args(&format!("cancel_{}", command), args)?;
+    crate::task::cancel(command, args.id);
+    Ok(serde_json::Value::Null)
+}
+
+/// The body of the WASM `poll_*` export. The status is converted with the
+/// JSON-compatible serializer, so the result's objects stay plain objects.
+#[cfg(target_arch = "wasm32")]
+pub fn wasm_task_poll(
+    command: &'static str,
+    args: wasm_bindgen::JsValue,
+) -> Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue> {
+    let name = format!("poll_{}", command);
+    let args: TaskArgs = serde_wasm_bindgen::from_value(args)
+        .map_err(|err| diagnostic(DiagnosticCode::ArgsInvalid, &name, err))?;
+    let status = crate::task::poll(command, args.id).map_err(|err| *err)?;
+    status
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|err| diagnostic(DiagnosticCode::SerializeFailed, &name, err).into())
+}
+
+/// The body of the WASM `cancel_*` export.
+#[cfg(target_arch = "wasm32")]
+pub fn wasm_task_cancel(
+    command: &'static str,
+    args: wasm_bindgen::JsValue,
+) -> Result<(), wasm_bindgen::JsValue> {
+    let args: TaskArgs = serde_wasm_bindgen::from_value(args).map_err(|err| {
+        diagnostic(
+            DiagnosticCode::ArgsInvalid,
+            &format!("cancel_{}", command),
+            err,
+        )
+    })?;
+    crate::task::cancel(command, args.id);
+    Ok(())
+}
+
+/// Runs a WASM task body from the next event-loop turn, after its `start_*`
+/// export has returned, and records its outcome.
+#[cfg(target_arch = "wasm32")]
+pub fn wasm_task_spawn<F>(task: TaskRun, body: F)
+where
+    F: std::future::Future<Output = Result<serde_json::Value, Box<Diagnostic>>> + 'static,
+{
+    wasm_bindgen_futures::spawn_local(async move {
+        crate::task::next_turn().await;
+        task.finish(body.await);
+    });
+}
+
+/// The TypeScript type of `poll_*`'s result for a body returning `result`.
+pub fn ts_task_status(result: &str) -> String {
+    format!(
+        "{{ state: \"running\"; progress?: {{ percent: number; message?: string }} }} \
+         | {{ state: \"completed\"; result: {} }} \
+         | {{ state: \"cancelled\" }} \
+         | {{ state: \"failed\"; error: {{ code: string; runtime: string; command: string; \
+         message: string; hint: string; details?: unknown }} }}",
+        result
+    )
+}
+
+// ── Cancellation ──────────────────────────────────────────────────────
+
+pub use crate::cancel::CancelScope;
+#[cfg(target_arch = "wasm32")]
+pub use crate::cancel::CancelSignal;
+
+/// Starts a cancellable invocation of `command` for its native or routed
+/// wrapper. `id` is the number `invoke()` passed for the `Cancel` argument.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cancel_scope(
+    command: &'static str,
+    id: Option<u32>,
+    timeout_ms: Option<u64>,
+) -> CancelScope {
+    crate::cancel::scope(command, id, timeout_ms)
+}
+
+/// Starts a cancellable invocation of `command` for its WASM wrapper.
+#[cfg(target_arch = "wasm32")]
+pub fn cancel_scope(
+    command: &'static str,
+    signal: CancelSignal,
+    timeout_ms: Option<u64>,
+) -> CancelScope {
+    crate::cancel::scope(command, signal, timeout_ms)
+}
+
+/// The body of the native `cancel_*` wrapper of a cancellable command.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn cancel_request(command: &'static str, id: u32) {
+    crate::cancel::request(command, id)
+}
+
+/// The `webtau::Router` entry point of a cancellable command's `cancel_*`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn route_cancel_request(
+    command: &'static str,
+    args: serde_json::Value,
+) -> Result<serde_json::Value, Box<Diagnostic>> {
+    let args: TaskArgs = route_args(&format!("cancel_{}", command), args)?;
+    crate::cancel::request(command, args.id);
+    Ok(serde_json::Value::Null)
+}
+
+/// Deserializes a router args object, treating `null` as `{}` like
+/// `invoke()` without args.
+pub fn route_args<A: serde::de::DeserializeOwned>(
+    command: &str,
+    args: serde_json::Value,
+) -> Result<A, Box<Diagnostic>> {
+    let args = match args {
+        serde_json::Value::Null => serde_json::Value::Object(serde_json::Map::new()),
+        args => args,
+    };
+    serde_json::from_value(args)
+        .map_err(|err| Box::new(diagnostic(DiagnosticCode::ArgsInvalid, command, err)))
+}
+
+/// Borrowed router result, dispatched by autoref specialization like
+/// [`CommandError`]: `(&&RouteValue(&value)).to_json(name)` serializes `T`
+/// when it implements `Serialize`. Opaque returns such as `impl Trait`
+/// without a `Serialize` bound report `SERIALIZE_FAILED` instead of failing
+/// to compile, since the Tauri and WASM wrappers are where serde is enforced.
+pub struct RouteValue<'a, T: ?Sized>(pub &'a T);
+
+/// `T: Serialize` — the serialized value.
+pub trait RouteSerialize {
+    fn to_json(&self, command: &str) -> Result<serde_json::Value, Box<Diagnostic>>;
+}
+
+/// Any other `T` — a `SERIALIZE_FAILED` diagnostic.
+pub trait RouteOpaque {
+    fn to_json(&self, command: &str) -> Result<serde_json::Value, Box<Diagnostic>>;
+}
+
+impl<T: Serialize + ?Sized> RouteSerialize for &RouteValue<'_, T> {
+    fn to_json(&self, command: &str) -> Result<serde_json::Value, Box<Diagnostic>> {
+        serde_json::to_value(self.0)
+            .map_err(|err| Box::new(diagnostic(DiagnosticCode::SerializeFailed, command, err)))
+    }
+}
+
+impl<T: ?Sized> RouteOpaque for RouteValue<'_, T> {
+    fn to_json(&self, command: &str) -> Result<serde_json::Value, Box<Diagnostic>> {
+        Err(Box::new(diagnostic(
+            DiagnosticCode::SerializeFailed,
+            command,
+            format_args!(
+                "`{}` does not implement Serialize",
+                std::any::type_name::<T>()
+            ),
+        )))
+    }
+}
+
+/// Drives an `async` command to completion on the current thread, parking
+/// it while the future is pending.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake, Waker};
+
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(out) => return out,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+// ── Async state leases ────────────────────────────────────────────────
+
+/// A state locked for an `async` command body that runs on the async
+/// runtime.
+///
+/// The std `MutexGuard` is `!Send`, so it cannot live in a spawned future.
+/// [`lease`] locks the mutex on a blocking-pool thread instead, which keeps
+/// the guard until the `Lease` is released or dropped; the body borrows the
+/// state through the lease meanwhile.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct Lease<T> {
+    state: std::ptr::NonNull<T>,
+    // Dropping it lets the holding thread release the guard, which it
+    // acknowledges through `slot`.
+    release: Option<std::sync::mpsc::Sender<()>>,
+    slot: std::sync::Arc<std::sync::Mutex<Leasing<T>>>,
+}
+
+// SAFETY: a lease stands for the guard, which is exclusive access to `T`,
+// so it may cross threads whenever `&mut T` (or `&T`) may.
+#[cfg(not(target_arch = "wasm32"))]
+unsafe impl<T: Send> Send for Lease<T> {}
+#[cfg(not(target_arch = "wasm32"))]
+unsafe impl<T: Sync> Sync for Lease<T> {}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> std::ops::Deref for Lease<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the holding thread keeps the guard `state` points into
+        // until `release` is dropped, which outlives this borrow.
+        unsafe { self.state.as_ref() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> std::ops::DerefMut for Lease<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: as in `deref`; `&mut self` makes the borrow unique.
+        unsafe { self.state.as_mut() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> Lease<T> {
+    /// Unlocks the state and resolves once the holding thread has let go,
+    /// so the state is free again once the command that held it resolves.
+    pub async fn release(mut self) {
+        self.release = None;
+        std::future::poll_fn(|cx| poll_leasing(&self.slot, cx)).await;
+    }
+}
+
+/// Tells the holding thread to unlock without waiting for it; a lease that
+/// is not [released](Lease::release), e.g. because its body panicked, frees
+/// the state shortly after.
+#[cfg(not(target_arch = "wasm32"))]
+impl<T> Drop for Lease<T> {
+    fn drop(&mut self) {
+        self.release = None;
+    }
+}
+
+/// What the holding thread reports to the waiting [`lease`] and
+/// [`Lease::release`].
+#[cfg(not(target_arch = "wasm32"))]
+enum Leasing<T> {
+    Waiting(Option<std::task::Waker>),
+    Locked(LockedState<T>),
+    Released,
+    Failed(String),
+}
+
+/// Waits for the holding thread's next report.
+#[cfg(not(target_arch = "wasm32"))]
+fn poll_leasing<T>(
+    slot: &std::sync::Mutex<Leasing<T>>,
+    cx: &mut std::task::Context<'_>,
+) -> std::task::Poll<Leasing<T>> {
+    let mut slot = slot.lock().unwrap_or_else(|p| p.into_inner());
+    match std::mem::replace(&mut *slot, Leasing::Waiting(Some(cx.waker().clone()))) {
+        Leasing::Waiting(_) => std::task::Poll::Pending,
+        report => std::task::Poll::Ready(report),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct LockedState<T>(std::ptr::NonNull<T>);
+
+// SAFETY: the pointer is only handed over to become a `Lease`.
+#[cfg(not(target_arch = "wasm32"))]
+unsafe impl<T: Send> Send for LockedState<T> {}
+
+/// Locks the `Mutex<T>` that `state` finds in `app` on a thread that
+/// `spawn_blocking` provides (the async runtime's blocking pool), and
+/// resolves once the lock is held. Waiting for the lock never blocks the
+/// async runtime.
+///
+/// # Panics
+///
+/// If `state` panics, e.g. because the state is not managed.
+#[cfg(not(target_arch = "wasm32"))]
+pub async fn lease<A, T>(
+    app: A,
+    state: fn(&A) -> &std::sync::Mutex<T>,
+    spawn_blocking: impl FnOnce(Box<dyn FnOnce() + Send>),
+) -> Lease<T>
+where
+    A: Send + 'static,
+    T: Send + 'static,
+{
+    use std::sync::{mpsc, Arc, Mutex};
+
+    fn report<T>(slot: &Mutex<Leasing<T>>, next: Leasing<T>) {
+        let mut slot = slot.lock().unwrap_or_else(|p| p.into_inner());
+        if let Leasing::Waiting(Some(waker)) = std::mem::replace(&mut *slot, next) {
+            waker.wake();
+        }
+    }
+
+    let slot = Arc::new(Mutex::new(Leasing::Waiting(None)));
+    // If this future is dropped before the lock is taken, `release` goes
+    // with it and the holder returns as soon as it has locked.
+    let (release, release_rx) = mpsc::channel::<()>();
+    let holder = Arc::clone(&slot);
+    spawn_blocking(Box::new(move || {
+        let locked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            state(&app).lock().unwrap_or_else(|p| p.into_inner())
+        }));
+        match locked {
+            Ok(mut guard) => {
+                let state = std::ptr::NonNull::from(&mut *guard);
+                report(&holder, Leasing::Locked(LockedState(state)));
+                // Returns once the lease is released or dropped.
+                let _ = release_rx.recv();
+                drop(guard);
+                report(&holder, Leasing::Released);
+            }
+            Err(panic) => report(&holder, Leasing::Failed(panic_message(&*panic))),
+        }
+    }));
+
+    let state = match std::future::poll_fn(|cx| poll_leasing(&slot, cx)).await {
+        Leasing::Locked(LockedState(state)) => state,
+        Leasing::Failed(message) => panic!("{}", message),
+        Leasing::Waiting(_) | Leasing::Released => unreachable!(),
+    };
+    Lease {
+        state,
+        release: Some(release),
+        slot,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "state lease thread panicked".to_string())
+}
+
+// ── Command log ───────────────────────────────────────────────────────
+
+pub use crate::replay::is_recording;
+
+/// Starts a `webtau::replay` entry for a command that holds its state.
+/// Generated wrappers check [`is_recording`] first, so args are only
+/// serialized while a recorder runs.
+pub fn record<const N: usize>(command: &str, args: [(&str, serde_json::Value); N]) -> Record {
+    let args = args
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+    Record(crate::replay::Pending::new(command, args))
+}
+
+/// A started entry, appended by [`Record::finish`] once the command returns.
+pub struct Record(crate::replay::Pending);
+
+impl Record {
+    /// Appends the entry with the diagnostic the command failed with, if any.
+    pub fn finish(self, error: Option<&Diagnostic>) {
+        self.0.finish(error);
+    }
+}
+
+/// A wrapper's outcome, dispatched like [`RecordArg`]:
+/// `(&&RecordOutcome(&out)).failure()` is the diagnostic of an
+/// `Err(Diagnostic)` and `None` for any other value.
+pub struct RecordOutcome<'a, T>(pub &'a T);
+
+/// `Result<T, Diagnostic>` — the diagnostic, if it is an `Err`.
+pub trait RecordFailure {
+    fn failure(&self) -> Option<&Diagnostic>;
+}
+
+/// Any other outcome — `None`.
+pub trait RecordSuccess {
+    fn failure(&self) -> Option<&Diagnostic>;
+}
+
+impl<T> RecordFailure for &RecordOutcome<'_, Result<T, Diagnostic>> {
+    fn failure(&self) -> Option<&Diagnostic> {
+        self.0.as_ref().err()
+    }
+}
+
+impl<T> RecordSuccess for RecordOutcome<'_, T> {
+    fn failure(&self) -> Option<&Diagnostic> {
+        None
+    }
+}
+
+/// Borrowed command arg, dispatched by autoref specialization like
+/// [`RouteValue`]: `(&&RecordArg(&arg)).to_record()` serializes `T` when it
+/// implements `Serialize` and records `null` otherwise.
+pub struct RecordArg<'a, T: ?Sized>(pub &'a T);
+
+/// `T: Serialize` — the serialized value, or `null` if serialization fails.
+pub trait RecordSerialize {
+    fn to_record(&self) -> serde_json::Value;
+}
+
+/// Any other `T` — `null`.
+pub trait RecordOpaque {
+    fn to_record(&self) -> serde_json::Value;
+}
+
+impl<T: Serialize + ?Sized> RecordSerialize for &RecordArg<'_, T> {
+    fn to_record(&self) -> serde_json::Value {
+        serde_json::to_value(self.0).unwrap_or(serde_json::Value::Null)
+    }
+}
+
+impl<T: ?Sized> RecordOpaque for RecordArg<'_, T> {
+    fn to_record(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+}
+
+// ── TypeScript signatures ─────────────────────────────────────────────
+
+/// One `#[webtau::command]`'s TypeScript signature, submitted to
+/// `inventory` by the generated code and rendered by
+/// `webtau::ts::render_commands()`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct TsCommand {
+    pub name: &'static str,
+    pub signature: fn(&mut Declarations) -> TsSignature,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+inventory::collect!(TsCommand);
+
+pub struct TsSignature {
+    pub args: Vec<TsField>,
+    pub result: String,
+}
+
+/// A property of a TypeScript object type. `key` is already quoted if it
+/// is not a plain identifier.
+pub struct TsField {
+    pub key: &'static str,
+    pub optional: bool,
+    pub ty: String,
+}
+
+/// A field of type `T`, optional when `T` is an `Option` or `optional` is set
+/// (`#[serde(skip_serializing_if)]`).
+pub fn ts_field<T: TypeScript + ?Sized>(
+    decls: &mut Declarations,
+    key: &'static str,
+    optional: bool,
+) -> TsField {
+    TsField {
+        key,
+        optional: optional || T::ts_optional(),
+        ty: decls.reference::<T>(),
+    }
+}
+
+/// Renders `{ a: number; b?: string }`, or one property per line for
+/// top-level interfaces.
+pub fn ts_object(fields: &[TsField], multiline: bool) -> String {
+    if fields.is_empty() {
+        return "{}".to_string();
+    }
+    let props = fields
+        .iter()
+        .map(|f| format!("{}{}: {}", f.key, if f.optional { "?" } else { "" }, f.ty));
+    if multiline {
+        let body: String = props.map(|p| format!("  {};\n", p)).collect();
+        format!("{{\n{}}}", body)
+    } else {
+        format!("{{ {} }}", props.collect::<Vec<_>>().join("; "))
+    }
+}
+
+/// Quotes an object key unless it is a plain JavaScript identifier.
+pub fn ts_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let ident = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if ident {
+        key.to_string()
+    } else {
+        serde_json::Value::from(key).to_string()
+    }
+}
+
+/// Command arg or return type, dispatched by autoref specialization like
+/// [`CommandError`]: `(&&TsProbe::<T>::new()).ts_field(..)` uses `T`'s
+/// [`TypeScript`] impl when there is one and falls back to `unknown`.
+pub struct TsProbe<T: ?Sized>(PhantomData<T>);
+
+impl<T: ?Sized> TsProbe<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// `T: TypeScript` — the real type.
+pub trait TsKnown {
+    fn ts_field(&self, decls: &mut Declarations, key: &'static str) -> TsField;
+    fn ts_type(&self, decls: &mut Declarations) -> String;
+}
+
+/// Any other `T` — `unknown`.
+pub trait TsUnknown {
+    fn ts_field(&self, decls: &mut Declarations, key: &'static str) -> TsField;
+    fn ts_type(&self, decls: &mut Declarations) -> String;
+}
+
+impl<T: TypeScript + ?Sized> TsKnown for &TsProbe<T> {
+    fn ts_field(&self, decls: &mut Declarations, key: &'static str) -> TsField {
+        ts_field::<T>(decls, key, false)
+    }
+    fn ts_type(&self, decls: &mut Declarations) -> String {
+        decls.reference::<T>()
+    }
+}
+
+impl<T: ?Sized> TsUnknown for TsProbe<T> {
+    fn ts_field(&self, _decls: &mut Declarations, key: &'static str) -> TsField {
+        TsField {
+            key,
+            optional: false,
+            ty: "unknown".to_string(),
+        }
+    }
+    fn ts_type(&self, _decls: &mut Declarations) -> String {
+        "unknown".to_string()
+    }
+}
+
+#[cfg(test)]
+// The `CommandError` tests spell out the generated `&&&` autoref call.
+#[allow(clippy::needless_borrow)]
+mod tests {
+    use super::*;
+    use std::any::type_name;
+
+    struct World;
+    struct Profile;
+
+    #[derive(Serialize)]
+    #[serde(tag = "kind")]
+    enum SaveError {
+        DiskFull { needed: u64 },
+    }
+
+    impl Display for SaveError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::DiskFull { needed } => write!(f, "disk full: need {needed} bytes"),
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Code(u32);
+
+    #[test]
+    fn serializable_display_error_keeps_both() {
+        let err = SaveError::DiskFull { needed: 10 };
+        let diagnostic = (&&&CommandError(&err)).to_diagnostic("save");
+        assert_eq!(diagnostic.code, DiagnosticCode::CommandError);
+        assert_eq!(diagnostic.message, "disk full: need 10 bytes");
+        assert_eq!(
+            diagnostic.details,
+            Some(serde_json::json!({ "kind": "DiskFull", "needed": 10 }))
+        );
+    }
+
+    #[test]
+    fn serialize_only_error_uses_value_as_message() {
+        let diagnostic = (&&&CommandError(&Code(7))).to_diagnostic("load");
+        assert_eq!(diagnostic.message, "7");
+        assert_eq!(diagnostic.details, Some(serde_json::json!(7)));
+    }
+
+    #[test]
+    fn display_only_error_has_no_details() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "save slot missing");
+        let diagnostic = (&&&CommandError(&err)).to_diagnostic("load");
+        assert_eq!(diagnostic.message, "save slot missing");
+        assert_eq!(diagnostic.details, None);
+    }
+
+    #[test]
+    fn anyhow_errors_keep_their_cause_chain() {
+        let err = anyhow::anyhow!("slot 3 is corrupt").context("failed to load save");
+        let diagnostic = (&&&CommandError(&err)).to_diagnostic("load");
+        assert_eq!(diagnostic.message, "failed to load save: slot 3 is corrupt");
+        assert_eq!(diagnostic.details, None);
+    }
+
+    #[test]
+    fn string_errors_keep_message_and_details_identical() {
+        let err = String::from("inventory is empty");
+        let diagnostic = (&&&CommandError(&err)).to_diagnostic("take");
+        assert_eq!(diagnostic.message, "inventory is empty");
+        assert_eq!(
+            diagnostic.details,
+            Some(serde_json::json!("inventory is empty"))
+        );
+    }
+    #[test]
+    fn route_values_serialize_or_report_opaque_types() {
+        struct Opaque;
+        assert_eq!(
+            (&&RouteValue(&[1, 2])).to_json("list").unwrap(),
+            serde_json::json!([1, 2])
+        );
+        let err = (&&RouteValue(&Opaque)).to_json("opaque").unwrap_err();
+        assert_eq!(err.code, DiagnosticCode::SerializeFailed);
+        assert!(err
+            .message
+            .ends_with("Opaque` does not implement Serialize"));
+    }
+
+    #[test]
+    fn block_on_drives_futures_to_completion() {
+        assert_eq!(block_on(async { 7 }), 7);
+    }
+
+    fn spawn_thread(hold: Box<dyn FnOnce() + Send>) {
+        std::thread::spawn(hold);
+    }
+
+    #[test]
+    fn leases_hold_the_lock_until_released() {
+        let app = std::sync::Arc::new(std::sync::Mutex::new(1));
+        let mut lease = block_on(lease(app.clone(), |app| &**app, spawn_thread));
+        *lease += 1;
+        assert!(app.try_lock().is_err());
+        block_on(lease.release());
+        assert_eq!(*app.try_lock().unwrap(), 2);
+    }
+
+    #[test]
+    fn dropped_leases_unlock_without_waiting() {
+        let app = std::sync::Arc::new(std::sync::Mutex::new(1));
+        let mut lease = block_on(lease(app.clone(), |app| &**app, spawn_thread));
+        *lease += 1;
+        drop(lease);
+        assert_eq!(*app.lock().unwrap(), 2);
+    }
+
+    struct Settings;
+
+    fn entry<T: 'static>() -> (TypeId, &'static str) {
+        (TypeId::of::<T>(), type_name::<T>())
+    }
+
+    #[test]
+    fn state_cell_is_keyed_by_type_and_name() {
+        struct Score(u32);
+        *state_cell::<Score>("state").borrow_mut() = Some(Score(3));
+        assert_eq!(
+            state_cell::<Score>("state").borrow().as_ref().map(|s| s.0),
+            Some(3)
+        );
+        assert!(state_cell::<Score>("best").borrow().is_none());
+        assert!(state_cell::<World>("state").borrow().is_none());
+    }
+
+    #[test]
+    fn commands_find_the_only_instance_of_a_type() {
+        struct Lives(u32);
+        assert!(state_cell_of::<Lives>().unwrap().borrow().is_none());
+        *state_cell::<Lives>("lives").borrow_mut() = Some(Lives(3));
+        let cell = state_cell_of::<Lives>().unwrap();
+        assert_eq!(cell.borrow().as_ref().map(|l| l.0), Some(3));
+
+        state_cell::<Lives>("extra");
+        let err = state_cell_of::<Lives>().err().unwrap();
+        assert!(err.contains("(lives, extra)"), "{}", err);
+    }
+
+    #[test]
+    fn lock_order_is_independent_of_parameter_order() {
+        let forward =
+            lock_order([entry::<World>(), entry::<Profile>(), entry::<Settings>()]).unwrap();
+        let reverse =
+            lock_order([entry::<Settings>(), entry::<Profile>(), entry::<World>()]).unwrap();
+        let ids = [
+            TypeId::of::<World>(),
+            TypeId::of::<Profile>(),
+            TypeId::of::<Settings>(),
+        ];
+        let rids = [
+            TypeId::of::<Settings>(),
+            TypeId::of::<Profile>(),
+            TypeId::of::<World>(),
+        ];
+        let forward_types: Vec<_> = forward.iter().map(|&i| ids[i]).collect();
+        let reverse_types: Vec<_> = reverse.iter().map(|&i| rids[i]).collect();
+        assert_eq!(forward_types, reverse_types);
+    }
+
+    #[test]
+    fn lock_order_rejects_aliased_state() {
+        type Alias = World;
+        let err = lock_order([entry::<World>(), entry::<Profile>(), entry::<Alias>()]).unwrap_err();
+        assert!(err.ends_with("World"));
+    }
+}
@@ -0,0 +1,583 @@
+//! Command-log recording and deterministic replay.
+//!
+//! While a [`Recorder`] is running, every `#[webtau::command]` that borrows
+//! state mutably appends a [`LogEntry`] (`seq`, command name, args object,
+//! timestamp, and the diagnostic it failed with, if any) when it returns,
+//! before releasing its state, on desktop and on the web alike. The log
+//! therefore lists mutations in the order they were applied.
+//!
+//! ```rust,ignore
+//! // When the game starts, e.g. behind a "record session" setting:
+//! webtau::replay::start(webtau::replay::Recorder::ring(10_000))?;
+//!
+//! // When the player files a bug report:
+//! let log = webtau::replay::recorded().to_jsonl();
+//!
+//! // In a test or CLI, natively:
+//! let log = CommandLog::from_jsonl(&report)?;
+//! let world = webtau::replay::replay(&log, GameWorld::new)?;
+//! ```
+//!
+//! Logs are JSON Lines: a [`LogHeader`] naming the format version and the
+//! recording `webtau` version, then one entry per line, so a
+//! [`Recorder::file`] log survives a crash up to its last command.
+//!
+//! Args are recorded through their `Serialize` impl; an arg type without one
+//! is recorded as `null`, and such commands cannot be replayed. Replay
+//! assumes commands are deterministic given their state and args.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::Diagnostic;
+
+/// The `format` field of every log header.
+pub const FORMAT: &str = "webtau-command-log";
+
+/// The log format version written by this crate. Logs with a newer version
+/// are rejected.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// First line of a command log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogHeader {
+    /// Always [`FORMAT`].
+    pub format: String,
+    /// [`FORMAT_VERSION`] at recording time.
+    pub version: u32,
+    /// Version of the `webtau` crate that recorded the log.
+    pub webtau: String,
+}
+
+impl Default for LogHeader {
+    fn default() -> Self {
+        Self {
+            format: FORMAT.to_string(),
+            version: FORMAT_VERSION,
+            webtau: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// One recorded command invocation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Position in the recording, from 0.
+    pub seq: u64,
+    /// Command name, as `webtau::Router` dispatches it.
+    pub command: String,
+    /// The args object in the command's `rename_all` casing.
+    pub args: Value,
+    /// Milliseconds since the Unix epoch, when the command took its state.
+    pub timestamp: u64,
+    /// The diagnostic the command returned, if it failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<Diagnostic>,
+}
+
+/// A header plus the recorded entries, in `seq` order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandLog {
+    pub header: LogHeader,
+    pub entries: Vec<LogEntry>,
+}
+
+/// A malformed, unsupported, or unreplayable command log.
+#[derive(Debug)]
+pub enum LogError {
+    /// A line is not valid JSON for its position (1-based line number).
+    Parse {
+        line: usize,
+        error: serde_json::Error,
+    },
+    /// The header names another format or a newer version.
+    Unsupported(LogHeader),
+    /// The log does not start at `seq` 0 (a wrapped ring buffer), so it
+    /// cannot rebuild state from scratch.
+    Truncated {
+        first_seq: u64,
+    },
+    /// A command failed to dispatch: unknown name or args it no longer accepts.
+    Dispatch {
+        seq: u64,
+        diagnostic: Box<Diagnostic>,
+    },
+    /// The entry at `index` names a command linked into the binary that
+    /// `Router<S>` cannot run: it borrows states other than `S`. A command
+    /// borrowing several states needs `S` to be their tuple.
+    Unroutable {
+        index: usize,
+        command: String,
+        state: &'static str,
+    },
+    /// A command succeeded where it failed when recorded, failed where it
+    /// succeeded, or failed differently. `None` stands for success.
+    Mismatch {
+        seq: u64,
+        recorded: Option<Box<Diagnostic>>,
+        replayed: Option<Box<Diagnostic>>,
+    },
+    Io(std::io::Error),
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse { line, error } => write!(f, "command log line {}: {}", line, error),
+            Self::Unsupported(header) => write!(
+                f,
+                "unsupported command log `{}` version {} (recorded by webtau {}); \
+                 this build reads `{}` up to version {}",
+                header.format, header.version, header.webtau, FORMAT, FORMAT_VERSION
+            ),
+            Self::Truncated { first_seq } => write!(
+                f,
+                "command log starts at seq {}; earlier commands were dropped",
+                first_seq
+            ),
+            Self::Dispatch { seq, diagnostic } => {
+                write!(f, "command log seq {}: {}", seq, diagnostic)
+            }
+            Self::Unroutable {
+                index,
+                command,
+                state,
+            } => write!(
+                f,
+                "command log entry {}: `{}` does not take state `{}`, \
+                 so `Router<{}>` cannot replay it",
+                index, command, state, state
+            ),
+            Self::Mismatch {
+                seq,
+                recorded,
+                replayed,
+            } => write!(
+                f,
+                "command log seq {}: recorded {}, replayed {}",
+                seq,
+                Outcome(recorded.as_deref()),
+                Outcome(replayed.as_deref())
+            ),
+            Self::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+/// A command outcome in [`LogError::Mismatch`] messages.
+struct Outcome<'a>(Option<&'a Diagnostic>);
+
+impl fmt::Display for Outcome<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(diagnostic) => write!(f, "{}: {}", diagnostic.code, diagnostic.message),
+            None => f.write_str("success"),
+        }
+    }
+}
+
+impl std::error::Error for LogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse { error, .. } => Some(error),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LogError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl CommandLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the log starts at the beginning of its recording.
+    pub fn is_complete(&self) -> bool {
+        self.entries.first().map_or(true, |entry| entry.seq == 0)
+    }
+
+    /// Serializes the log as JSON Lines, header first.
+    pub fn to_jsonl(&self) -> String {
+        let mut out = json_line(&self.header);
+        for entry in &self.entries {
+            out.push_str(&json_line(entry));
+        }
+        out
+    }
+
+    /// Parses a JSON Lines log, checking its header. Blank lines are ignored.
+    pub fn from_jsonl(jsonl: &str) -> Result<Self, LogError> {
+        let mut lines = jsonl
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let header: LogHeader = match lines.next() {
+            Some((i, line)) => parse_line(i, line)?,
+            None => return Ok(Self::new()),
+        };
+        if header.format != FORMAT || header.version > FORMAT_VERSION {
+            return Err(LogError::Unsupported(header));
+        }
+        let entries = lines
+            .map(|(i, line)| parse_line(i, line))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { header, entries })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, LogError> {
+        Self::from_jsonl(&std::fs::read_to_string(path)?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_jsonl())
+    }
+}
+
+fn json_line<T: Serialize>(value: &T) -> String {
+    let mut line = serde_json::to_string(value).expect("command log entries serialize to JSON");
+    line.push('\n');
+    line
+}
+
+fn parse_line<T: serde::de::DeserializeOwned>(index: usize, line: &str) -> Result<T, LogError> {
+    serde_json::from_str(line).map_err(|error| LogError::Parse {
+        line: index + 1,
+        error,
+    })
+}
+
+// ── Recording ─────────────────────────────────────────────────────────
+
+/// Where a recording goes.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    capacity: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    file: Option<std::path::PathBuf>,
+}
+
+impl Recorder {
+    /// Keeps the last `capacity` entries in memory, for [`recorded`].
+    pub fn ring(capacity: usize) -> Self {
+        Self {
+            capacity,
+            #[cfg(not(target_arch = "wasm32"))]
+            file: None,
+        }
+    }
+
+    /// Writes the header to `path`, replacing the file, then appends each
+    /// entry as it is recorded. Nothing is kept in memory.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn file(path: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            capacity: 0,
+            file: Some(path.into()),
+        }
+    }
+}
+
+struct Recording {
+    next_seq: u64,
+    capacity: usize,
+    ring: VecDeque<LogEntry>,
+    #[cfg(not(target_arch = "wasm32"))]
+    file: Option<std::fs::File>,
+}
+
+static RECORDING_ON: AtomicBool = AtomicBool::new(false);
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+fn recording() -> std::sync::MutexGuard<'static, Option<Recording>> {
+    RECORDING.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+/// Starts a new recording, replacing any running one. Sequence numbers
+/// restart at 0.
+pub fn start(recorder: Recorder) -> std::io::Result<()> {
+    #[cfg(not(target_arch = "wasm32"))]
+    let file = match &recorder.file {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::fs::File::create(path)?;
+            std::io::Write::write_all(&mut file, json_line(&LogHeader::default()).as_bytes())?;
+            Some(file)
+        }
+        None => None,
+    };
+    *recording() = Some(Recording {
+        next_seq: 0,
+        capacity: recorder.capacity,
+        ring: VecDeque::new(),
+        #[cfg(not(target_arch = "wasm32"))]
+        file,
+    });
+    RECORDING_ON.store(true, Ordering::Release);
+    Ok(())
+}
+
+/// Stops recording and returns the in-memory entries.
+pub fn stop() -> CommandLog {
+    RECORDING_ON.store(false, Ordering::Release);
+    let entries = recording()
+        .take()
+        .map(|recording| recording.ring.into())
+        .unwrap_or_default();
+    CommandLog {
+        header: LogHeader::default(),
+        entries,
+    }
+}
+
+/// The in-memory entries recorded so far, without stopping.
+pub fn recorded() -> CommandLog {
+    let entries = recording()
+        .as_ref()
+        .map(|recording| recording.ring.iter().cloned().collect())
+        .unwrap_or_default();
+    CommandLog {
+        header: LogHeader::default(),
+        entries,
+    }
+}
+
+pub fn is_recording() -> bool {
+    RECORDING_ON.load(Ordering::Acquire)
+}
+
+/// An entry for a command that holds its state and is still running.
+/// Generated wrappers [`finish`](Self::finish) it with the command's
+/// outcome before releasing the state, so entries are in the order the
+/// mutations were applied. An entry dropped unfinished, by a panicking
+/// command, is appended without an error.
+pub(crate) struct Pending {
+    entry: Option<LogEntry>,
+}
+
+impl Pending {
+    pub(crate) fn new(command: &str, args: Map<String, Value>) -> Self {
+        Self {
+            entry: Some(LogEntry {
+                seq: 0,
+                command: command.to_string(),
+                args: Value::Object(args),
+                timestamp: now_ms(),
+                error: None,
+            }),
+        }
+    }
+
+    pub(crate) fn finish(mut self, error: Option<&Diagnostic>) {
+        if let Some(mut entry) = self.entry.take() {
+            entry.error = error.cloned();
+            append(entry);
+        }
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if let Some(entry) = self.entry.take() {
+            append(entry);
+        }
+    }
+}
+
+fn append(mut entry: LogEntry) {
+    let mut recording = recording();
+    let Some(recording) = recording.as_mut() else {
+        return;
+    };
+    entry.seq = recording.next_seq;
+    recording.next_seq += 1;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(file) = &mut recording.file {
+        // A failing disk must not fail the command being recorded.
+        let _ = std::io::Write::write_all(file, json_line(&entry).as_bytes());
+    }
+    if recording.capacity > 0 {
+        if recording.ring.len() == recording.capacity {
+            recording.ring.pop_front();
+        }
+        recording.ring.push_back(entry);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn now_ms() -> u64 {
+    #[wasm_bindgen::prelude::wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = Date, js_name = now)]
+        fn date_now() -> f64;
+    }
+    date_now() as u64
+}
+
+// ── Replay ────────────────────────────────────────────────────────────
+
+/// Rebuilds state by running every entry of `log` against `init()` through
+/// [`Router`](crate::Router).
+///
+/// Every entry must be a command `Router<S>` can dispatch: a command
+/// borrowing other states is a [`LogError::Unroutable`]
+/// naming its index, and an unknown command a [`LogError::Dispatch`]. Each
+/// replayed command must end as it did when recorded: succeed, or fail with
+/// the same code, message and details; otherwise replay stops with a
+/// [`LogError::Mismatch`].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn replay<S: 'static>(log: &CommandLog, init: impl FnOnce() -> S) -> Result<S, LogError> {
+    if let Some(first) = log.entries.first().filter(|_| !log.is_complete()) {
+        return Err(LogError::Truncated {
+            first_seq: first.seq,
+        });
+    }
+    let mut state = init();
+    apply(log, &mut state)?;
+    Ok(state)
+}
+
+/// Runs every entry of `log` against existing `state`, e.g. one restored
+/// from a save taken where a truncated log begins.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn apply<S: 'static>(log: &CommandLog, state: &mut S) -> Result<(), LogError> {
+    let router = crate::Router::<S>::new();
+    for (index, entry) in log.entries.iter().enumerate() {
+        if !router.contains(&entry.command)
+            && crate::__private::native_command(&entry.command).is_some()
+        {
+            return Err(LogError::Unroutable {
+                index,
+                command: entry.command.clone(),
+                state: std::any::type_name::<S>(),
+            });
+        }
+        let replayed = router
+            .dispatch(state, &entry.command, entry.args.clone())
+            .err();
+        match (&entry.error, replayed) {
+            (None, None) => {}
+            (Some(recorded), Some(replayed)) if same_failure(recorded, &replayed) => {}
+            (_, Some(replayed)) if !is_command_failure(replayed.code) => {
+                return Err(LogError::Dispatch {
+                    seq: entry.seq,
+                    diagnostic: replayed,
+                });
+            }
+            (recorded, replayed) => {
+                return Err(LogError::Mismatch {
+                    seq: entry.seq,
+                    recorded: recorded.clone().map(Box::new),
+                    replayed,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Failures a command reports once it runs, as opposed to dispatch errors.
+#[cfg(not(target_arch = "wasm32"))]
+fn is_command_failure(code: crate::DiagnosticCode) -> bool {
+    use crate::DiagnosticCode;
+
+    matches!(
+        code,
+        DiagnosticCode::CommandError | DiagnosticCode::Timeout | DiagnosticCode::Cancelled
+    )
+}
+
+/// Compares what a command reported, not where: `runtime` differs between a
+/// web recording and a native replay, and `hint` follows the webtau version.
+#[cfg(not(target_arch = "wasm32"))]
+fn same_failure(recorded: &Diagnostic, replayed: &Diagnostic) -> bool {
+    recorded.code == replayed.code
+        && recorded.message == replayed.message
+        && recorded.details == replayed.details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(seq: u64, command: &str) -> LogEntry {
+        LogEntry {
+            seq,
+            command: command.to_string(),
+            args: serde_json::json!({ "dt": 0.5 }),
+            timestamp: 1_700_000_000_000,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn jsonl_round_trips_with_header() {
+        let log = CommandLog {
+            header: LogHeader::default(),
+            entries: vec![entry(0, "tick"), entry(1, "fire")],
+        };
+        let jsonl = log.to_jsonl();
+        let mut lines = jsonl.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            format!(
+                r#"{{"format":"webtau-command-log","version":1,"webtau":"{}"}}"#,
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            r#"{"seq":0,"command":"tick","args":{"dt":0.5},"timestamp":1700000000000}"#
+        );
+        assert_eq!(CommandLog::from_jsonl(&jsonl).unwrap(), log);
+    }
+
+    #[test]
+    fn rejects_newer_or_foreign_logs() {
+        let newer = r#"{"format":"webtau-command-log","version":99,"webtau":"9.0.0"}"#;
+        assert!(matches!(
+            CommandLog::from_jsonl(newer),
+            Err(LogError::Unsupported(_))
+        ));
+        let foreign = r#"{"format":"other","version":1,"webtau":"0.7.0"}"#;
+        assert!(matches!(
+            CommandLog::from_jsonl(foreign),
+            Err(LogError::Unsupported(_))
+        ));
+        let broken = format!("{}\n{{\"seq\":", json_line(&LogHeader::default()).trim());
+        assert!(matches!(
+            CommandLog::from_jsonl(&broken),
+            Err(LogError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn completeness_follows_first_seq() {
+        let mut log = CommandLog::new();
+        assert!(log.is_complete());
+        log.entries.push(entry(3, "tick"));
+        assert!(!log.is_complete());
+    }
+}
@@ -0,0 +1,126 @@
+//! In-process command dispatch for servers, CLIs and tests.
+//!
+//! Every native `#[webtau::command]` registers a JSON entry point alongside
+//! its Tauri wrapper. [`Router`] collects them, so a command can be called by
+//! name with the same args object the frontend would pass to `invoke()`:
+//!
+//! ```rust,ignore
+//! use my_game_commands as _;
+//!
+//! let router = webtau::Router::<GameWorld>::new();
+//! let mut world = GameWorld::new();
+//! let view = router.dispatch(&mut world, "tick", serde_json::json!({ "dt": 0.016 }))?;
+//! ```
+//!
+//! Dispatch runs the command's own body, so args use its `rename_all`
+//! casing, missing `Option` args are `None`, and failures are the same
+//! [`Diagnostic`]s the Tauri wrapper returns. `async` commands are driven to
+//! completion on the calling thread; they must not rely on a Tauri or Tokio
+//! runtime being present.
+//!
+//! A command borrowing several states is routed by a router over the tuple
+//! of their types, in parameter order: `fn buy(world: &mut World, profile:
+//! &Profile, item: u32)` is dispatched by `Router::<(World, Profile)>`,
+//! against a `&mut (World, Profile)`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::__private::{RouteCommand, StatefulRoute, StatelessRoute};
+use crate::{Diagnostic, DiagnosticCode};
+
+enum Route<S> {
+    Stateful(StatefulRoute<S>),
+    Stateless(StatelessRoute),
+}
+
+/// Commands taking state `S` (and stateless commands), callable by name.
+///
+/// A router holds every command linked into the current binary that either
+/// borrows `S` or no state at all; for a tuple `S`, commands borrowing each
+/// of its field types in that order. Commands borrowing other state types
+/// are left out. An integration test links the crate under test only when
+/// it names it, hence `use my_game_commands as _;`.
+pub struct Router<S: 'static> {
+    routes: HashMap<&'static str, Route<S>>,
+}
+
+impl<S: 'static> Router<S> {
+    /// Collects the registered commands for `S`.
+    ///
+    /// # Panics
+    ///
+    /// If two of them share a name.
+    pub fn new() -> Self {
+        let mut routes = HashMap::new();
+        for command in inventory::iter::<RouteCommand>() {
+            let route = if let Some(route) = command.route.downcast_ref::<StatefulRoute<S>>() {
+                Route::Stateful(*route)
+            } else if let Some(route) = command.route.downcast_ref::<StatelessRoute>() {
+                Route::Stateless(*route)
+            } else {
+                continue;
+            };
+            if routes.insert(command.name, route).is_some() {
+                panic!(
+                    "webtau: command `{}` is registered more than once; \
+                     give one of them a different `#[command(name = ...)]`",
+                    command.name
+                );
+            }
+        }
+        Self { routes }
+    }
+
+    /// Runs `command` against `state` with a JSON args object, returning its
+    /// serialized result. `Value::Null` stands for "no args".
+    pub fn dispatch(
+        &self,
+        state: &mut S,
+        command: &str,
+        args: Value,
+    ) -> Result<Value, Box<Diagnostic>> {
+        match self.routes.get(command) {
+            Some(Route::Stateful(route)) => route(state, args),
+            Some(Route::Stateless(route)) => route(args),
+            None => Err(Box::new(Diagnostic::new(
+                DiagnosticCode::UnknownCommand,
+                command,
+                format!(
+                    "no command named `{}` takes state `{}`",
+                    command,
+                    std::any::type_name::<S>()
+                ),
+            ))),
+        }
+    }
+
+    /// Whether `command` can be dispatched.
+    pub fn contains(&self, command: &str) -> bool {
+        self.routes.contains_key(command)
+    }
+
+    /// The dispatchable command names, sorted.
+    pub fn commands(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.routes.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+}
+
+impl<S: 'static> Default for Router<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: 'static> fmt::Debug for Router<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field("state", &std::any::type_name::<S>())
+            .field("commands", &self.commands())
+            .finish()
+    }
+}
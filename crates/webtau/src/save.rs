@@ -0,0 +1,248 @@
+//! Versioned save data that keeps loading after the state layout changes.
+//!
+//! A save is an envelope `{ "version": 3, "payload": { ... } }`. A
+//! [`Schema`] knows the current version and how to upgrade each older one:
+//! migrations are plain functions that edit the payload as a
+//! `serde_json::Value` tree, so they never need the old Rust types.
+//!
+//! ```rust,ignore
+//! use serde_json::{json, Value};
+//!
+//! fn migrate_v1_to_v2(save: &mut Value) -> Result<(), String> {
+//!     // v2 split `score: [left, right]` into two fields.
+//!     let score = save["score"].take();
+//!     save["left_score"] = score[0].clone();
+//!     save["right_score"] = score[1].clone();
+//!     Ok(())
+//! }
+//!
+//! fn schema() -> webtau::save::Schema<PongGame> {
+//!     webtau::save::Schema::new(2).migration(1, migrate_v1_to_v2)
+//! }
+//!
+//! let json = schema().to_json(&game)?;
+//! let game = schema().from_json(&json)?; // runs v1 -> v2 on old saves
+//! ```
+//!
+//! Everything here is plain serde and works on native and WASM builds alike.
+//! [`Schema::store`] and [`Schema::restore`] keep saves in a
+//! [`webtau::storage`](crate::storage) backend.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::storage::Storage;
+
+/// The on-disk form of a save.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Save {
+    /// Schema version the payload is laid out in.
+    pub version: u32,
+    pub payload: Value,
+}
+
+/// Upgrades a payload from version `N` to `N + 1` in place.
+pub type Migration = fn(&mut Value) -> Result<(), String>;
+
+/// Why a save could not be written or loaded.
+///
+/// Serialized with a `kind` tag, so a command returning it rejects with a
+/// `COMMAND_ERROR` diagnostic whose `details` say what went wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SaveError {
+    /// The bytes are not a save envelope.
+    Malformed { message: String },
+    /// The save was written by a newer build.
+    TooNew { found: u32, current: u32 },
+    /// No migration upgrades saves from version `from`.
+    MissingMigration { from: u32 },
+    /// The migration from version `from` returned an error.
+    MigrationFailed { from: u32, message: String },
+    /// The (migrated) payload does not match the state type, or the state
+    /// failed to serialize.
+    Payload { message: String },
+    /// The [`Storage`] backend failed.
+    Storage { message: String },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { message } => write!(f, "not a save file: {}", message),
+            Self::TooNew { found, current } => write!(
+                f,
+                "save version {} is newer than this build's version {}",
+                found, current
+            ),
+            Self::MissingMigration { from } => {
+                write!(f, "no migration from save version {} to {}", from, from + 1)
+            }
+            Self::MigrationFailed { from, message } => write!(
+                f,
+                "migrating save version {} to {} failed: {}",
+                from,
+                from + 1,
+                message
+            ),
+            Self::Payload { message } => write!(f, "save payload: {}", message),
+            Self::Storage { message } => write!(f, "save storage: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+fn payload_error(err: impl fmt::Display) -> SaveError {
+    SaveError::Payload {
+        message: err.to_string(),
+    }
+}
+
+fn storage_error(err: std::io::Error) -> SaveError {
+    SaveError::Storage {
+        message: err.to_string(),
+    }
+}
+
+fn malformed(err: impl fmt::Display) -> SaveError {
+    SaveError::Malformed {
+        message: err.to_string(),
+    }
+}
+
+/// The current save version of `T` and the migrations leading up to it.
+pub struct Schema<T> {
+    version: u32,
+    migrations: BTreeMap<u32, Migration>,
+    _state: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned> Schema<T> {
+    /// A schema whose saves are written at `version`.
+    pub fn new(version: u32) -> Self {
+        Self {
+            version,
+            migrations: BTreeMap::new(),
+            _state: PhantomData,
+        }
+    }
+
+    /// Registers the migration from version `from` to `from + 1`.
+    ///
+    /// # Panics
+    ///
+    /// If `from` is not below the current version, or already has one.
+    pub fn migration(mut self, from: u32, migrate: Migration) -> Self {
+        assert!(
+            from < self.version,
+            "webtau: migration from save version {} is not below the current version {}",
+            from,
+            self.version
+        );
+        assert!(
+            self.migrations.insert(from, migrate).is_none(),
+            "webtau: save version {} has two migrations",
+            from
+        );
+        self
+    }
+
+    /// The version new saves are written at.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Wraps `state` in an envelope at the current version.
+    pub fn save(&self, state: &T) -> Result<Save, SaveError> {
+        Ok(Save {
+            version: self.version,
+            payload: serde_json::to_value(state).map_err(payload_error)?,
+        })
+    }
+
+    /// Upgrades `save` to the current version, running each migration in
+    /// turn, and deserializes it.
+    pub fn load(&self, save: Save) -> Result<T, SaveError> {
+        let save = self.migrate(save)?;
+        serde_json::from_value(save.payload).map_err(payload_error)
+    }
+
+    /// Upgrades `save` to the current version without deserializing it.
+    pub fn migrate(&self, mut save: Save) -> Result<Save, SaveError> {
+        if save.version > self.version {
+            return Err(SaveError::TooNew {
+                found: save.version,
+                current: self.version,
+            });
+        }
+        while save.version < self.version {
+            let from = save.version;
+            let migrate = self
+                .migrations
+                .get(&from)
+                .ok_or(SaveError::MissingMigration { from })?;
+            migrate(&mut save.payload)
+                .map_err(|message| SaveError::MigrationFailed { from, message })?;
+            save.version += 1;
+        }
+        Ok(save)
+    }
+
+    pub fn to_json(&self, state: &T) -> Result<String, SaveError> {
+        serde_json::to_string(&self.save(state)?).map_err(payload_error)
+    }
+
+    pub fn from_json(&self, json: &str) -> Result<T, SaveError> {
+        self.load(serde_json::from_str(json).map_err(malformed)?)
+    }
+
+    pub fn to_bytes(&self, state: &T) -> Result<Vec<u8>, SaveError> {
+        serde_json::to_vec(&self.save(state)?).map_err(payload_error)
+    }
+
+    pub fn from_bytes(&self, bytes: &[u8]) -> Result<T, SaveError> {
+        self.load(serde_json::from_slice(bytes).map_err(malformed)?)
+    }
+
+    /// Writes `state` to `storage` under `key`.
+    pub fn store(&self, storage: &impl Storage, key: &str, state: &T) -> Result<(), SaveError> {
+        storage
+            .put(key, &self.to_bytes(state)?)
+            .map_err(storage_error)
+    }
+
+    /// Reads and migrates the save under `key`, or `None` if there is none.
+    pub fn restore(&self, storage: &impl Storage, key: &str) -> Result<Option<T>, SaveError> {
+        match storage.get(key).map_err(storage_error)? {
+            Some(bytes) => self.from_bytes(&bytes).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Loads a [`snapshot`](crate::snapshot) from `export_state`, migrating
+    /// it from the schema version in its header. Decode the command's base64
+    /// string with [`from_base64`](crate::snapshot::from_base64) first.
+    pub fn from_snapshot(&self, bytes: &[u8]) -> Result<T, SaveError> {
+        let snapshot = crate::snapshot::decode(bytes).map_err(malformed)?;
+        self.load(Save {
+            version: snapshot.header.version,
+            payload: snapshot.state,
+        })
+    }
+}
+
+impl<T> fmt::Debug for Schema<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Schema")
+            .field("state", &std::any::type_name::<T>())
+            .field("version", &self.version)
+            .field("migrations", &self.migrations.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
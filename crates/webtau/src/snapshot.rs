@@ -0,0 +1,352 @@
+//! Whole-state snapshots for save games and debug dumps.
+//!
+//! [`snapshot_commands!`](crate::snapshot_commands) generates an
+//! `export_state` / `import_state` command pair for a state type, so the
+//! frontend can save and restore the world with the same `invoke()` calls
+//! on desktop and on the web:
+//!
+//! ```rust,ignore
+//! // commands.rs, next to the other commands (not behind a wasm32 cfg)
+//! webtau::snapshot_commands!(GameWorld, version = 3);
+//! ```
+//!
+//! ```ts
+//! const snapshot = await invoke("export_state", { encoding: "binary" });
+//! await invoke("import_state", { snapshot });
+//! ```
+//!
+//! A snapshot is a [`SnapshotHeader`] (format name, schema version,
+//! recording `webtau` version) plus the serialized state, encoded either as
+//! JSON or as MessagePack behind a short magic prefix. Importing detects the
+//! encoding, and rejects snapshots whose schema version differs from the
+//! state's current one. To upgrade old snapshots instead, read them with
+//! [`decode`] and rewrite the payload before deserializing.
+//!
+//! The commands carry snapshots as base64 strings ([`export_base64`],
+//! [`from_base64`]): raw bytes would cross `invoke()` as a JSON array of
+//! numbers, several times their size, on either target.
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::ts::{Declarations, TypeScript};
+
+/// The `format` field of every snapshot header.
+pub const FORMAT: &str = "webtau-snapshot";
+
+/// Prefix of [`Encoding::Binary`] snapshots. JSON snapshots start with `{`.
+pub const BINARY_MAGIC: &[u8; 4] = b"WTS\x01";
+
+/// How a snapshot is encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    /// UTF-8 JSON, readable in a debugger or diff.
+    #[default]
+    Json,
+    /// MessagePack, typically less than half the size of JSON.
+    Binary,
+}
+
+impl TypeScript for Encoding {
+    fn ts_type() -> String {
+        "SnapshotEncoding".to_string()
+    }
+
+    fn ts_declare(decls: &mut Declarations) {
+        decls.declare("SnapshotEncoding", |_| {
+            "export type SnapshotEncoding =\n  | \"json\"\n  | \"binary\";".to_string()
+        });
+    }
+}
+
+/// Identifies a snapshot and the state layout it was taken with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    /// Always [`FORMAT`].
+    pub format: String,
+    /// The state's schema version when the snapshot was taken.
+    pub version: u32,
+    /// Version of the `webtau` crate that took the snapshot.
+    pub webtau: String,
+}
+
+impl SnapshotHeader {
+    pub fn new(version: u32) -> Self {
+        Self {
+            format: FORMAT.to_string(),
+            version,
+            webtau: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// A decoded snapshot whose state is still an untyped value tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawSnapshot {
+    pub header: SnapshotHeader,
+    pub state: serde_json::Value,
+}
+
+/// Why a snapshot could not be taken or restored.
+///
+/// Serialized with a `kind` tag, so `import_state` rejects with a
+/// `COMMAND_ERROR` diagnostic whose `details` say what went wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SnapshotError {
+    /// The bytes are not a snapshot in either encoding.
+    Malformed { message: String },
+    /// The snapshot was taken with another schema version.
+    VersionMismatch { found: u32, expected: u32 },
+    /// The state did not match the snapshot, or failed to serialize.
+    State { message: String },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { message } => write!(f, "not a webtau snapshot: {}", message),
+            Self::VersionMismatch { found, expected } => write!(
+                f,
+                "snapshot schema version {} cannot be imported into version {}",
+                found, expected
+            ),
+            Self::State { message } => write!(f, "snapshot state: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+fn malformed(err: impl fmt::Display) -> SnapshotError {
+    SnapshotError::Malformed {
+        message: err.to_string(),
+    }
+}
+
+fn state_error(err: impl fmt::Display) -> SnapshotError {
+    SnapshotError::State {
+        message: err.to_string(),
+    }
+}
+
+#[derive(Serialize)]
+struct SnapshotRef<'a, T: ?Sized> {
+    format: &'a str,
+    version: u32,
+    webtau: &'a str,
+    state: &'a T,
+}
+
+#[derive(Deserialize)]
+struct SnapshotOwned<T> {
+    format: String,
+    version: u32,
+    webtau: String,
+    state: T,
+}
+
+impl<T> SnapshotOwned<T> {
+    fn into_parts(self) -> (SnapshotHeader, T) {
+        let header = SnapshotHeader {
+            format: self.format,
+            version: self.version,
+            webtau: self.webtau,
+        };
+        (header, self.state)
+    }
+}
+
+/// Serializes `state` with its schema `version`.
+pub fn export<T: Serialize + ?Sized>(
+    state: &T,
+    version: u32,
+    encoding: Encoding,
+) -> Result<Vec<u8>, SnapshotError> {
+    let snapshot = SnapshotRef {
+        format: FORMAT,
+        version,
+        webtau: env!("CARGO_PKG_VERSION"),
+        state,
+    };
+    match encoding {
+        Encoding::Json => serde_json::to_vec(&snapshot).map_err(state_error),
+        Encoding::Binary => {
+            let mut bytes = BINARY_MAGIC.to_vec();
+            rmp_serde::encode::write_named(&mut bytes, &snapshot).map_err(state_error)?;
+            Ok(bytes)
+        }
+    }
+}
+
+/// [`export`], base64-encoded: what `export_state` returns.
+pub fn export_base64<T: Serialize + ?Sized>(
+    state: &T,
+    version: u32,
+    encoding: Encoding,
+) -> Result<String, SnapshotError> {
+    use base64::Engine as _;
+
+    let bytes = export(state, version, encoding)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(bytes))
+}
+
+/// The snapshot bytes of a base64 string from `export_state`.
+pub fn from_base64(snapshot: &str) -> Result<Vec<u8>, SnapshotError> {
+    use base64::Engine as _;
+
+    base64::engine::general_purpose::STANDARD
+        .decode(snapshot.trim())
+        .map_err(|err| malformed(format_args!("invalid base64: {}", err)))
+}
+
+/// Restores a state exported with schema `version`, in either encoding.
+pub fn import<T: DeserializeOwned>(bytes: &[u8], version: u32) -> Result<T, SnapshotError> {
+    let header = decode_header(bytes)?;
+    if header.version != version {
+        return Err(SnapshotError::VersionMismatch {
+            found: header.version,
+            expected: version,
+        });
+    }
+    let snapshot: SnapshotOwned<T> = decode_as(bytes).map_err(state_error)?;
+    Ok(snapshot.state)
+}
+
+/// Decodes any snapshot without checking its version, e.g. to migrate an
+/// older layout.
+pub fn decode(bytes: &[u8]) -> Result<RawSnapshot, SnapshotError> {
+    decode_header(bytes)?;
+    let snapshot: SnapshotOwned<serde_json::Value> = decode_as(bytes).map_err(malformed)?;
+    let (header, state) = snapshot.into_parts();
+    Ok(RawSnapshot { header, state })
+}
+
+/// The encoding of `bytes`, judged by their first byte.
+pub fn encoding_of(bytes: &[u8]) -> Option<Encoding> {
+    if bytes.starts_with(BINARY_MAGIC) {
+        Some(Encoding::Binary)
+    } else if bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'{') {
+        Some(Encoding::Json)
+    } else {
+        None
+    }
+}
+
+fn decode_header(bytes: &[u8]) -> Result<SnapshotHeader, SnapshotError> {
+    // Unknown fields, the state among them, are skipped.
+    let header: SnapshotHeader = decode_as(bytes).map_err(malformed)?;
+    if header.format != FORMAT {
+        return Err(malformed(format_args!(
+            "unknown format `{}`",
+            header.format
+        )));
+    }
+    Ok(header)
+}
+
+fn decode_as<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+    match encoding_of(bytes) {
+        Some(Encoding::Json) => serde_json::from_slice(bytes).map_err(|err| err.to_string()),
+        Some(Encoding::Binary) => {
+            rmp_serde::from_slice(&bytes[BINARY_MAGIC.len()..]).map_err(|err| err.to_string())
+        }
+        None => Err("expected a JSON object or a binary snapshot".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct World {
+        tick: u64,
+        names: Vec<String>,
+    }
+
+    fn world() -> World {
+        World {
+            tick: 7,
+            names: vec!["ada".into(), "grace".into()],
+        }
+    }
+
+    #[test]
+    fn round_trips_in_both_encodings() {
+        for encoding in [Encoding::Json, Encoding::Binary] {
+            let bytes = export(&world(), 2, encoding).unwrap();
+            assert_eq!(encoding_of(&bytes), Some(encoding));
+            assert_eq!(import::<World>(&bytes, 2).unwrap(), world());
+
+            let text = export_base64(&world(), 2, encoding).unwrap();
+            assert_eq!(from_base64(&text).unwrap(), bytes);
+        }
+        assert!(matches!(
+            from_base64("not base64!"),
+            Err(SnapshotError::Malformed { .. })
+        ));
+    }
+
+    #[test]
+    fn json_snapshots_carry_the_header_inline() {
+        let bytes = export(&world(), 2, Encoding::Json).unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            format!(
+                r#"{{"format":"webtau-snapshot","version":2,"webtau":"{}","state":{{"tick":7,"names":["ada","grace"]}}}}"#,
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+    }
+
+    #[test]
+    fn binary_is_smaller_than_json() {
+        let json = export(&world(), 1, Encoding::Json).unwrap();
+        let binary = export(&world(), 1, Encoding::Binary).unwrap();
+        assert!(
+            binary.len() < json.len(),
+            "{} >= {}",
+            binary.len(),
+            json.len()
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions_but_decodes_them() {
+        let bytes = export(&world(), 1, Encoding::Binary).unwrap();
+        assert_eq!(
+            import::<World>(&bytes, 2),
+            Err(SnapshotError::VersionMismatch {
+                found: 1,
+                expected: 2
+            })
+        );
+        let raw = decode(&bytes).unwrap();
+        assert_eq!(raw.header.version, 1);
+        assert_eq!(raw.state["names"][1], "grace");
+    }
+
+    #[test]
+    fn rejects_garbage_and_mismatched_state() {
+        assert!(matches!(
+            import::<World>(b"save", 1),
+            Err(SnapshotError::Malformed { .. })
+        ));
+        assert!(matches!(
+            import::<World>(
+                br#"{"format":"other","version":1,"webtau":"0.7.0","state":{}}"#,
+                1
+            ),
+            Err(SnapshotError::Malformed { .. })
+        ));
+        let bytes = export(&42u32, 1, Encoding::Json).unwrap();
+        assert!(matches!(
+            import::<World>(&bytes, 1),
+            Err(SnapshotError::State { .. })
+        ));
+    }
+}
@@ -0,0 +1,556 @@
+//! Persistent key/value storage of byte blobs, the same API on every target.
+//!
+//! Game code takes a [`Storage`] and never needs a `cfg` of its own:
+//!
+//! ```rust,ignore
+//! use webtau::storage::Storage;
+//!
+//! fn save_slot(storage: &impl Storage, slot: u32, bytes: &[u8]) -> std::io::Result<()> {
+//!     storage.put(&format!("saves/slot-{}.json", slot), bytes)
+//! }
+//!
+//! let storage = webtau::storage::open("com.example.my-game").await?;
+//! save_slot(&storage, 1, &bytes)?;
+//! ```
+//!
+//! Backends:
+//!
+//! - [`FileStorage`] (native) — one file per key under a directory, by
+//!   default the platform app-data directory Tauri uses for the app
+//!   identifier.
+//! - `FsStorage` (WASM) — one file per key in the `webtau/fs` IndexedDB
+//!   store that [`VirtualPath`](crate::path::VirtualPath) reads and writes,
+//!   under `/app/data`.
+//! - [`MemoryStorage`] — an in-memory map, for tests and headless runs.
+//!
+//! Keys are `/`-separated relative paths such as `saves/slot-1.json`. Empty
+//! segments, `.` and `..`, backslashes, `:` and anything `std::path` reads
+//! as a root or drive prefix are rejected, so a key can never escape its
+//! backend's directory.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// Byte blobs stored under string keys.
+///
+/// Methods take `&self`, so one storage can be shared between systems;
+/// backends synchronize internally.
+pub trait Storage {
+    /// The value stored under `key`, or `None`.
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>>;
+
+    /// Stores `value` under `key`, replacing any previous value.
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()>;
+
+    /// Removes `key`. Removing a missing key is not an error.
+    fn delete(&self, key: &str) -> io::Result<()>;
+
+    /// Every key starting with `prefix`, sorted. `""` lists everything.
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>>;
+
+    fn contains(&self, key: &str) -> io::Result<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+}
+
+impl<S: Storage + ?Sized> Storage for &S {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        (**self).get(key)
+    }
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        (**self).put(key, value)
+    }
+    fn delete(&self, key: &str) -> io::Result<()> {
+        (**self).delete(key)
+    }
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        (**self).list(prefix)
+    }
+}
+
+impl<S: Storage + ?Sized> Storage for Box<S> {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        (**self).get(key)
+    }
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        (**self).put(key, value)
+    }
+    fn delete(&self, key: &str) -> io::Result<()> {
+        (**self).delete(key)
+    }
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        (**self).list(prefix)
+    }
+}
+
+impl<S: Storage + ?Sized> Storage for Arc<S> {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        (**self).get(key)
+    }
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        (**self).put(key, value)
+    }
+    fn delete(&self, key: &str) -> io::Result<()> {
+        (**self).delete(key)
+    }
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        (**self).list(prefix)
+    }
+}
+
+/// The persistent backend of the current target.
+#[cfg(not(target_arch = "wasm32"))]
+pub type PlatformStorage = FileStorage;
+
+/// The persistent backend of the current target.
+#[cfg(target_arch = "wasm32")]
+pub type PlatformStorage = FsStorage;
+
+/// Opens persistent storage for the app `identifier` (Tauri's
+/// `identifier`, e.g. `com.example.my-game`): its app-data directory
+/// natively, and the page's `webtau/fs` app-data directory on the web,
+/// whose entries are loaded first. Native code outside an async context
+/// can call [`FileStorage::app_data`] instead.
+pub async fn open(identifier: &str) -> io::Result<PlatformStorage> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        FileStorage::app_data(identifier)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        // The store already belongs to the page's origin.
+        let _ = identifier;
+        FsStorage::load(crate::path::app_data_dir()).await
+    }
+}
+
+/// Rejects keys that are empty, absolute, or could leave the backend's root.
+fn check_key(key: &str) -> io::Result<()> {
+    let valid = !key.is_empty()
+        && !key.contains(['\\', ':'])
+        && key
+            .split('/')
+            .all(|segment| !matches!(segment, "" | "." | ".."))
+        && std::path::Path::new(key)
+            .components()
+            .all(|component| matches!(component, std::path::Component::Normal(_)));
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "invalid storage key `{}`: use a relative path like `saves/slot-1.json`",
+                key
+            ),
+        ))
+    }
+}
+
+// ── Memory ────────────────────────────────────────────────────────────
+
+/// Keeps everything in memory. Clones share the same entries, so a test
+/// can hand one clone to the code under test and inspect another.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    entries: Arc<Mutex<BTreeMap<String, Vec<u8>>>>,
+}
+
+impl MemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, Vec<u8>>> {
+        self.entries.lock().unwrap_or_else(|p| p.into_inner())
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        check_key(key)?;
+        Ok(self.entries().get(key).cloned())
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        check_key(key)?;
+        self.entries().insert(key.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> io::Result<()> {
+        check_key(key)?;
+        self.entries().remove(key);
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        Ok(self
+            .entries()
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+// ── Files ─────────────────────────────────────────────────────────────
+
+/// One file per key under a root directory, created on first write.
+///
+/// Writes go to a temporary file that is then renamed over the old one, so
+/// a crash mid-save leaves the previous value intact.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    root: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const TEMP_SUFFIX: &str = ".webtau-tmp";
+
+/// Numbers this process's temporary files, so concurrent writes to one key
+/// never share one.
+#[cfg(not(target_arch = "wasm32"))]
+static TEMP_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileStorage {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Storage in the app-data directory Tauri's `app_data_dir()` returns
+    /// for `identifier`, the directory behind
+    /// [`path::app_data_dir`](crate::path::app_data_dir).
+    pub fn app_data(identifier: &str) -> io::Result<Self> {
+        crate::path::app_data_dir()
+            .to_path_in(identifier)
+            .map(Self::new)
+    }
+
+    pub fn root(&self) -> &std::path::Path {
+        &self.root
+    }
+
+    fn path(&self, key: &str) -> io::Result<std::path::PathBuf> {
+        check_key(key)?;
+        Ok(key
+            .split('/')
+            .fold(self.root.clone(), |path, segment| path.join(segment)))
+    }
+
+    fn walk(&self, dir: &std::path::Path, prefix: &str, keys: &mut Vec<String>) -> io::Result<()> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+                continue;
+            };
+            let key = if prefix.is_empty() {
+                name.clone()
+            } else {
+                format!("{}/{}", prefix, name)
+            };
+            if entry.file_type()?.is_dir() {
+                self.walk(&entry.path(), &key, keys)?;
+            } else if !name.ends_with(TEMP_SUFFIX) {
+                keys.push(key);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for FileStorage {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        match std::fs::read(self.path(key)?) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut temp = path.clone().into_os_string();
+        temp.push(format!(
+            ".{}-{}{}",
+            std::process::id(),
+            TEMP_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            TEMP_SUFFIX
+        ));
+        let written = std::fs::write(&temp, value).and_then(|()| std::fs::rename(&temp, &path));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&temp);
+        }
+        written
+    }
+
+    fn delete(&self, key: &str) -> io::Result<()> {
+        match std::fs::remove_file(self.path(key)?) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        let mut keys = Vec::new();
+        self.walk(&self.root, "", &mut keys)?;
+        keys.retain(|key| key.starts_with(prefix));
+        keys.sort();
+        Ok(keys)
+    }
+}
+
+// ── webtau/fs ─────────────────────────────────────────────────────────
+
+/// One file per key under a root directory of the `webtau/fs` store, the
+/// IndexedDB store (or `setFsAdapter()` override) that the JS `fs` shim and
+/// [`VirtualPath`](crate::path::VirtualPath) share.
+///
+/// The store is asynchronous and [`Storage`] is not, so
+/// [`load`](Self::load) reads every file under the root into memory. Reads
+/// are served from memory; writes update it at once and reach the store in
+/// the background, in call order. Await [`flush`](Self::flush) before the
+/// page may close.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Clone)]
+pub struct FsStorage {
+    root: crate::path::VirtualPath,
+    entries: MemoryStorage,
+    writes: std::rc::Rc<Writes>,
+}
+
+/// The background writes of an [`FsStorage`] and its clones.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug)]
+struct Writes {
+    /// Settles once the last queued write has.
+    tail: std::cell::RefCell<js_sys::Promise>,
+    /// The first write that failed since the last `flush`.
+    error: std::cell::RefCell<Option<io::Error>>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl FsStorage {
+    /// Loads every file under `root` whose path is a valid key.
+    pub async fn load(root: crate::path::VirtualPath) -> io::Result<Self> {
+        use crate::path::web;
+
+        let entries = MemoryStorage::new();
+        let dir = root.to_string();
+        if web::call(web::exists(&dir)).await?.is_truthy() {
+            let options = js_sys::Object::new();
+            js_sys::Reflect::set(&options, &"recursive".into(), &true.into())
+                .map_err(|_| io::Error::other("webtau/fs: cannot build readDir options"))?;
+            let tree = web::call(web::read_dir(&dir, &options)).await?;
+            let mut keys = Vec::new();
+            file_keys(&tree, "", &mut keys);
+            for key in keys.into_iter().filter(|key| check_key(key).is_ok()) {
+                let bytes = root.join(&key).read().await?;
+                entries.put(&key, &bytes)?;
+            }
+        }
+        Ok(Self {
+            root,
+            entries,
+            writes: std::rc::Rc::new(Writes {
+                tail: std::cell::RefCell::new(js_sys::Promise::resolve(
+                    &wasm_bindgen::JsValue::UNDEFINED,
+                )),
+                error: std::cell::RefCell::new(None),
+            }),
+        })
+    }
+
+    pub fn root(&self) -> &crate::path::VirtualPath {
+        &self.root
+    }
+
+    /// Waits for the writes made so far to reach the store, returning the
+    /// first error among the writes since the last flush.
+    pub async fn flush(&self) -> io::Result<()> {
+        let tail = self.writes.tail.borrow().clone();
+        let _ = wasm_bindgen_futures::JsFuture::from(tail).await;
+        match self.writes.error.borrow_mut().take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Queues writing `value` to `key`'s file, or removing it for `None`,
+    /// after every write queued before.
+    fn queue(&self, key: &str, value: Option<Vec<u8>>) {
+        let path = self.root.join(key);
+        let writes = self.writes.clone();
+        let previous = writes.tail.borrow().clone();
+        let write = {
+            let writes = writes.clone();
+            async move {
+                let _ = wasm_bindgen_futures::JsFuture::from(previous).await;
+                let written = match value {
+                    Some(bytes) => path.write(&bytes).await,
+                    None => path.remove().await,
+                };
+                if let Err(err) = written {
+                    writes.error.borrow_mut().get_or_insert(err);
+                }
+                Ok(wasm_bindgen::JsValue::UNDEFINED)
+            }
+        };
+        *writes.tail.borrow_mut() = wasm_bindgen_futures::future_to_promise(write);
+    }
+}
+
+/// Collects the file keys of a recursive `readDir` tree, below `prefix`.
+#[cfg(target_arch = "wasm32")]
+fn file_keys(tree: &wasm_bindgen::JsValue, prefix: &str, keys: &mut Vec<String>) {
+    let field = |entry: &wasm_bindgen::JsValue, name: &str| {
+        js_sys::Reflect::get(entry, &name.into()).unwrap_or(wasm_bindgen::JsValue::UNDEFINED)
+    };
+    for entry in js_sys::Array::from(tree).iter() {
+        let Some(name) = field(&entry, "name").as_string() else {
+            continue;
+        };
+        let key = if prefix.is_empty() {
+            name
+        } else {
+            format!("{}/{}", prefix, name)
+        };
+        if field(&entry, "isDirectory").is_truthy() {
+            file_keys(&field(&entry, "children"), &key, keys);
+        } else {
+            keys.push(key);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Storage for FsStorage {
+    fn get(&self, key: &str) -> io::Result<Option<Vec<u8>>> {
+        self.entries.get(key)
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> io::Result<()> {
+        self.entries.put(key, value)?;
+        self.queue(key, Some(value.to_vec()));
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> io::Result<()> {
+        self.entries.delete(key)?;
+        self.queue(key, None);
+        Ok(())
+    }
+
+    fn list(&self, prefix: &str) -> io::Result<Vec<String>> {
+        self.entries.list(prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exercise(storage: &dyn Storage) {
+        assert_eq!(storage.get("saves/slot-1.json").unwrap(), None);
+        storage.put("saves/slot-1.json", b"one").unwrap();
+        storage.put("saves/slot-2.json", b"two").unwrap();
+        storage.put("settings.json", b"{}").unwrap();
+        storage.put("saves/slot-1.json", b"uno").unwrap();
+
+        assert_eq!(
+            storage.get("saves/slot-1.json").unwrap().as_deref(),
+            Some(&b"uno"[..])
+        );
+        assert!(storage.contains("settings.json").unwrap());
+        assert_eq!(
+            storage.list("").unwrap(),
+            ["saves/slot-1.json", "saves/slot-2.json", "settings.json"]
+        );
+        assert_eq!(
+            storage.list("saves/").unwrap(),
+            ["saves/slot-1.json", "saves/slot-2.json"]
+        );
+
+        storage.delete("saves/slot-1.json").unwrap();
+        storage.delete("saves/slot-1.json").unwrap();
+        assert_eq!(storage.get("saves/slot-1.json").unwrap(), None);
+        assert_eq!(storage.list("saves/").unwrap(), ["saves/slot-2.json"]);
+
+        for key in [
+            "", "/abs", "a//b", "../up", "a/./b", "dir/", "a\\b", "c:", "C:/saves", "a:b/c",
+        ] {
+            let err = storage.put(key, b"x").unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{:?}", key);
+        }
+    }
+
+    #[test]
+    fn memory_storage() {
+        let storage = MemoryStorage::new();
+        exercise(&storage);
+        let shared = storage.clone();
+        storage.put("shared", b"1").unwrap();
+        assert!(shared.contains("shared").unwrap());
+    }
+
+    #[test]
+    fn file_storage() {
+        let root = std::env::temp_dir().join(format!("webtau-storage-{}", std::process::id()));
+        let storage = FileStorage::new(&root);
+        exercise(&storage);
+        assert!(root.join("saves").join("slot-2.json").is_file());
+        let leftovers = std::fs::read_dir(root.join("saves"))
+            .unwrap()
+            .filter(|entry| {
+                entry
+                    .as_ref()
+                    .unwrap()
+                    .file_name()
+                    .to_string_lossy()
+                    .ends_with(TEMP_SUFFIX)
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn file_storage_concurrent_writes_to_one_key() {
+        let root = std::env::temp_dir().join(format!("webtau-storage-race-{}", std::process::id()));
+        let storage = FileStorage::new(&root);
+        std::thread::scope(|scope| {
+            for i in 0..8u8 {
+                let storage = &storage;
+                scope.spawn(move || {
+                    for _ in 0..20 {
+                        storage.put("saves/slot-1.json", &[i; 64]).unwrap();
+                    }
+                });
+            }
+        });
+        let value = storage.get("saves/slot-1.json").unwrap().unwrap();
+        assert!(value.iter().all(|&byte| byte == value[0]));
+        assert_eq!(storage.list("").unwrap(), ["saves/slot-1.json"]);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn storages_compose_behind_pointers() {
+        let storage: Arc<dyn Storage + Send + Sync> = Arc::new(MemoryStorage::new());
+        storage.put("a", b"1").unwrap();
+        let boxed: Box<dyn Storage> = Box::new(&storage);
+        assert_eq!(boxed.list("").unwrap(), ["a"]);
+    }
+}
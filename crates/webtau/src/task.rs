@@ -0,0 +1,393 @@
+//! Long-running commands with progress and cancellation, on both targets.
+//!
+//! `#[webtau::command(task)]` turns one function into three commands. The
+//! function takes a [`TaskContext`] to report progress and notice
+//! cancellation:
+//!
+//! ```rust,ignore
+//! #[webtau::command(task)]
+//! async fn generate_map(world: &mut World, ctx: webtau::TaskContext, seed: u64) -> MapView {
+//!     for row in 0..world.height() {
+//!         if ctx.is_cancelled() {
+//!             break;
+//!         }
+//!         world.generate_row(seed, row);
+//!         ctx.report_progress(100.0 * row as f64 / world.height() as f64, "terrain");
+//!         ctx.yield_now().await;
+//!     }
+//!     world.map_view()
+//! }
+//! ```
+//!
+//! - `start_generate_map({ seed })` starts the body and returns its task id
+//!   right away.
+//! - `poll_generate_map({ id })` returns a [`TaskStatus`], the shape of
+//!   `webtau/task`'s `TaskState`.
+//! - `cancel_generate_map({ id })` asks the body to stop.
+//!
+//! Natively the body runs on a blocking worker thread, holding its states'
+//! mutexes until it returns. On WASM it runs on the event loop, starting on
+//! the turn after `start_*` returns; an `async` body hands the loop back at
+//! every [`TaskContext::yield_now`], so the page stays responsive between
+//! chunks. The state stays borrowed across those yields, and commands
+//! sharing it get a `STATE_BUSY` diagnostic until the task ends.
+//!
+//! Cancellation is cooperative: the body decides when to stop and what to
+//! leave behind. Once a cancelled body returns, polls report `cancelled`
+//! and its result is dropped. A finished task is forgotten after a poll
+//! reports its outcome. Outcomes nobody polls, say after a page reload, are
+//! kept for the newest [`MAX_UNPOLLED`] finished tasks of each command and
+//! forgotten beyond that.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Diagnostic, DiagnosticCode};
+
+/// How far a task has come, as reported through
+/// [`TaskContext::report_progress`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskProgress {
+    /// Completion percentage, 0 to 100.
+    pub percent: f64,
+    /// What the task is doing, if it said.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// A task's state as `poll_*` reports it, serialized like `webtau/task`'s
+/// `TaskState`: `{ "state": "running", "progress": { .. } }`,
+/// `{ "state": "completed", "result": .. }`, `{ "state": "cancelled" }` or
+/// `{ "state": "failed", "error": <Diagnostic> }`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// The body has not returned yet.
+    Running {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        progress: Option<TaskProgress>,
+    },
+    /// The body returned a value.
+    Completed { result: Value },
+    /// The body returned after cancellation was requested.
+    Cancelled,
+    /// The body returned an error or panicked.
+    Failed { error: Diagnostic },
+}
+
+impl TaskStatus {
+    /// Whether the task has ended.
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, Self::Running { .. })
+    }
+}
+
+#[derive(Default)]
+struct Shared {
+    cancelled: AtomicBool,
+    progress: Mutex<Option<TaskProgress>>,
+    outcome: Mutex<Option<TaskStatus>>,
+}
+
+/// A task's handle on its own progress and cancellation. See the
+/// [module docs](self).
+///
+/// Cheap to clone, so it can be handed to helpers or worker closures.
+#[derive(Clone)]
+pub struct TaskContext {
+    shared: Arc<Shared>,
+}
+
+impl TaskContext {
+    /// Records how far the task has come, for the next `poll_*`. An empty
+    /// `message` leaves it out.
+    pub fn report_progress(&self, percent: f64, message: impl Into<String>) {
+        let message = message.into();
+        *lock(&self.shared.progress) = Some(TaskProgress {
+            percent,
+            message: (!message.is_empty()).then_some(message),
+        });
+    }
+
+    /// Whether `cancel_*` was invoked for this task.
+    pub fn is_cancelled(&self) -> bool {
+        self.shared.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Hands the event loop back to the browser until its next turn on
+    /// WASM. Returns immediately natively, where the task has its own thread.
+    pub async fn yield_now(&self) {
+        #[cfg(target_arch = "wasm32")]
+        next_turn().await;
+    }
+}
+
+impl std::fmt::Debug for TaskContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskContext")
+            .field("cancelled", &self.is_cancelled())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Resolves on a later event-loop turn, after pending input and rendering.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn next_turn() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let scheduled = web_sys::window().and_then(|window| {
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 0)
+                .ok()
+        });
+        if scheduled.is_none() {
+            let _ = resolve.call0(&wasm_bindgen::JsValue::UNDEFINED);
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+// ── Registry ──────────────────────────────────────────────────────────
+
+struct Entry {
+    command: &'static str,
+    shared: Arc<Shared>,
+}
+
+/// How many finished but unpolled tasks each task command keeps. Older
+/// ones are forgotten, as if a poll had reported their outcome.
+pub const MAX_UNPOLLED: usize = 64;
+
+static TASKS: Mutex<BTreeMap<u32, Entry>> = Mutex::new(BTreeMap::new());
+static NEXT_ID: AtomicU32 = AtomicU32::new(1);
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|p| p.into_inner())
+}
+
+/// A started task, held by the generated `start_*` wrapper until the body
+/// returns. Dropping it unfinished fails the task.
+#[doc(hidden)]
+pub struct TaskRun {
+    id: u32,
+    command: &'static str,
+    shared: Arc<Shared>,
+    finished: bool,
+}
+
+/// Registers a new running task of the task command `command`.
+pub(crate) fn start(command: &'static str) -> TaskRun {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let shared = Arc::new(Shared::default());
+    lock(&TASKS).insert(
+        id,
+        Entry {
+            command,
+            shared: Arc::clone(&shared),
+        },
+    );
+    TaskRun {
+        id,
+        command,
+        shared,
+        finished: false,
+    }
+}
+
+impl TaskRun {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn context(&self) -> TaskContext {
+        TaskContext {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+
+    /// Runs `body` to completion and records its outcome. A panic fails the
+    /// task instead of leaving it running forever.
+    pub fn run(self, body: impl FnOnce() -> Result<Value, Box<Diagnostic>>) {
+        let outcome =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)).unwrap_or_else(|panic| {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "task panicked".to_string());
+                Err(Box::new(
+                    self.failure(format!("task panicked: {}", message)),
+                ))
+            });
+        self.finish(outcome);
+    }
+
+    /// Records the body's outcome.
+    pub fn finish(mut self, outcome: Result<Value, Box<Diagnostic>>) {
+        let status = match outcome {
+            _ if self.shared.cancelled.load(Ordering::Relaxed) => TaskStatus::Cancelled,
+            Ok(result) => TaskStatus::Completed { result },
+            Err(error) => TaskStatus::Failed { error: *error },
+        };
+        self.record(status);
+    }
+
+    /// Stores the task's final status and forgets the oldest unpolled
+    /// outcomes of its command beyond [`MAX_UNPOLLED`].
+    fn record(&mut self, status: TaskStatus) {
+        if std::mem::replace(&mut self.finished, true) {
+            return;
+        }
+        *lock(&self.shared.outcome) = Some(status);
+        let mut tasks = lock(&TASKS);
+        let unpolled: Vec<u32> = tasks
+            .iter()
+            .filter(|(_, entry)| {
+                entry.command == self.command && lock(&entry.shared.outcome).is_some()
+            })
+            .map(|(&id, _)| id)
+            .collect();
+        for id in &unpolled[..unpolled.len().saturating_sub(MAX_UNPOLLED)] {
+            tasks.remove(id);
+        }
+    }
+
+    fn failure(&self, message: String) -> Diagnostic {
+        Diagnostic::new(
+            DiagnosticCode::CommandError,
+            format!("start_{}", self.command),
+            message,
+        )
+    }
+}
+
+impl Drop for TaskRun {
+    fn drop(&mut self) {
+        if !self.finished {
+            let error = self.failure("task ended without a result".to_string());
+            self.record(TaskStatus::Failed { error });
+        }
+    }
+}
+
+fn unknown_task(command: &str, id: u32) -> Diagnostic {
+    Diagnostic::new(
+        DiagnosticCode::ArgsInvalid,
+        format!("poll_{}", command),
+        format!("`start_{}` has no pending task {}", command, id),
+    )
+    .with_hint(
+        "Pass the id start_* returned. A task is forgotten once a poll has \
+         reported its outcome.",
+    )
+}
+
+/// The status of task `id` of the task command `command`, forgetting the
+/// task once it has ended.
+pub(crate) fn poll(command: &'static str, id: u32) -> Result<TaskStatus, Box<Diagnostic>> {
+    let mut tasks = lock(&TASKS);
+    let entry = match tasks.get(&id) {
+        Some(entry) if entry.command == command => entry,
+        _ => return Err(Box::new(unknown_task(command, id))),
+    };
+    let finished = lock(&entry.shared.outcome).clone();
+    match finished {
+        Some(status) => {
+            tasks.remove(&id);
+            Ok(status)
+        }
+        None => Ok(TaskStatus::Running {
+            progress: lock(&entry.shared.progress).clone(),
+        }),
+    }
+}
+
+/// Asks task `id` of the task command `command` to stop. A no-op for
+/// unknown or finished tasks, like `webtau/task`'s `cancelTask()`.
+pub(crate) fn cancel(command: &'static str, id: u32) {
+    if let Some(entry) = lock(&TASKS)
+        .get(&id)
+        .filter(|entry| entry.command == command)
+    {
+        entry.shared.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn reports_progress_until_finished() {
+        let task = start("unit_progress");
+        let id = task.id();
+        task.context().report_progress(40.0, "halfway-ish");
+        assert_eq!(
+            serde_json::to_value(poll("unit_progress", id).unwrap()).unwrap(),
+            json!({ "state": "running", "progress": { "percent": 40.0, "message": "halfway-ish" } })
+        );
+
+        task.run(|| Ok(json!(7)));
+        assert_eq!(
+            poll("unit_progress", id).unwrap(),
+            TaskStatus::Completed { result: json!(7) }
+        );
+        // Forgotten once its outcome was reported.
+        let err = poll("unit_progress", id).unwrap_err();
+        assert_eq!(err.code, DiagnosticCode::ArgsInvalid);
+        assert_eq!(err.command, "poll_unit_progress");
+    }
+
+    #[test]
+    fn cancelled_tasks_drop_their_result() {
+        let task = start("unit_cancel");
+        let (id, ctx) = (task.id(), task.context());
+        cancel("other_command", id);
+        assert!(!ctx.is_cancelled());
+        cancel("unit_cancel", id);
+        assert!(ctx.is_cancelled());
+        assert!(!poll("unit_cancel", id).unwrap().is_finished());
+
+        task.run(|| Ok(Value::Null));
+        assert_eq!(poll("unit_cancel", id).unwrap(), TaskStatus::Cancelled);
+    }
+
+    #[test]
+    fn panics_and_dropped_runs_fail_the_task() {
+        let task = start("unit_panic");
+        let id = task.id();
+        task.run(|| panic!("out of tiles"));
+        let TaskStatus::Failed { error } = poll("unit_panic", id).unwrap() else {
+            panic!("expected a failed task");
+        };
+        assert_eq!(error.message, "task panicked: out of tiles");
+        assert_eq!(error.command, "start_unit_panic");
+
+        let id = start("unit_panic").id();
+        assert!(matches!(
+            poll("unit_panic", id),
+            Ok(TaskStatus::Failed { .. })
+        ));
+    }
+
+    #[test]
+    fn unpolled_outcomes_are_capped_per_command() {
+        let ids: Vec<u32> = (0..MAX_UNPOLLED + 2)
+            .map(|_| {
+                let task = start("unit_unpolled");
+                let id = task.id();
+                task.run(|| Ok(Value::Null));
+                id
+            })
+            .collect();
+        assert!(poll("unit_unpolled", ids[0]).is_err());
+        assert!(poll("unit_unpolled", ids[1]).is_err());
+        for &id in &ids[2..] {
+            assert!(poll("unit_unpolled", id).unwrap().is_finished());
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! Headless command tests.
+//!
+//! [`Harness`] owns a state value and invokes commands on it by name with a
+//! JSON args object, exactly as the frontend's `invoke()` would, through the
+//! same [`Router`] the generated code registers with. No Tauri app or browser
+//! is involved:
+//!
+//! ```rust,ignore
+//! use my_game_commands as _;
+//! use webtau::testing::{json, Harness};
+//! use webtau::DiagnosticCode;
+//!
+//! #[test]
+//! fn tick_advances_the_world() {
+//!     let mut app = Harness::new(GameWorld::new());
+//!     let view = app.invoke("tick_world", json!({ "dt": 0.5 }));
+//!     assert_eq!(view["tick"], 1);
+//!
+//!     let err = app.invoke_err("tick_world", json!({ "dt": "soon" }));
+//!     assert_eq!(err.code, DiagnosticCode::ArgsInvalid);
+//! }
+//! ```
+//!
+//! Like [`Router`], only commands borrowing the harness state type (or no
+//! state) can be invoked.
+//!
+//! # Scenarios and golden snapshots
+//!
+//! A [`Scenario`] is a list of `{ "command", "args" }` steps, e.g. a Pong
+//! rally or a Battlestation volley, stored as JSON next to the tests (or in
+//! any other serde format, such as RON, via `Scenario`'s `Deserialize` impl).
+//! [`Harness::run`] plays it through the command layer and returns a
+//! [`Transcript`] of every result, which
+//! [`assert_snapshot`](Transcript::assert_snapshot) compares with a golden
+//! file:
+//!
+//! ```rust,ignore
+//! #[test]
+//! fn rally() {
+//!     let scenario = Scenario::load("tests/scenarios/rally.json").unwrap();
+//!     Harness::new(PongGame::new())
+//!         .run(&scenario)
+//!         .assert_snapshot("tests/snapshots/rally.json");
+//! }
+//! ```
+//!
+//! A changed result fails the test with a diff. Set
+//! `WEBTAU_UPDATE_SNAPSHOTS=1` to write new snapshots or accept intentional
+//! changes; without it a missing snapshot fails too, so CI cannot pass by
+//! writing its own goldens. Failures are snapshotted by code, command,
+//! message and details: the hint wording and the runtime are left out.
+
+use std::fmt;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{Diagnostic, DiagnosticCode, Router};
+
+pub use serde_json::{json, Value};
+
+/// A state value plus the commands that operate on it.
+pub struct Harness<S: 'static> {
+    router: Router<S>,
+    state: S,
+    events: Vec<EmittedEvent>,
+    channel_items: Vec<ChannelItem>,
+}
+
+/// An event a command emitted through its [`Emitter`](crate::Emitter)
+/// parameter while a [`Harness`] invoked it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmittedEvent {
+    pub event: String,
+    pub payload: Value,
+}
+
+/// An item a command sent through a [`Channel`](crate::Channel) parameter
+/// while a [`Harness`] invoked it. `channel` is the parameter's key in the
+/// args object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelItem {
+    pub channel: String,
+    pub item: Value,
+}
+
+impl<S: 'static> Harness<S> {
+    /// Wraps `state`, as `.manage()` or a `wasm_state!` setter would.
+    pub fn new(state: S) -> Self {
+        Self {
+            router: Router::new(),
+            state,
+            events: Vec::new(),
+            channel_items: Vec::new(),
+        }
+    }
+
+    /// Invokes `command` and returns its serialized result or diagnostic.
+    pub fn try_invoke(&mut self, command: &str, args: Value) -> Result<Value, Box<Diagnostic>> {
+        let ((result, items), events) = crate::events::capture(|| {
+            crate::channel::capture(|| self.router.dispatch(&mut self.state, command, args))
+        });
+        self.channel_items.extend(
+            items
+                .into_iter()
+                .map(|(channel, item)| ChannelItem { channel, item }),
+        );
+        self.events.extend(
+            events
+                .into_iter()
+                .map(|(event, payload)| EmittedEvent { event, payload }),
+        );
+        result
+    }
+
+    /// Invokes `command` and returns its serialized result.
+    ///
+    /// # Panics
+    ///
+    /// If the command fails, with the diagnostic in the message.
+    #[track_caller]
+    pub fn invoke(&mut self, command: &str, args: Value) -> Value {
+        match self.try_invoke(command, args) {
+            Ok(value) => value,
+            Err(diagnostic) => panic!("`{}` failed: {}", command, diagnostic),
+        }
+    }
+
+    /// Invokes `command` and deserializes its result into `T`, like a typed
+    /// frontend binding.
+    ///
+    /// # Panics
+    ///
+    /// If the command fails or its result does not deserialize as `T`.
+    #[track_caller]
+    pub fn invoke_as<T: DeserializeOwned>(&mut self, command: &str, args: Value) -> T {
+        let value = self.invoke(command, args);
+        serde_json::from_value(value.clone()).unwrap_or_else(|err| {
+            panic!(
+                "`{}` returned {} which is not a `{}`: {}",
+                command,
+                value,
+                std::any::type_name::<T>(),
+                err
+            )
+        })
+    }
+
+    /// Invokes `command`, expecting it to fail, and returns the diagnostic.
+    ///
+    /// # Panics
+    ///
+    /// If the command succeeds.
+    #[track_caller]
+    pub fn invoke_err(&mut self, command: &str, args: Value) -> Diagnostic {
+        match self.try_invoke(command, args) {
+            Ok(value) => panic!("`{}` succeeded with {}, expected an error", command, value),
+            Err(diagnostic) => *diagnostic,
+        }
+    }
+
+    /// Invokes `command`, expecting it to fail with `code`, and returns the
+    /// diagnostic for further checks on its message or details.
+    ///
+    /// # Panics
+    ///
+    /// If the command succeeds or fails with another code.
+    #[track_caller]
+    pub fn assert_diagnostic(
+        &mut self,
+        command: &str,
+        args: Value,
+        code: DiagnosticCode,
+    ) -> Diagnostic {
+        let diagnostic = self.invoke_err(command, args);
+        assert_eq!(
+            diagnostic.code, code,
+            "`{}` failed with {} instead of {}: {}",
+            command, diagnostic.code, code, diagnostic.message
+        );
+        diagnostic
+    }
+
+    /// Delivers `event` to the `webtau::events::listen` handlers for `S`, as
+    /// if the frontend emitted it, and returns the first failure.
+    pub fn emit_from_frontend(
+        &mut self,
+        event: &str,
+        payload: Value,
+    ) -> Result<(), Box<Diagnostic>> {
+        let state = &mut self.state;
+        crate::events::native::deliver(
+            event,
+            &payload,
+            Some(std::any::TypeId::of::<S>()),
+            &mut |_, run| {
+                run(state);
+                crate::events::native::Reach::Ran
+            },
+        )
+    }
+
+    /// Plays every step of `scenario` in order and records the outcomes.
+    /// Failed steps are recorded too; they do not stop the scenario.
+    pub fn run(&mut self, scenario: &Scenario) -> Transcript {
+        let mut entries = Vec::new();
+        for step in &scenario.steps {
+            for _ in 0..step.repeat {
+                let outcome = self.try_invoke(&step.command, step.args.clone());
+                let (result, error) = match outcome {
+                    Ok(value) => (Some(value), None),
+                    Err(diagnostic) => (None, Some(*diagnostic)),
+                };
+                entries.push(Entry {
+                    command: step.command.clone(),
+                    args: step.args.clone(),
+                    result,
+                    error,
+                });
+            }
+        }
+        Transcript { entries }
+    }
+
+    /// The command names this harness can invoke, sorted.
+    pub fn commands(&self) -> Vec<&'static str> {
+        self.router.commands()
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Events emitted by the commands invoked so far, oldest first.
+    pub fn events(&self) -> &[EmittedEvent] {
+        &self.events
+    }
+
+    /// Returns the events emitted so far and forgets them, to check the
+    /// events of the next invocation on their own.
+    pub fn take_events(&mut self) -> Vec<EmittedEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Items sent through `Channel` parameters so far, oldest first.
+    pub fn channel_items(&self) -> &[ChannelItem] {
+        &self.channel_items
+    }
+
+    /// Returns the channel items sent so far and forgets them.
+    pub fn take_channel_items(&mut self) -> Vec<ChannelItem> {
+        std::mem::take(&mut self.channel_items)
+    }
+
+    pub fn state_mut(&mut self) -> &mut S {
+        &mut self.state
+    }
+
+    pub fn into_state(self) -> S {
+        self.state
+    }
+}
+
+impl<S: Default + 'static> Default for Harness<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: fmt::Debug + 'static> fmt::Debug for Harness<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Harness")
+            .field("state", &self.state)
+            .field("commands", &self.commands())
+            .finish()
+    }
+}
+
+// ── Scenarios ─────────────────────────────────────────────────────────
+
+/// Environment variable that makes [`Transcript::assert_snapshot`] write
+/// snapshots instead of comparing them.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "WEBTAU_UPDATE_SNAPSHOTS";
+
+/// An ordered list of command invocations. Serialized as a JSON array of
+/// steps.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Scenario {
+    pub steps: Vec<Step>,
+}
+
+/// One scenario step: `{ "command": "tick", "args": { "dt": 0.016 } }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub command: String,
+    /// The `invoke()` args object. Omitted means no args.
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub args: Value,
+    /// How many times to invoke the step in a row, for runs of identical
+    /// frames. Defaults to 1.
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub repeat: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(n: &u32) -> bool {
+    *n == 1
+}
+
+impl Scenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a step.
+    pub fn step(mut self, command: impl Into<String>, args: Value) -> Self {
+        self.steps.push(Step {
+            command: command.into(),
+            args,
+            repeat: 1,
+        });
+        self
+    }
+
+    /// Appends a step invoked `repeat` times.
+    pub fn repeat(mut self, repeat: u32, command: impl Into<String>, args: Value) -> Self {
+        self.steps.push(Step {
+            command: command.into(),
+            args,
+            repeat,
+        });
+        self
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Reads a JSON scenario file.
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+    }
+}
+
+/// The recorded outcome of every invocation in a scenario run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Transcript {
+    pub entries: Vec<Entry>,
+}
+
+/// One invocation: the serialized result, or the diagnostic it failed with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub args: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Serialized without its `hint` and `runtime`, which are read back as
+    /// [`Diagnostic::new`] fills them in.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "golden_error"
+    )]
+    pub error: Option<Diagnostic>,
+}
+
+/// The parts of a [`Diagnostic`] a golden file pins down. The hint is prose
+/// that may be reworded, and the runtime would make one scenario's snapshot
+/// differ between native and web runs.
+mod golden_error {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{Diagnostic, DiagnosticCode};
+
+    #[derive(Serialize, Deserialize)]
+    struct Golden<T> {
+        code: DiagnosticCode,
+        command: T,
+        message: T,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        details: Option<serde_json::Value>,
+    }
+
+    pub fn serialize<S: Serializer>(
+        error: &Option<Diagnostic>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        error
+            .as_ref()
+            .map(|d| Golden {
+                code: d.code,
+                command: d.command.as_str(),
+                message: d.message.as_str(),
+                details: d.details.clone(),
+            })
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Diagnostic>, D::Error> {
+        let golden = Option::<Golden<String>>::deserialize(deserializer)?;
+        Ok(golden.map(|g| {
+            let diagnostic = Diagnostic::new(g.code, g.command, g.message);
+            match g.details {
+                Some(details) => diagnostic.with_details(details),
+                None => diagnostic,
+            }
+        }))
+    }
+}
+
+impl Transcript {
+    /// The snapshot file contents: pretty-printed JSON with a trailing newline.
+    pub fn to_snapshot(&self) -> String {
+        let mut json = serde_json::to_string_pretty(self).expect("transcripts serialize to JSON");
+        json.push('\n');
+        json
+    }
+
+    /// Compares the transcript with the golden file at `path`.
+    ///
+    /// The file is written instead when [`UPDATE_SNAPSHOTS_ENV`] is set to
+    /// anything but `0`.
+    ///
+    /// # Panics
+    ///
+    /// If the snapshot differs, with a line diff in the message, if it does
+    /// not exist and updates are off, or if the file cannot be read or
+    /// written.
+    #[track_caller]
+    pub fn assert_snapshot(&self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let actual = self.to_snapshot();
+        let update = std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some_and(|v| v != "0");
+        let expected = match std::fs::read_to_string(path) {
+            Ok(expected) if !update => expected,
+            Ok(_) => return write_snapshot(path, &actual),
+            Err(_) if update => return write_snapshot(path, &actual),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => panic!(
+                "webtau: snapshot `{}` does not exist\nSet {}=1 to write it.",
+                path.display(),
+                UPDATE_SNAPSHOTS_ENV
+            ),
+            Err(err) => panic!("webtau: cannot read snapshot `{}`: {}", path.display(), err),
+        };
+        if expected.replace("\r\n", "\n") != actual {
+            panic!(
+                "webtau: snapshot `{}` does not match\n{}\nSet {}=1 to accept the new output.",
+                path.display(),
+                line_diff(&expected, &actual),
+                UPDATE_SNAPSHOTS_ENV
+            );
+        }
+    }
+}
+
+#[track_caller]
+fn write_snapshot(path: &Path, contents: &str) {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .unwrap_or_else(|err| panic!("webtau: cannot create `{}`: {}", parent.display(), err));
+    }
+    std::fs::write(path, contents).unwrap_or_else(|err| {
+        panic!(
+            "webtau: cannot write snapshot `{}`: {}",
+            path.display(),
+            err
+        )
+    });
+}
+
+/// Lines around the first difference, `-` for the snapshot and `+` for the
+/// new output.
+fn line_diff(expected: &str, actual: &str) -> String {
+    const CONTEXT: usize = 3;
+    const MAX_CHANGED: usize = 20;
+    let expected: Vec<_> = expected.lines().collect();
+    let actual: Vec<_> = actual.lines().collect();
+    let first = expected
+        .iter()
+        .zip(&actual)
+        .position(|(e, a)| e != a)
+        .unwrap_or(expected.len().min(actual.len()));
+    // Matching lines after the difference, counted from the end.
+    let suffix = expected[first..]
+        .iter()
+        .rev()
+        .zip(actual[first..].iter().rev())
+        .take_while(|(e, a)| e == a)
+        .count();
+
+    let mut out = format!("first difference at line {}:\n", first + 1);
+    for line in &expected[first.saturating_sub(CONTEXT)..first] {
+        out.push_str(&format!("  {}\n", line));
+    }
+    let removed = &expected[first..expected.len() - suffix];
+    let added = &actual[first..actual.len() - suffix];
+    for (sign, lines) in [("-", removed), ("+", added)] {
+        for line in lines.iter().take(MAX_CHANGED) {
+            out.push_str(&format!("{} {}\n", sign, line));
+        }
+        if lines.len() > MAX_CHANGED {
+            out.push_str(&format!(
+                "{} ... {} more lines\n",
+                sign,
+                lines.len() - MAX_CHANGED
+            ));
+        }
+    }
+    for line in expected[expected.len() - suffix..].iter().take(CONTEXT) {
+        out.push_str(&format!("  {}\n", line));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenario_json_round_trips() {
+        let scenario = Scenario::from_json(
+            r#"[{ "command": "get_state" }, { "command": "tick", "args": { "dt": 0.5 }, "repeat": 3 }]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            scenario,
+            Scenario::new()
+                .step("get_state", Value::Null)
+                .repeat(3, "tick", json!({ "dt": 0.5 }))
+        );
+        assert_eq!(
+            serde_json::to_value(&scenario).unwrap(),
+            json!([{ "command": "get_state" }, { "command": "tick", "args": { "dt": 0.5 }, "repeat": 3 }])
+        );
+    }
+
+    #[test]
+    fn line_diff_shows_changed_lines_with_context() {
+        let expected = "a\nb\nc\nd\ne\nf\n";
+        let actual = "a\nb\nc\nX\nY\ne\nf\n";
+        assert_eq!(
+            line_diff(expected, actual),
+            "first difference at line 4:\n  a\n  b\n  c\n- d\n+ X\n+ Y\n  e\n  f\n"
+        );
+    }
+}
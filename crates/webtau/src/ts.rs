@@ -0,0 +1,351 @@
+//! TypeScript declarations for `#[webtau::command]` signatures.
+//!
+//! Every `#[webtau::command]` records its args object and return type when
+//! built natively. [`export_commands`] renders them, together with the
+//! declarations of every type they mention, into a `.d.ts` that augments
+//! `invoke()` in the `webtau` npm package:
+//!
+//! ```rust,ignore
+//! #[derive(serde::Serialize, webtau::TypeScript)]
+//! pub struct GameView {
+//!     pub score: u32,
+//!     pub paused: bool,
+//! }
+//!
+//! // In the commands crate, e.g. `tests/bindings.rs`:
+//! use my_game_commands as _;
+//!
+//! #[test]
+//! fn export_bindings() {
+//!     webtau::ts::export_commands("../../src/bindings/commands.d.ts").unwrap();
+//! }
+//! ```
+//!
+//! Types follow serde's JSON representation, which is what Tauri returns.
+//! `#[derive(TypeScript)]` reads the same `#[serde(...)]` attributes as serde
+//! (`rename`, `rename_all`, `skip`, `flatten`, `tag`, `content`, `untagged`,
+//! `transparent`, ...), and `#[ts(type = "...")]` overrides a field's type.
+//! Command args and return types without a [`TypeScript`] impl are declared
+//! as `unknown`.
+//!
+//! Declarations are keyed by name, so two exported types must not share one.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Derives [`TypeScript`] for a struct or enum, honouring its `#[serde]`
+/// attributes.
+pub use webtau_macros::TypeScript;
+
+/// A Rust type with a TypeScript counterpart.
+pub trait TypeScript {
+    /// The TypeScript type expression used wherever this type is referenced,
+    /// e.g. `number`, `GameView` or `string[]`.
+    fn ts_type() -> String;
+
+    /// Adds the named declarations this type relies on. Derived types add
+    /// their own `export interface` / `export type`; containers forward to
+    /// their element types.
+    fn ts_declare(_decls: &mut Declarations) {}
+
+    /// Whether a field or argument of this type may be left out (`Option`).
+    fn ts_optional() -> bool {
+        false
+    }
+}
+
+/// Named TypeScript declarations, deduplicated and sorted by name.
+#[derive(Debug, Default, Clone)]
+pub struct Declarations {
+    entries: BTreeMap<String, String>,
+}
+
+impl Declarations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the declarations `T` needs and returns its type expression.
+    pub fn reference<T: TypeScript + ?Sized>(&mut self) -> String {
+        T::ts_declare(self);
+        T::ts_type()
+    }
+
+    /// Adds the declaration of `name`, unless it is already present. `render`
+    /// may reference other types, including `name` itself.
+    pub fn declare(&mut self, name: &str, render: impl FnOnce(&mut Self) -> String) {
+        if self.entries.contains_key(name) {
+            return;
+        }
+        // Placeholder first, so recursive types terminate.
+        self.entries.insert(name.to_string(), String::new());
+        let decl = render(self);
+        self.entries.insert(name.to_string(), decl);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl fmt::Display for Declarations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, decl) in self.entries.values().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            writeln!(f, "{}", decl)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders every `#[webtau::command]` linked into the current binary as a
+/// `.d.ts` module: the declarations of the types they use, a
+/// `WebtauCommands` interface keyed by command name, and an augmentation
+/// of the `webtau` package's `CommandMap` that types `invoke()`.
+///
+/// Commands are collected at link time from every crate linked into the
+/// current binary. An integration test links the crate under test only when
+/// it names it, hence `use my_game_commands as _;`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_commands() -> String {
+    let mut commands: Vec<_> = inventory::iter::<crate::__private::TsCommand>().collect();
+    commands.sort_by_key(|command| command.name);
+
+    let mut decls = Declarations::new();
+    let mut map = String::new();
+    for command in commands {
+        let signature = (command.signature)(&mut decls);
+        let args = if signature.args.is_empty() {
+            "Record<string, never>".to_string()
+        } else {
+            crate::__private::ts_object(&signature.args, false)
+        };
+        map.push_str(&format!(
+            "  {}: {{ args: {}; result: {} }};\n",
+            crate::__private::ts_key(command.name),
+            args,
+            signature.result
+        ));
+    }
+
+    let mut out = String::from("// Generated by webtau::ts::export_commands(). Do not edit.\n\n");
+    if !decls.is_empty() {
+        out.push_str(&decls.to_string());
+        out.push('\n');
+    }
+    out.push_str("export interface WebtauCommands {\n");
+    out.push_str(&map);
+    out.push_str("}\n\n");
+    out.push_str("declare module \"webtau\" {\n");
+    out.push_str("  interface CommandMap extends WebtauCommands {}\n");
+    out.push_str("}\n");
+    out
+}
+
+/// Writes [`render_commands`] to `path`, creating parent directories. The
+/// file is left untouched when its contents are already current, so dev
+/// servers watching it do not reload needlessly.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_commands(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let rendered = render_commands();
+    if std::fs::read_to_string(path).is_ok_and(|current| current == rendered) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, rendered)
+}
+
+// ── Built-in impls ────────────────────────────────────────────────────
+
+macro_rules! ts_primitive {
+    ($ts:literal: $($T:ty),*) => {
+        $(impl TypeScript for $T {
+            fn ts_type() -> String {
+                $ts.to_string()
+            }
+        })*
+    };
+}
+
+ts_primitive!("boolean": bool);
+ts_primitive!("number": i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+ts_primitive!("string": char, str, String, std::path::Path, std::path::PathBuf);
+ts_primitive!("null": ());
+ts_primitive!("unknown": serde_json::Value);
+ts_primitive!("Record<string, unknown>": serde_json::Map<String, serde_json::Value>);
+
+macro_rules! ts_transparent {
+    ($(<$($g:lifetime,)? $T:ident> $ty:ty),*) => {
+        $(impl<$($g,)? $T: TypeScript + ?Sized> TypeScript for $ty {
+            fn ts_type() -> String {
+                $T::ts_type()
+            }
+            fn ts_declare(decls: &mut Declarations) {
+                $T::ts_declare(decls)
+            }
+            fn ts_optional() -> bool {
+                $T::ts_optional()
+            }
+        })*
+    };
+}
+
+ts_transparent!(
+    <'a, T> &'a T,
+    <'a, T> &'a mut T,
+    <T> Box<T>,
+    <T> std::rc::Rc<T>,
+    <T> std::sync::Arc<T>
+);
+
+impl<T: TypeScript + ToOwned + ?Sized> TypeScript for std::borrow::Cow<'_, T> {
+    fn ts_type() -> String {
+        T::ts_type()
+    }
+    fn ts_declare(decls: &mut Declarations) {
+        T::ts_declare(decls)
+    }
+}
+
+impl<T: TypeScript> TypeScript for Option<T> {
+    fn ts_type() -> String {
+        format!("{} | null", T::ts_type())
+    }
+    fn ts_declare(decls: &mut Declarations) {
+        T::ts_declare(decls)
+    }
+    fn ts_optional() -> bool {
+        true
+    }
+}
+
+/// `T[]`, or `Array<T>` when `T` is not a single term.
+fn array_of(item: String) -> String {
+    if item.contains(' ') {
+        format!("Array<{}>", item)
+    } else {
+        format!("{}[]", item)
+    }
+}
+
+macro_rules! ts_array {
+    ($(<$T:ident $(, $N:ident)?> $ty:ty),*) => {
+        $(impl<$T: TypeScript $(, const $N: usize)?> TypeScript for $ty {
+            fn ts_type() -> String {
+                array_of($T::ts_type())
+            }
+            fn ts_declare(decls: &mut Declarations) {
+                $T::ts_declare(decls)
+            }
+        })*
+    };
+}
+
+ts_array!(
+    <T> [T],
+    <T, N> [T; N],
+    <T> Vec<T>,
+    <T> std::collections::VecDeque<T>,
+    <T> std::collections::BTreeSet<T>,
+    <T> std::collections::HashSet<T>
+);
+
+macro_rules! ts_map {
+    ($($ty:ident),*) => {
+        $(impl<K: TypeScript, V: TypeScript> TypeScript for std::collections::$ty<K, V> {
+            fn ts_type() -> String {
+                format!("Record<{}, {}>", K::ts_type(), V::ts_type())
+            }
+            fn ts_declare(decls: &mut Declarations) {
+                K::ts_declare(decls);
+                V::ts_declare(decls);
+            }
+        })*
+    };
+}
+
+ts_map!(BTreeMap, HashMap);
+
+macro_rules! ts_tuple {
+    ($(($($T:ident),+)),*) => {
+        $(impl<$($T: TypeScript),+> TypeScript for ($($T,)+) {
+            fn ts_type() -> String {
+                format!("[{}]", [$($T::ts_type()),+].join(", "))
+            }
+            fn ts_declare(decls: &mut Declarations) {
+                $($T::ts_declare(decls);)+
+            }
+        })*
+    };
+}
+
+ts_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F)
+);
+
+impl<T: TypeScript, E: TypeScript> TypeScript for Result<T, E> {
+    fn ts_type() -> String {
+        format!("{{ Ok: {} }} | {{ Err: {} }}", T::ts_type(), E::ts_type())
+    }
+    fn ts_declare(decls: &mut Declarations) {
+        T::ts_declare(decls);
+        E::ts_declare(decls);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_types() {
+        assert_eq!(u64::ts_type(), "number");
+        assert_eq!(<&str>::ts_type(), "string");
+        assert_eq!(Option::<String>::ts_type(), "string | null");
+        assert_eq!(Vec::<f32>::ts_type(), "number[]");
+        assert_eq!(Vec::<Option<u8>>::ts_type(), "Array<number | null>");
+        assert_eq!(<[i32; 3]>::ts_type(), "number[]");
+        assert_eq!(<(u8, String)>::ts_type(), "[number, string]");
+        assert_eq!(
+            std::collections::HashMap::<String, Vec<bool>>::ts_type(),
+            "Record<string, boolean[]>"
+        );
+        assert!(Option::<u8>::ts_optional());
+        assert!(Box::<Option<u8>>::ts_optional());
+        assert!(!Vec::<Option<u8>>::ts_optional());
+    }
+
+    #[test]
+    fn declare_dedupes_and_handles_recursion() {
+        let mut decls = Declarations::new();
+        decls.declare("Node", |decls| {
+            decls.declare("Node", |_| unreachable!("already being declared"));
+            "export interface Node {}".to_string()
+        });
+        decls.declare("Node", |_| unreachable!("already declared"));
+        decls.declare("Leaf", |_| "export type Leaf = null;".to_string());
+        assert_eq!(decls.len(), 2);
+        assert_eq!(
+            decls.to_string(),
+            "export type Leaf = null;\n\nexport interface Node {}\n"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Save migrations of counter and pong states shaped like the examples',
+//! with fixtures as older builds of them would have written them.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use webtau::save::{Save, SaveError, Schema};
+use webtau::snapshot::{self, Encoding};
+
+fn fixture(name: &str) -> String {
+    let path = format!(
+        "{}/tests/fixtures/save/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}", path, err))
+}
+
+// ── Counter: v1 called the field `count` ──────────────────────────────
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Counter {
+    value: i32,
+}
+
+fn counter(value: i32) -> Counter {
+    Counter { value }
+}
+
+fn migrate_counter_v1_to_v2(save: &mut Value) -> Result<(), String> {
+    let count = save["count"].take();
+    save["value"] = count;
+    save.as_object_mut()
+        .ok_or("counter save is not an object")?
+        .remove("count");
+    Ok(())
+}
+
+fn counter_schema() -> Schema<Counter> {
+    Schema::new(2).migration(1, migrate_counter_v1_to_v2)
+}
+
+#[test]
+fn counter_v1_save_loads() {
+    let counter = counter_schema()
+        .from_json(&fixture("counter-v1.json"))
+        .unwrap();
+    assert_eq!(counter, self::counter(7));
+}
+
+#[test]
+fn current_saves_round_trip_without_migrating() {
+    let schema = counter_schema();
+    let json = schema.to_json(&counter(-2)).unwrap();
+    assert_eq!(json, r#"{"version":2,"payload":{"value":-2}}"#);
+    assert_eq!(schema.from_json(&json).unwrap(), counter(-2));
+    let bytes = schema.to_bytes(&counter(4)).unwrap();
+    assert_eq!(schema.from_bytes(&bytes).unwrap(), counter(4));
+}
+
+// ── Pong: v1 nested the ball, v2 kept the score as a pair ─────────────
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PongGame {
+    ball_x: f64,
+    ball_y: f64,
+    ball_vx: f64,
+    ball_vy: f64,
+    left_y: f64,
+    right_y: f64,
+    left_score: u32,
+    right_score: u32,
+}
+
+fn migrate_pong_v1_to_v2(save: &mut Value) -> Result<(), String> {
+    let ball = save["ball"].take();
+    for (from, to) in [
+        ("x", "ball_x"),
+        ("y", "ball_y"),
+        ("vx", "ball_vx"),
+        ("vy", "ball_vy"),
+    ] {
+        save[to] = ball[from].clone();
+    }
+    save.as_object_mut()
+        .ok_or("pong save is not an object")?
+        .remove("ball");
+    Ok(())
+}
+
+fn migrate_pong_v2_to_v3(save: &mut Value) -> Result<(), String> {
+    let score = save["score"].take();
+    let [left, right] = [&score[0], &score[1]];
+    if !left.is_u64() || !right.is_u64() {
+        return Err(format!("expected a [left, right] score, got {}", score));
+    }
+    save["left_score"] = left.clone();
+    save["right_score"] = right.clone();
+    save.as_object_mut()
+        .ok_or("pong save is not an object")?
+        .remove("score");
+    Ok(())
+}
+
+fn pong_schema() -> Schema<PongGame> {
+    Schema::new(3)
+        .migration(2, migrate_pong_v2_to_v3)
+        .migration(1, migrate_pong_v1_to_v2)
+}
+
+/// A mid-rally game.
+fn pong() -> PongGame {
+    PongGame {
+        ball_x: 412.5,
+        ball_y: 288.0,
+        ball_vx: -320.0,
+        ball_vy: 40.0,
+        left_y: 300.0,
+        right_y: 260.0,
+        left_score: 3,
+        right_score: 5,
+    }
+}
+
+#[test]
+fn pong_saves_migrate_through_every_version() {
+    let schema = pong_schema();
+    assert_eq!(schema.from_json(&fixture("pong-v1.json")).unwrap(), pong());
+    assert_eq!(schema.from_json(&fixture("pong-v2.json")).unwrap(), pong());
+}
+
+#[test]
+fn migrate_upgrades_the_envelope_only() {
+    let v1: Save = serde_json::from_str(&fixture("pong-v1.json")).unwrap();
+    let v3 = pong_schema().migrate(v1).unwrap();
+    assert_eq!(v3.version, 3);
+    assert_eq!(v3.payload["left_score"], 3);
+    assert!(v3.payload.get("score").is_none());
+}
+
+#[test]
+fn snapshots_from_older_schemas_migrate() {
+    let v2 = serde_json::from_str::<Save>(&fixture("pong-v2.json")).unwrap();
+    let bytes = snapshot::export(&v2.payload, 2, Encoding::Binary).unwrap();
+    assert_eq!(pong_schema().from_snapshot(&bytes).unwrap(), pong());
+}
+
+#[test]
+fn failures_name_the_version() {
+    let schema = pong_schema();
+    assert_eq!(
+        schema.from_json(r#"{"version":4,"payload":{}}"#),
+        Err(SaveError::TooNew {
+            found: 4,
+            current: 3
+        })
+    );
+    assert_eq!(
+        schema.from_json(r#"{"version":0,"payload":{}}"#),
+        Err(SaveError::MissingMigration { from: 0 })
+    );
+    match schema.from_json(r#"{"version":2,"payload":{"score":"3-5"}}"#) {
+        Err(SaveError::MigrationFailed { from: 2, message }) => {
+            assert!(message.contains("[left, right]"), "{}", message)
+        }
+        other => panic!("expected a failed migration, got {:?}", other),
+    }
+    assert!(matches!(
+        schema.from_json("{}"),
+        Err(SaveError::Malformed { .. })
+    ));
+    assert!(matches!(
+        schema.from_json(r#"{"version":3,"payload":{"ball_x":1}}"#),
+        Err(SaveError::Payload { .. })
+    ));
+    assert_eq!(
+        serde_json::to_value(SaveError::TooNew {
+            found: 4,
+            current: 3
+        })
+        .unwrap(),
+        json!({ "kind": "too_new", "found": 4, "current": 3 })
+    );
+}
+
+#[test]
+#[should_panic(expected = "not below the current version")]
+fn migrations_must_lead_to_the_current_version() {
+    let _ = Schema::<Counter>::new(2).migration(2, migrate_counter_v1_to_v2);
+}
+
+#[test]
+fn saves_round_trip_through_storage() {
+    use webtau::storage::{MemoryStorage, Storage};
+
+    let storage = MemoryStorage::new();
+    let schema = pong_schema();
+    assert_eq!(schema.restore(&storage, "saves/pong.json").unwrap(), None);
+    schema.store(&storage, "saves/pong.json", &pong()).unwrap();
+    assert_eq!(
+        schema.restore(&storage, "saves/pong.json").unwrap(),
+        Some(pong())
+    );
+
+    storage
+        .put("saves/old.json", fixture("pong-v1.json").as_bytes())
+        .unwrap();
+    assert_eq!(
+        schema.restore(&storage, "saves/old.json").unwrap(),
+        Some(pong())
+    );
+    assert!(matches!(
+        schema.store(&storage, "../escape.json", &pong()),
+        Err(SaveError::Storage { .. })
+    ));
+}
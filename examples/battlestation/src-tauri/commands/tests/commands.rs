@@ -0,0 +1,89 @@
+// Commands register themselves at link time; naming the crate links it.
+use battlestation_commands as _;
+
+use battlestation_core::BattlestationSim;
+use webtau::testing::{json, Harness, Scenario};
+use webtau::DiagnosticCode;
+
+fn mission() -> Harness<BattlestationSim> {
+    Harness::new(BattlestationSim::new())
+}
+
+#[test]
+fn mission_starts_with_the_first_wave() {
+    let view = mission().invoke("get_mission_view", json!({}));
+    assert_eq!(view["tick"], 0);
+    assert_eq!(view["wave"], 1);
+    assert_eq!(view["integrity"], 100);
+    assert_eq!(view["mission_state"], "ACTIVE");
+    assert_eq!(view["selected_contact_id"], 1);
+
+    let types: Vec<_> = view["contacts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["enemy_type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types, ["RED_CUBE", "RED_CUBE", "ALIEN_8_BIT"]);
+}
+
+#[test]
+fn cycle_target_wraps_in_both_directions() {
+    let mut app = mission();
+    let view = app.invoke("cycle_target", json!({ "direction": 1 }));
+    assert_eq!(view["selected_contact_id"], 2);
+    app.invoke("cycle_target", json!({ "direction": -1 }));
+    let view = app.invoke("cycle_target", json!({ "direction": -1 }));
+    assert_eq!(view["selected_contact_id"], 3);
+}
+
+#[test]
+fn fire_shot_destroys_the_selected_contact() {
+    let mut app = mission();
+    let result = app.invoke("fire_shot", json!({}));
+    assert_eq!(result["hit"], true);
+    assert_eq!(result["killed"], true);
+    assert_eq!(result["score_delta"], 5);
+    assert_eq!(result["summary"], "Target #1 (RED_CUBE) destroyed. +5 points.");
+
+    let view = app.invoke("get_mission_view", json!({}));
+    assert_eq!(view["score"], 5);
+    assert_eq!(view["contacts"].as_array().unwrap().len(), 2);
+    assert_eq!(view["selected_contact_id"], 2);
+}
+
+#[test]
+fn fire_at_empty_space_misses_and_raises_an_alert() {
+    let mut app = mission();
+    let result = app.invoke("fire_at", json!({ "x": 320.0, "y": 320.0 }));
+    assert_eq!(result["hit"], false);
+    assert_eq!(result["score_delta"], 0);
+    assert_eq!(app.invoke("get_mission_view", json!({}))["alerts"], 1);
+}
+
+#[test]
+fn tick_advances_contacts_toward_the_base() {
+    let mut app = mission();
+    let view = app.invoke("tick", json!({ "dt": 0.1 }));
+    assert_eq!(view["tick"], 1);
+    assert!(view["contacts"][0]["progress"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn malformed_args_are_rejected() {
+    let mut app = mission();
+    app.assert_diagnostic("fire_at", json!({ "x": 1.0 }), DiagnosticCode::ArgsInvalid);
+    app.assert_diagnostic("tick", json!({ "dt": null }), DiagnosticCode::ArgsInvalid);
+    assert_eq!(app.state().view().tick, 0);
+}
+
+/// Replays `tests/scenarios/volley.json` and compares every result with
+/// `tests/snapshots/volley.json`. After an intentional gameplay change, rerun
+/// with `WEBTAU_UPDATE_SNAPSHOTS=1` and commit the updated snapshot.
+#[test]
+fn volley_matches_snapshot() {
+    let scenario = Scenario::load(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/scenarios/volley.json")).unwrap();
+    mission()
+        .run(&scenario)
+        .assert_snapshot(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/volley.json"));
+}
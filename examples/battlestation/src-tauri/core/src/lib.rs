@@ -7,22 +7,24 @@ const CENTER_Y: f64 = HEIGHT / 2.0;
 const MAX_ENEMIES: usize = 8;
 const INITIAL_SPAWN_INTERVAL: u64 = 30;
 
-#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, webtau::TypeScript)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum EnemyType {
     RedCube,
     HeavyRedCube,
+    // SCREAMING_SNAKE_CASE would give "ALIEN8_BIT".
+    #[serde(rename = "ALIEN_8_BIT")]
     Alien8Bit,
 }
 
-#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, webtau::TypeScript)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum MissionState {
     Active,
     Failed,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, webtau::TypeScript)]
 pub struct ContactView {
     pub id: u32,
     pub x: f64,
@@ -34,7 +36,7 @@ pub struct ContactView {
     pub selected: bool,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, webtau::TypeScript)]
 pub struct MissionView {
     pub tick: u64,
     pub score: i32,
@@ -46,7 +48,7 @@ pub struct MissionView {
     pub selected_contact_id: Option<u32>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, webtau::TypeScript)]
 pub struct FireResult {
     pub hit: bool,
     pub killed: bool,
@@ -0,0 +1,51 @@
+// Generated by webtau::ts::export_commands(). Do not edit.
+
+export interface ContactView {
+  id: number;
+  x: number;
+  y: number;
+  enemy_type: EnemyType;
+  hp: number;
+  max_hp: number;
+  progress: number;
+  selected: boolean;
+}
+
+export type EnemyType =
+  | "RED_CUBE"
+  | "HEAVY_RED_CUBE"
+  | "ALIEN_8_BIT";
+
+export interface FireResult {
+  hit: boolean;
+  killed: boolean;
+  summary: string;
+  score_delta: number;
+}
+
+export type MissionState =
+  | "ACTIVE"
+  | "FAILED";
+
+export interface MissionView {
+  tick: number;
+  score: number;
+  integrity: number;
+  alerts: number;
+  wave: number;
+  mission_state: MissionState;
+  contacts: ContactView[];
+  selected_contact_id?: number | null;
+}
+
+export interface WebtauCommands {
+  cycle_target: { args: { direction: number }; result: MissionView };
+  fire_at: { args: { x: number; y: number }; result: FireResult };
+  fire_shot: { args: Record<string, never>; result: FireResult };
+  get_mission_view: { args: Record<string, never>; result: MissionView };
+  tick: { args: { dt: number }; result: MissionView };
+}
+
+declare module "webtau" {
+  interface CommandMap extends WebtauCommands {}
+}
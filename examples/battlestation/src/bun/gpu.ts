@@ -110,7 +110,7 @@ async function main() {
     },
     hud: {
       updateMission(view) {
-        const target = view.selected_contact_id === null ? "NONE" : `#${view.selected_contact_id}`;
+        const target = view.selected_contact_id == null ? "NONE" : `#${view.selected_contact_id}`;
         window.setTitle(
           `A130 Defense | ${view.mission_state} | score ${view.score} | integrity ${view.integrity} | target ${target}`,
         );
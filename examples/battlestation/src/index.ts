@@ -16,7 +16,7 @@ function updateHud(view: import("./services/backend").MissionView): void {
   document.getElementById("alerts")!.textContent = String(view.alerts);
   document.getElementById("wave")!.textContent = String(view.wave);
   document.getElementById("target")!.textContent =
-    view.selected_contact_id === null ? "NONE" : `#${view.selected_contact_id}`;
+    view.selected_contact_id == null ? "NONE" : `#${view.selected_contact_id}`;
 }
 
 function updateProfileHud(profile: import("./services/profile").OperatorProfile): void {
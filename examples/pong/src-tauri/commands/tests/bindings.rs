@@ -0,0 +1,11 @@
+// Commands register their signatures at link time; naming the crate links it.
+use pong_commands as _;
+
+/// Regenerates the frontend's command typings. Run with
+/// `cargo test -p pong-commands --test bindings` after changing a command
+/// or a view type, and commit the updated `src/bindings/commands.d.ts`.
+#[test]
+fn export_bindings() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../../src/bindings/commands.d.ts");
+    webtau::ts::export_commands(path).unwrap();
+}
@@ -0,0 +1,78 @@
+// Commands register themselves at link time; naming the crate links it.
+use pong_commands as _;
+
+use pong_core::PongGame;
+use webtau::testing::{json, Harness, Scenario};
+use webtau::DiagnosticCode;
+
+fn game() -> Harness<PongGame> {
+    Harness::new(PongGame::new())
+}
+
+#[test]
+fn get_state_reports_the_serve() {
+    let view = game().invoke("get_state", json!({}));
+    assert_eq!(
+        view,
+        json!({
+            "ball_x": 400.0,
+            "ball_y": 300.0,
+            "left_y": 300.0,
+            "right_y": 300.0,
+            "left_score": 0,
+            "right_score": 0,
+        })
+    );
+}
+
+#[test]
+fn tick_moves_paddles_and_ball() {
+    let mut app = game();
+    let view = app.invoke("tick", json!({ "dt": 0.05, "left_input": -1, "right_input": 1 }));
+    assert_eq!(view["left_y"], 280.0);
+    assert_eq!(view["right_y"], 320.0);
+    assert_eq!(view["ball_x"], 415.0);
+
+    // Oversized frames are capped, so a tab switch cannot tunnel the ball.
+    let view = app.invoke("tick", json!({ "dt": 5.0, "left_input": 0, "right_input": 0 }));
+    assert_eq!(view["ball_x"], 430.0);
+}
+
+#[test]
+fn missed_ball_scores_for_the_other_side() {
+    let mut app = game();
+    let mut view = json!(null);
+    for _ in 0..60 {
+        // Right paddle dodges to the bottom wall, so the serve gets past it.
+        view = app.invoke("tick", json!({ "dt": 0.05, "left_input": 0, "right_input": 1 }));
+        if view["left_score"] == 1 {
+            break;
+        }
+    }
+    assert_eq!(view["left_score"], 1);
+    assert_eq!(view["right_score"], 0);
+    assert_eq!(view["ball_x"], 400.0);
+}
+
+#[test]
+fn tick_requires_snake_case_args() {
+    let mut app = game();
+    let err = app.assert_diagnostic(
+        "tick",
+        json!({ "dt": 0.05, "leftInput": 0, "rightInput": 0 }),
+        DiagnosticCode::ArgsInvalid,
+    );
+    assert_eq!(err.command, "tick");
+    app.assert_diagnostic("tick", json!({ "dt": "fast", "left_input": 0, "right_input": 0 }), DiagnosticCode::ArgsInvalid);
+}
+
+/// Replays `tests/scenarios/rally.json` and compares every result with
+/// `tests/snapshots/rally.json`. After an intentional gameplay change, rerun
+/// with `WEBTAU_UPDATE_SNAPSHOTS=1` and commit the updated snapshot.
+#[test]
+fn rally_matches_snapshot() {
+    let scenario = Scenario::load(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/scenarios/rally.json")).unwrap();
+    game()
+        .run(&scenario)
+        .assert_snapshot(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/rally.json"));
+}
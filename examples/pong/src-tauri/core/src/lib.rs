@@ -12,7 +12,7 @@ const BALL_INITIAL_SPEED: f64 = 300.0;
 const BALL_SPEED_INCREMENT: f64 = 20.0;
 const BALL_MAX_SPEED: f64 = 600.0;
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, webtau::TypeScript)]
 pub struct GameView {
     pub ball_x: f64,
     pub ball_y: f64,
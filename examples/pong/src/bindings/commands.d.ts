@@ -0,0 +1,19 @@
+// Generated by webtau::ts::export_commands(). Do not edit.
+
+export interface GameView {
+  ball_x: number;
+  ball_y: number;
+  left_y: number;
+  right_y: number;
+  left_score: number;
+  right_score: number;
+}
+
+export interface WebtauCommands {
+  get_state: { args: Record<string, never>; result: GameView };
+  tick: { args: { dt: number; left_input: number; right_input: number }; result: GameView };
+}
+
+declare module "webtau" {
+  interface CommandMap extends WebtauCommands {}
+}
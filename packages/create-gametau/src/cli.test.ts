@@ -226,7 +226,7 @@ describe("create-gametau CLI", () => {
       "utf-8",
     );
     expect(appLibRs).toContain("use my_cool_game_core::GameWorld;");
-    expect(appLibRs).toContain("use my_cool_game_commands::{");
+    expect(appLibRs).toContain("webtau::generate_handler!(my_cool_game_commands)");
 
     const indexTs = readFileSync(join(projectDir, "src", "index.ts"), "utf-8");
     expect(indexTs).toContain("./wasm/my_cool_game_wasm");
@@ -1,7 +1,3 @@
 mod commands;
 
-#[cfg(not(target_arch = "wasm32"))]
-pub use commands::{get_world_view, tick_world};
-
-#[cfg(target_arch = "wasm32")]
-pub use commands::{init, get_world_view, tick_world};
+webtau::export_commands!(commands);
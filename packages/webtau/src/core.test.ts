@@ -1,5 +1,6 @@
 import { afterEach, beforeEach, describe, expect, mock, test } from "bun:test";
 import {
+  Channel,
   configure,
   convertFileSrc,
   getProvider,
@@ -218,6 +219,45 @@ describe("invoke (web mode)", () => {
     expect(received).toEqual({ x: 1, y: 2 });
   });
 
+  test("passes channels to WASM commands as callbacks", async () => {
+    configure({
+      loadWasm: async () => ({
+        count_down: (args: { from: number; on_tick: (n: number) => void }) => {
+          for (let n = args.from; n > 0; n--) args.on_tick(n);
+        },
+      }),
+    });
+    const ticks: number[] = [];
+    const args = { from: 3, on_tick: new Channel<number>((n) => ticks.push(n)) };
+    await invoke("count_down", args);
+    expect(ticks).toEqual([3, 2, 1]);
+    expect(args.on_tick).toBeInstanceOf(Channel);
+  });
+
+  test("maps namespaced commands to their WASM export", async () => {
+    configure({
+      loadWasm: async () => ({
+        world_tick: (args: { ticks: number }) => args.ticks + 1,
+      }),
+    });
+    expect(await invoke("world.tick", { ticks: 2 })).toBe(3);
+  });
+
+  test("passes abort signals to WASM commands as is", async () => {
+    const controller = new AbortController();
+    let received: unknown;
+    configure({
+      loadWasm: async () => ({
+        bake: (args: { cancel: AbortSignal }) => {
+          received = args.cancel;
+          return 1;
+        },
+      }),
+    });
+    await invoke("bake", { cancel: controller.signal });
+    expect(received).toBe(controller.signal);
+  });
+
   test("handles promise-returning WASM functions", async () => {
     configure({
       loadWasm: async () => ({
@@ -360,6 +400,9 @@ describe("Tauri lazy auto-registration", () => {
   const tauriConvertFileSrc = mock((path: string, protocol?: string) => (
     `${protocol ?? "asset"}://localhost${path}`
   ));
+  class TauriChannel {
+    onmessage: (item: unknown) => void = () => {};
+  }
   let tauriImportCount = 0;
   let hadWindow = false;
   let previousWindow: unknown;
@@ -378,6 +421,7 @@ describe("Tauri lazy auto-registration", () => {
       return {
         invoke: tauriInvoke,
         convertFileSrc: tauriConvertFileSrc,
+        Channel: TauriChannel,
       };
     });
 
@@ -418,6 +462,36 @@ describe("Tauri lazy auto-registration", () => {
     expect(tauriInvoke).toHaveBeenNthCalledWith(2, "second_cmd", { n: 2 });
   });
 
+  test("passes channels to Tauri as IPC channels", async () => {
+    const ticks: unknown[] = [];
+    await invoke("count_down", { from: 2, on_tick: new Channel((n) => ticks.push(n)) });
+
+    const sent = tauriInvoke.mock.calls[0][1] as { from: number; on_tick: TauriChannel };
+    expect(sent.from).toBe(2);
+    expect(sent.on_tick).toBeInstanceOf(TauriChannel);
+    sent.on_tick.onmessage(2);
+    sent.on_tick.onmessage(1);
+    expect(ticks).toEqual([2, 1]);
+  });
+
+  test("cancels Tauri commands through cancel_<name> while they run", async () => {
+    const controller = new AbortController();
+    await invoke("bake", { level: 1, cancel: controller.signal });
+    const sent = tauriInvoke.mock.calls[0][1] as { level: number; cancel: number };
+    expect(sent.level).toBe(1);
+    expect(typeof sent.cancel).toBe("number");
+
+    // Settled invokes are no longer cancelled.
+    controller.abort();
+    expect(tauriInvoke).toHaveBeenCalledTimes(1);
+
+    // An already aborted signal cancels before the command starts.
+    await invoke("bake", { cancel: controller.signal });
+    const id = (tauriInvoke.mock.calls[2][1] as { cancel: number }).cancel;
+    expect(tauriInvoke).toHaveBeenNthCalledWith(2, "cancel_bake", { id });
+    expect(tauriInvoke).toHaveBeenNthCalledWith(3, "bake", { cancel: id });
+  });
+
   test("convertFileSrc delegates to Tauri provider after auto-registration", async () => {
     await invoke("boot");
 
@@ -730,6 +804,86 @@ describe("WebtauError envelope shape", () => {
     }
   });
 
+  test("diagnostic object thrown by a Rust command becomes a WebtauError", async () => {
+    const diagnostic = {
+      code: "STATE_UNINITIALIZED",
+      runtime: "wasm",
+      command: "tick_world",
+      message: "state `GameWorld` not initialized",
+      hint: "Initialize the state before invoking commands.",
+    };
+    configure({
+      loadWasm: async () => ({
+        tick_world: () => { throw diagnostic; },
+      }),
+    });
+
+    try {
+      await invoke("tick_world");
+      throw new Error("should have thrown");
+    } catch (err) {
+      expect(err).toBeInstanceOf(WebtauError);
+      const e = err as WebtauError;
+      expect(e.code).toBe("STATE_UNINITIALIZED");
+      expect(e.runtime).toBe("wasm");
+      expect(e.command).toBe("tick_world");
+      expect(e.message).toBe(diagnostic.message);
+      expect(e.hint).toBe(diagnostic.hint);
+    }
+  });
+
+  test("diagnostic object rejected by an async Rust command keeps its code", async () => {
+    configure({
+      loadWasm: async () => ({
+        save: async () => {
+          throw {
+            code: "COMMAND_ERROR",
+            runtime: "wasm",
+            command: "save",
+            message: "disk full",
+            hint: "The command returned an error; see the message for details.",
+            details: { kind: "DiskFull", needed: 10 },
+          };
+        },
+      }),
+    });
+
+    try {
+      await invoke("save", { slot: 1 });
+      throw new Error("should have thrown");
+    } catch (err) {
+      expect(err).toBeInstanceOf(WebtauError);
+      expect((err as WebtauError).code).toBe("COMMAND_ERROR");
+      expect((err as WebtauError).message).toBe("disk full");
+      expect((err as WebtauError).details).toEqual({ kind: "DiskFull", needed: 10 });
+    }
+  });
+
+  test("provider rejection with a diagnostic object keeps its code", async () => {
+    registerProvider({
+      id: "tauri",
+      invoke: async () => {
+        throw {
+          code: "ARGS_INVALID",
+          runtime: "tauri",
+          command: "tick_world",
+          message: "missing field `dt`",
+          hint: "Check the invoke() args object.",
+        };
+      },
+      convertFileSrc: (p) => p,
+    });
+
+    try {
+      await invoke("tick_world", {});
+      throw new Error("should have thrown");
+    } catch (err) {
+      expect(err).toBeInstanceOf(WebtauError);
+      expect((err as WebtauError).code).toBe("ARGS_INVALID");
+      expect((err as WebtauError).runtime).toBe("tauri");
+    }
+  });
+
   test("all DiagnosticEnvelope fields are present and typed correctly", () => {
     const err = new WebtauError({
       code: "PROVIDER_ERROR",
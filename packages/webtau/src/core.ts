@@ -8,7 +8,7 @@
  * backend (for example Electrobun).
  */
 
-import { WebtauError } from "./diagnostics.js";
+import { isDiagnosticEnvelope, WebtauError } from "./diagnostics.js";
 import type {
   CoreProvider,
   RuntimeCapabilities,
@@ -18,7 +18,7 @@ import type {
 
 export type { CoreProvider, RuntimeCapabilities, RuntimeInfo, RuntimeInfoResolver };
 export type { DiagnosticCode, DiagnosticEnvelope } from "./diagnostics.js";
-export { WebtauError } from "./diagnostics.js";
+export { isDiagnosticEnvelope, WebtauError } from "./diagnostics.js";
 
 // biome-ignore lint/suspicious/noExplicitAny: WASM modules have dynamic signatures that cannot be statically typed
 type WasmModule = Record<string, (...args: any[]) => any>;
@@ -229,6 +229,95 @@ async function getWasmModule(): Promise<WasmModule> {
   return wasmLoadPromise;
 }
 
+/**
+ * Streams items from a command that takes a `webtau::Channel<T>` argument.
+ * Pass it in the args object; every item the command sends reaches
+ * `onmessage`, on both runtimes:
+ *
+ * ```typescript
+ * const steps = new Channel<Tile>((tile) => drawStep(tile));
+ * await invoke("find_path", { from, to, steps });
+ * ```
+ *
+ * `invoke()` turns it into a Tauri `Channel` under Tauri and into a callback
+ * function for WASM commands. Registered providers receive it as is.
+ */
+export class Channel<T = unknown> {
+  constructor(public onmessage: (item: T) => void = () => {}) {}
+}
+
+/**
+ * The WASM export of a namespaced command: `#[webtau::command(name = "world.tick")]`
+ * exports `world_tick`, since JS export names cannot contain dots.
+ */
+function wasmExportName(command: string): string {
+  return command.replace(/\./g, "_");
+}
+
+/** Maps every value of `args` through `convert`, copying `args` only if a value changes. */
+function convertArgs(
+  args: Record<string, unknown> | undefined,
+  convert: (value: unknown) => unknown,
+): Record<string, unknown> | undefined {
+  if (!args) return args;
+  let converted: Record<string, unknown> | undefined;
+  for (const [key, value] of Object.entries(args)) {
+    const next = convert(value);
+    if (next !== value) {
+      converted ??= { ...args };
+      converted[key] = next;
+    }
+  }
+  return converted ?? args;
+}
+
+/**
+ * Stands in for an `AbortSignal` passed to a Tauri command's `webtau::Cancel`
+ * arg. Aborting invokes the generated `cancel_<command>` with the returned id
+ * until `release()` runs once the invoke settles.
+ */
+function tauriCancelId(
+  tauri: { invoke: (cmd: string, args?: Record<string, unknown>) => Promise<unknown> },
+  command: string,
+  signal: AbortSignal,
+  releases: (() => void)[],
+): number {
+  const id = Math.floor(Math.random() * 2 ** 32);
+  const cancel = () => {
+    tauri.invoke(`cancel_${command}`, { id }).catch(() => {});
+  };
+  if (signal.aborted) {
+    cancel();
+  } else {
+    signal.addEventListener("abort", cancel, { once: true });
+    releases.push(() => signal.removeEventListener("abort", cancel));
+  }
+  return id;
+}
+
+/**
+ * Command signatures keyed by command name. Empty by default; the `.d.ts`
+ * written by `webtau::ts::export_commands()` augments it, which gives
+ * `invoke()` typed args and results for every `#[webtau::command]`.
+ */
+// biome-ignore lint/suspicious/noEmptyInterface: filled in by module augmentation
+export interface CommandMap {}
+
+type CommandArgs<K extends keyof CommandMap> = CommandMap[K] extends { args: infer A } ? A : never;
+type CommandResult<K extends keyof CommandMap> = CommandMap[K] extends { result: infer R } ? R : never;
+/** Commands without required args may omit the args object. */
+type InvokeArgs<A> = Record<string, never> extends A ? [args?: A] : [args: A];
+/** Rejects names that `CommandMap` declares, so typed commands cannot fall back to the untyped overload. */
+type UntypedCommand<C extends string> = C extends keyof CommandMap ? never : unknown;
+
+export function invoke<K extends keyof CommandMap>(
+  command: K,
+  ...args: InvokeArgs<CommandArgs<K>>
+): Promise<CommandResult<K>>;
+export function invoke<T = unknown, C extends string = string>(
+  command: C & UntypedCommand<C>,
+  args?: Record<string, unknown>,
+): Promise<T>;
 export async function invoke<T = unknown>(
   command: string,
   args?: Record<string, unknown>,
@@ -238,6 +327,7 @@ export async function invoke<T = unknown>(
       return await registeredProvider.invoke<T>(command, args);
     } catch (err) {
       if (err instanceof WebtauError) throw err;
+      if (isDiagnosticEnvelope(err)) throw new WebtauError(err);
       throw new WebtauError({
         code: "PROVIDER_ERROR",
         runtime: registeredProvider.id,
@@ -253,7 +343,25 @@ export async function invoke<T = unknown>(
 
     const tauriProvider: CoreProvider = {
       id: "tauri",
-      invoke: (cmd, a) => mod.invoke(cmd, a),
+      invoke: async (cmd, a) => {
+        const releases: (() => void)[] = [];
+        const tauriArgs = convertArgs(a, (value) => {
+          if (value instanceof Channel) {
+            const tauriChannel = new mod.Channel();
+            tauriChannel.onmessage = (item: unknown) => value.onmessage(item);
+            return tauriChannel;
+          }
+          if (typeof AbortSignal !== "undefined" && value instanceof AbortSignal) {
+            return tauriCancelId(mod, cmd, value, releases);
+          }
+          return value;
+        });
+        try {
+          return await mod.invoke(cmd, tauriArgs);
+        } finally {
+          for (const release of releases) release();
+        }
+      },
       convertFileSrc: (path, protocol) => mod.convertFileSrc(path, protocol),
       runtimeInfo: {
         id: "tauri",
@@ -263,11 +371,17 @@ export async function invoke<T = unknown>(
     };
     registeredProvider = tauriProvider;
 
-    return tauriProvider.invoke<T>(command, args);
+    try {
+      return await tauriProvider.invoke<T>(command, args);
+    } catch (err) {
+      // Rust commands reject with a serialized `webtau::Diagnostic`.
+      if (isDiagnosticEnvelope(err)) throw new WebtauError(err);
+      throw err;
+    }
   }
 
   const wasm = await getWasmModule();
-  const fn = wasm[command];
+  const fn = wasm[command] ?? wasm[wasmExportName(command)];
 
   if (typeof fn !== "function") {
     const available = Object.keys(wasm).filter((k) => typeof wasm[k] === "function").join(", ");
@@ -281,13 +395,17 @@ export async function invoke<T = unknown>(
   }
 
   try {
-    const result = args ? fn(args) : fn();
+    const wasmArgs = convertArgs(args, (value) =>
+      value instanceof Channel ? (item: unknown) => value.onmessage(item) : value,
+    );
+    const result = wasmArgs ? fn(wasmArgs) : fn();
 
     if (result instanceof Promise) {
       try {
         return await result;
       } catch (asyncErr) {
         if (asyncErr instanceof WebtauError) throw asyncErr;
+        if (isDiagnosticEnvelope(asyncErr)) throw new WebtauError(asyncErr);
         throw new WebtauError({
           code: "PROVIDER_ERROR",
           runtime: "wasm",
@@ -301,6 +419,7 @@ export async function invoke<T = unknown>(
     return result as T;
   } catch (execErr) {
     if (execErr instanceof WebtauError) throw execErr;
+    if (isDiagnosticEnvelope(execErr)) throw new WebtauError(execErr);
     throw new WebtauError({
       code: "PROVIDER_ERROR",
       runtime: "wasm",
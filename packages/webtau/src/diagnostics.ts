@@ -11,7 +11,17 @@ export type DiagnosticCode =
   | "UNKNOWN_COMMAND"
   | "LOAD_FAILED"
   | "PROVIDER_ERROR"
-  | "PROVIDER_MISSING";
+  | "PROVIDER_MISSING"
+  // Reported by #[webtau::command] wrappers (Rust `webtau::DiagnosticCode`).
+  | "STATE_UNINITIALIZED"
+  | "STATE_BUSY"
+  | "STATE_UNREACHABLE"
+  | "STATE_AMBIGUOUS"
+  | "ARGS_INVALID"
+  | "SERIALIZE_FAILED"
+  | "COMMAND_ERROR"
+  | "TIMEOUT"
+  | "CANCELLED";
 
 export interface DiagnosticEnvelope {
   /** Machine-readable failure category. */
@@ -24,6 +34,8 @@ export interface DiagnosticEnvelope {
   message: string;
   /** Remediation hint for the developer. */
   hint: string;
+  /** Structured payload, e.g. the serialized error value a Rust command returned. */
+  details?: unknown;
 }
 
 export class WebtauError extends Error implements DiagnosticEnvelope {
@@ -31,6 +43,7 @@ export class WebtauError extends Error implements DiagnosticEnvelope {
   readonly runtime: string;
   readonly command: string;
   readonly hint: string;
+  readonly details?: unknown;
 
   constructor(envelope: DiagnosticEnvelope) {
     super(envelope.message);
@@ -39,5 +52,22 @@ export class WebtauError extends Error implements DiagnosticEnvelope {
     this.runtime = envelope.runtime;
     this.command = envelope.command;
     this.hint = envelope.hint;
+    if (envelope.details !== undefined) this.details = envelope.details;
   }
 }
+
+/**
+ * True when `value` is a plain diagnostic object, such as the one a
+ * `#[webtau::command]` wrapper throws from WASM or rejects with from Tauri.
+ */
+export function isDiagnosticEnvelope(value: unknown): value is DiagnosticEnvelope {
+  if (typeof value !== "object" || value === null) return false;
+  const v = value as Record<string, unknown>;
+  return (
+    typeof v.code === "string" &&
+    typeof v.runtime === "string" &&
+    typeof v.command === "string" &&
+    typeof v.message === "string" &&
+    typeof v.hint === "string"
+  );
+}
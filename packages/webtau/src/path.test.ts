@@ -24,6 +24,7 @@ import {
   pictureDir,
   publicDir,
   resolve,
+  resolveResource,
   resourceDir,
   sep,
   tempDir,
@@ -101,6 +102,12 @@ describe("webtau/path", () => {
     expect(await resourceDir()).toBe("/app/resources");
   });
 
+  test("resolveResource stays inside the resource directory", async () => {
+    expect(await resolveResource("levels/1.json")).toBe("/app/resources/levels/1.json");
+    expect(await resolveResource("./levels//../1.json")).toBe("/app/resources/1.json");
+    expect(await resolveResource("../../etc/passwd")).toBe("/app/resources/etc/passwd");
+  });
+
   test("tempDir returns virtual path", async () => {
     expect(await tempDir()).toBe("/app/temp");
   });
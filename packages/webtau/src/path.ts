@@ -93,6 +93,18 @@ export async function resourceDir(): Promise<string> {
   return "/app/resources";
 }
 
+/**
+ * Resolves a bundled resource by its path relative to the resource
+ * directory. `..` segments never climb above it.
+ *
+ * ```ts
+ * resolveResource("levels/1.json") // "/app/resources/levels/1.json"
+ * ```
+ */
+export async function resolveResource(path: string): Promise<string> {
+  return normalizePath(`/app/resources/${normalizePath(`/${path}`)}`);
+}
+
 /** Temporary directory. */
 export async function tempDir(): Promise<string> {
   return "/app/temp";
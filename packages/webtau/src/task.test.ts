@@ -425,3 +425,85 @@ describe("error envelope propagation", () => {
     }
   });
 });
+
+// ---------------------------------------------------------------------------
+// Backend tasks — #[webtau::command(task)]
+// ---------------------------------------------------------------------------
+
+describe("backend tasks", () => {
+  /** Stands in for the start_/poll_/cancel_ commands of one Rust task. */
+  function mockBackend(statuses: unknown[]) {
+    const calls: Array<[string, unknown]> = [];
+    registerProvider({
+      id: "mock",
+      invoke: async (command: string, args?: Record<string, unknown>) => {
+        calls.push([command, args]);
+        if (command === "start_generate_map") return 7;
+        if (command === "poll_generate_map") return statuses.shift();
+        return null;
+      },
+      convertFileSrc: (p) => p,
+    });
+    return calls;
+  }
+
+  test("starts through start_<command> and polls poll_<command>", async () => {
+    const calls = mockBackend([
+      { state: "running", progress: { percent: 40, message: "terrain" } },
+      { state: "completed", result: { tiles: 64 } },
+    ]);
+
+    const taskId = await startTask("generate_map", { seed: 3 }, { backend: true });
+    expect(await pollTask(taskId)).toEqual({
+      state: "running",
+      progress: { percent: 40, message: "terrain" },
+    });
+    expect(await pollTask(taskId)).toEqual({ state: "completed", result: { tiles: 64 } });
+    // Terminal states are cached; the backend is not polled again.
+    expect(await pollTask(taskId)).toEqual({ state: "completed", result: { tiles: 64 } });
+
+    expect(calls).toEqual([
+      ["start_generate_map", { seed: 3 }],
+      ["poll_generate_map", { id: 7 }],
+      ["poll_generate_map", { id: 7 }],
+    ]);
+  });
+
+  test("failed backend tasks surface the diagnostic as a WebtauError", async () => {
+    mockBackend([
+      {
+        state: "failed",
+        error: {
+          code: "COMMAND_ERROR",
+          runtime: "wasm",
+          command: "start_generate_map",
+          message: "out of tiles",
+          hint: "The command returned an error; see the message for details.",
+        },
+      },
+    ]);
+
+    const taskId = await startTask("generate_map", {}, { backend: true });
+    const status = await pollTask(taskId);
+    expect(status.state).toBe("failed");
+    if (status.state === "failed") {
+      expect(status.error).toBeInstanceOf(WebtauError);
+      expect(status.error.code).toBe("COMMAND_ERROR");
+      expect(status.error.message).toBe("out of tiles");
+    }
+  });
+
+  test("cancelTask invokes cancel_<command> and waits for the backend", async () => {
+    const calls = mockBackend([{ state: "running" }, { state: "cancelled" }]);
+
+    const taskId = await startTask("generate_map", {}, { backend: true });
+    await cancelTask(taskId);
+    await cancelTask(taskId);
+    expect(calls.filter(([command]) => command === "cancel_generate_map")).toEqual([
+      ["cancel_generate_map", { id: 7 }],
+    ]);
+
+    expect((await pollTask(taskId)).state).toBe("running");
+    expect((await pollTask(taskId)).state).toBe("cancelled");
+  });
+});
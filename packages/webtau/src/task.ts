@@ -12,10 +12,14 @@
  * const status = await pollTask(taskId);
  * if (status.state === "completed") console.log(status.result);
  * ```
+ *
+ * Commands declared with `#[webtau::command(task)]` run in the background
+ * on the Rust side and report progress; start them with `{ backend: true }`
+ * and pollTask()/cancelTask() go through their `poll_*`/`cancel_*` commands.
  */
 
 import { invoke } from "./core.js";
-import { WebtauError } from "./diagnostics.js";
+import { type DiagnosticEnvelope, WebtauError } from "./diagnostics.js";
 
 // ── Public types ────────────────────────────────────────────────────────────
 
@@ -45,14 +49,34 @@ export interface StartTaskOptions {
    * ```
    */
   onCancel?: () => void | Promise<void>;
+  /**
+   * `command` is a `#[webtau::command(task)]`: invoke `start_<command>`
+   * and track the task through `poll_<command>` and `cancel_<command>`.
+   * Backend tasks report progress, and stay "running" after cancelTask()
+   * until the Rust body has stopped.
+   *
+   * ```ts
+   * const taskId = await startTask("generate_map", { seed: 7 }, { backend: true });
+   * ```
+   */
+  backend?: boolean;
 }
 
+/** What a `poll_<command>` command returns. */
+type BackendTaskStatus<T> =
+  | { state: "running"; progress?: TaskProgress }
+  | { state: "completed"; result: T }
+  | { state: "cancelled" }
+  | { state: "failed"; error: DiagnosticEnvelope };
+
 // ── Internal task registry ──────────────────────────────────────────────────
 
 interface TaskEntry<T> {
   status: TaskState<T>;
   cancelled: boolean;
   onCancel?: () => void | Promise<void>;
+  /** Set for `#[webtau::command(task)]` tasks: the command and Rust task id. */
+  backend?: { command: string; id: number };
 }
 
 const taskRegistry = new Map<string, TaskEntry<unknown>>();
@@ -70,6 +94,9 @@ let nextTaskId = 1;
  * The optional `options.onCancel` callback is invoked when cancelTask()
  * is called, allowing the consumer to propagate cancellation to the
  * backend (e.g. by invoking a cancel command).
+ *
+ * With `options.backend`, the returned Promise rejects if `start_<command>`
+ * does, e.g. with an ARGS_INVALID diagnostic.
  */
 export async function startTask<T>(
   command: string,
@@ -82,6 +109,14 @@ export async function startTask<T>(
     cancelled: false,
     onCancel: options?.onCancel,
   };
+
+  if (options?.backend) {
+    const id = await invoke<number>(`start_${command}`, args);
+    entry.backend = { command, id };
+    taskRegistry.set(taskId, entry as TaskEntry<unknown>);
+    return taskId;
+  }
+
   taskRegistry.set(taskId, entry as TaskEntry<unknown>);
 
   invoke<T>(command, args).then(
@@ -120,6 +155,8 @@ export async function startTask<T>(
  *
  * Returns a Promise that resolves immediately with the current TaskState.
  * Terminal states (completed, cancelled, failed) are permanent.
+ *
+ * Backend tasks are polled through `poll_<command>` until they end.
  */
 export async function pollTask<T>(taskId: string): Promise<TaskState<T>> {
   const entry = taskRegistry.get(taskId);
@@ -132,6 +169,13 @@ export async function pollTask<T>(taskId: string): Promise<TaskState<T>> {
       hint: "Ensure startTask() was called and the taskId is correct.",
     });
   }
+  if (entry.backend && entry.status.state === "running") {
+    const { command, id } = entry.backend;
+    const status = await invoke<BackendTaskStatus<T>>(`poll_${command}`, { id });
+    entry.status = (
+      status.state === "failed" ? { state: "failed", error: new WebtauError(status.error) } : status
+    ) as TaskState<unknown>;
+  }
   return entry.status as TaskState<T>;
 }
 
@@ -142,6 +186,8 @@ export async function pollTask<T>(taskId: string): Promise<TaskState<T>> {
  * If the task has already completed, this is a no-op.
  * If the task has not yet completed, the status transitions to "cancelled"
  * and any future invoke() resolution is ignored.
+ * Backend tasks are cancelled through `cancel_<command>` instead, and
+ * report "cancelled" from pollTask() once the Rust body has stopped.
  *
  * If `onCancel` was provided in startTask options, it is called to
  * propagate cancellation to the backend. This ensures the backend
@@ -153,7 +199,11 @@ export async function cancelTask(taskId: string): Promise<void> {
   if (entry.cancelled) return;
 
   entry.cancelled = true;
-  if (entry.status.state === "running") {
+  if (entry.backend) {
+    if (entry.status.state === "running") {
+      await invoke(`cancel_${entry.backend.command}`, { id: entry.backend.id });
+    }
+  } else if (entry.status.state === "running") {
     entry.status = { state: "cancelled" };
   }
 